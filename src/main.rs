@@ -1,31 +1,476 @@
 mod sync;
 
-use crate::sync::cli::Cli;
-use crate::sync::client::{execute_download, execute_list, execute_upload};
+use crate::sync::admin::{
+    execute_admin_codes, execute_admin_gc, execute_admin_revoke, execute_admin_token_create, execute_admin_token_list,
+    execute_admin_token_revoke, execute_admin_usage, AdminOptions,
+};
+use crate::sync::cli::{parse_size, Cli};
+use crate::sync::bump::execute_bump;
+use crate::sync::bundle::{execute_export_bundle, execute_import};
+use crate::sync::catalog::execute_catalog;
+use crate::sync::ci;
+use crate::sync::cli::{AdminCommand, AdminTokenCommand, CacheCommand, ConfigCommand, CredentialsCommand};
+use crate::sync::client::{
+    default_jobs, execute_cache_clear, execute_download_all, execute_list, execute_upload, read_codes_file,
+    DownloadOptions, ListOptions, PackOptions, UploadOptions,
+};
+use crate::sync::config::{
+    execute_config_edit, execute_config_get, execute_config_list, execute_config_set, Config,
+};
+use crate::sync::conflicts::execute_conflicts;
+use crate::sync::credentials;
+use crate::sync::encoding;
+use crate::sync::export::{execute_export, ExportOptions};
+use crate::sync::git;
+use crate::sync::http::{init_api_token, init_connections, init_http3, init_network, init_unix_socket, IpFamily, NetworkOptions};
+use crate::sync::info::execute_info;
+use crate::sync::mcp::{execute_mcp, McpOptions};
+use crate::sync::migrate::execute_migrate;
+use crate::sync::notify::notify_result;
+use crate::sync::pick::execute_pick;
+use crate::sync::pin;
+use crate::sync::plugin::execute_plugin;
+use crate::sync::prune::{self, execute_prune};
+use crate::sync::redact::Redactor;
+use crate::sync::rename::execute_rename;
+use crate::sync::renew::execute_renew;
+use crate::sync::restore::execute_restore;
+use crate::sync::schedule::{execute_schedule, ScheduleOptions};
+use crate::sync::server_error;
+use crate::sync::show::execute_show;
+use crate::sync::summary;
+use crate::sync::table::CellFormat;
+use crate::sync::telemetry::{self, execute_report};
+use crate::sync::temp::install_ctrl_c_handler;
+use crate::sync::ui::execute_ui;
+use crate::sync::update::execute_self_update;
+use crate::sync::validate::execute_validate;
+use crate::sync::verify::{execute_verify, execute_verify_archive};
+use crate::sync::ws;
+use anyhow::Context;
 use clap::Parser;
+use std::path::PathBuf;
 
 #[tokio::main]
 async fn main() {
     // 解析命令行参数
     let cli = Cli::parse();
 
-    if let Err(e) = run_sync_client(cli).await {
+    // 中断时清理临时归档
+    install_ctrl_c_handler();
+
+    let command = telemetry::command_name(&cli.command);
+    let summary_json = cli.summary_json.clone();
+    // MCP 服务常驻运行，没有命令结束的时刻可以写入结果
+    if summary_json.is_some() && command == "mcp" {
+        eprintln!("❌ 错误: --summary-json cannot be used with mcp / --summary-json 不能用于 mcp");
+        std::process::exit(2);
+    }
+    ci::init(cli.ci, cli.yes);
+    summary::start();
+    let result = run_sync_client(cli).await;
+    // MCP 服务的 stdout 是协议通道，不输出汇总
+    if command != "mcp" {
+        summary::finish(&command, summary_json.as_deref(), result.as_ref().err());
+    }
+    telemetry::record(&command, result.as_ref().err()).await;
+    if let Err(e) = result {
         eprintln!("❌ 错误: {}", e);
-        std::process::exit(1);
+        std::process::exit(server_error::exit_code(&e));
     }
 }
 
 async fn run_sync_client(cli: Cli) -> Result<(), anyhow::Error> {
+    // config 命令不依赖配置文件能被正确读取，格式错误时也能用 config edit 修复
+    if let crate::sync::cli::Command::Config { action } = &cli.command {
+        return match action {
+            ConfigCommand::Get { key } => execute_config_get(key),
+            ConfigCommand::Set { key, value } => execute_config_set(key, value),
+            ConfigCommand::List => execute_config_list(),
+            ConfigCommand::Edit => execute_config_edit(),
+        };
+    }
+
+    let config = Config::load()?;
+    let jobs = cli.jobs.unwrap_or_else(default_jobs).max(1);
+    let notify = cli.notify || config.notify;
+    // 插件和 MCP 子进程收到原始地址（包括 unix://），其余命令经由 init_unix_socket 转换后的地址访问服务器
+    let servers = if cli.server.is_empty() {
+        config.server.clone().into_iter().collect()
+    } else {
+        cli.server.clone()
+    };
+    if servers.len() > 1 {
+        if !matches!(cli.command, crate::sync::cli::Command::Upload { .. }) {
+            return Err(anyhow::anyhow!("-s/--server can only be repeated for upload / 只有 upload 可以指定多个 -s/--server"));
+        }
+        if servers.iter().any(|s| s.starts_with("unix://")) {
+            return Err(anyhow::anyhow!(
+                "unix:// servers cannot be combined with other servers / unix:// 服务器不能与其他服务器同时使用"
+            ));
+        }
+    }
+    let server_arg = servers.first().cloned();
+    let server = init_unix_socket(server_arg.clone())?;
+    init_api_token(
+        cli.token.clone(),
+        || credentials::stored_token(server_arg.as_deref()),
+        config.token.clone(),
+    );
+    ws::init(cli.websocket || config.websocket);
+    git::init(config.git_history);
+    telemetry::init(config.telemetry);
+    init_http3(cli.http3 || config.http3);
+    init_connections(cli.connections);
+    encoding::init(cli.upload_encoding.unwrap_or(config.upload_encoding));
+    pin::init(cli.trust_server_cert);
+    init_network(NetworkOptions {
+        family: match (cli.ipv4, cli.ipv6) {
+            (true, _) => Some(IpFamily::V4),
+            (_, true) => Some(IpFamily::V6),
+            _ => None,
+        },
+        resolve: cli.resolve.clone(),
+    });
+    let config_max_file_size = config
+        .max_file_size
+        .as_deref()
+        .map(parse_size)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!("Invalid max_file_size in config / 配置中的 max_file_size 无效: {}", e))?;
+    let redactor = Redactor::new(&config.redact)?;
+
     match cli.command {
-        crate::sync::cli::Command::Upload { dir } => {
-            execute_upload(dir, cli.server).await?;
+        crate::sync::cli::Command::Upload {
+            dirs,
+            archive_format,
+            compression_level,
+            max_part_size,
+            max_file_size,
+            keep_archive,
+            delta,
+            exclude,
+            skills,
+            names,
+            gitignore,
+            whole_dir,
+            code,
+            alias,
+            include_private,
+            strict,
+        } => {
+            let options = UploadOptions {
+                pack: PackOptions {
+                    format: archive_format,
+                    compression_level: compression_level.or(config.compression_level),
+                    jobs,
+                    base: None,
+                    filter: config.filter.clone(),
+                    redact: redactor.clone(),
+                },
+                max_part_size,
+                keep: keep_archive,
+                delta,
+                exclude,
+                skills,
+                names,
+                gitignore,
+                whole_dir: whole_dir || config.whole_dir,
+                max_file_size: max_file_size.or(config_max_file_size),
+                code,
+                alias,
+                skip_unchanged: false,
+                include_private,
+                policy: strict.then(|| config.policy.clone()),
+                hooks: config.hooks.clone(),
+            };
+            let servers = if servers.len() > 1 { servers } else { server.into_iter().collect() };
+            let result = execute_upload(dirs_or_config(dirs, &config)?, servers, options).await;
+            notify_result(notify, "Upload", &result);
+            result?;
+            prune::auto(&config.retention);
+        }
+        crate::sync::cli::Command::Download {
+            mut codes,
+            codes_file,
+            dir,
+            keep_archive,
+            archive_out,
+            no_cache,
+            include,
+            on_conflict,
+        } => {
+            let options = DownloadOptions {
+                jobs,
+                keep: keep_archive,
+                archive_out,
+                no_cache,
+                include,
+                on_conflict,
+                hooks: config.hooks.clone(),
+            };
+            if let Some(path) = codes_file {
+                codes.extend(read_codes_file(&path)?);
+            }
+            let result = execute_download_all(codes, dir, server, options).await;
+            notify_result(notify, "Download", &result);
+            result?;
+            prune::auto(&config.retention);
+        }
+        crate::sync::cli::Command::List {
+            dirs,
+            format,
+            columns,
+            wide,
+            truncate,
+            paths,
+            stale,
+            interactive,
+        } => {
+            if interactive {
+                let upload = config_upload_options(&config, jobs, config_max_file_size, &redactor);
+                execute_pick(dirs_or_config(dirs, &config)?, server, upload).await?;
+            } else {
+                let options = ListOptions {
+                    format,
+                    columns,
+                    cells: CellFormat {
+                        truncate: (!wide).then_some(truncate),
+                        paths,
+                    },
+                    style: config.list.clone(),
+                    stale,
+                };
+                execute_list(dirs_or_config(dirs, &config)?, options)?;
+            }
+        }
+        crate::sync::cli::Command::Ui { dirs } => {
+            let upload = config_upload_options(&config, jobs, config_max_file_size, &redactor);
+            let download = DownloadOptions {
+                jobs,
+                keep: None,
+                archive_out: None,
+                no_cache: false,
+                include: Vec::new(),
+                on_conflict: Default::default(),
+                hooks: config.hooks.clone(),
+            };
+            execute_ui(dirs_or_config(dirs, &config)?, server, upload, download).await?;
+        }
+        crate::sync::cli::Command::Pick { dirs } => {
+            let upload = config_upload_options(&config, jobs, config_max_file_size, &redactor);
+            execute_pick(dirs_or_config(dirs, &config)?, server, upload).await?;
+        }
+        crate::sync::cli::Command::Conflicts { code, no_cache } => {
+            execute_conflicts(code, server, jobs, no_cache).await?;
+        }
+        crate::sync::cli::Command::Show { code, no_cache } => {
+            execute_show(code, server, jobs, no_cache).await?;
+        }
+        crate::sync::cli::Command::Renew { code, expire } => {
+            execute_renew(code, expire, server).await?;
+        }
+        crate::sync::cli::Command::Sync {
+            every,
+            dir,
+            push,
+            pull,
+            code,
+            alias,
+        } => {
+            let upload = UploadOptions {
+                code,
+                alias,
+                skip_unchanged: true,
+                ..config_upload_options(&config, jobs, config_max_file_size, &redactor)
+            };
+            let download = DownloadOptions {
+                jobs,
+                keep: None,
+                archive_out: None,
+                no_cache: false,
+                include: Vec::new(),
+                on_conflict: Default::default(),
+                hooks: config.hooks.clone(),
+            };
+            let options = ScheduleOptions {
+                every,
+                dir,
+                push: push.then_some(upload),
+                pull: pull.map(|code| (code, download)),
+                notify,
+                summary_json: cli.summary_json.clone(),
+            };
+            execute_schedule(server, options).await?;
+        }
+        crate::sync::cli::Command::Cache { action } => match action {
+            CacheCommand::Clear => execute_cache_clear()?,
+        },
+        crate::sync::cli::Command::Prune { dry_run, keep_last, max_age, max_size } => {
+            let limits = config.retention.limits()?;
+            let limits = prune::Limits {
+                keep_last: keep_last.or(limits.keep_last),
+                max_age: max_age.or(limits.max_age),
+                max_size: max_size.or(limits.max_size),
+            };
+            execute_prune(limits, dry_run)?;
+        }
+        crate::sync::cli::Command::Report { json } => execute_report(json)?,
+        crate::sync::cli::Command::Config { .. } => unreachable!("handled before loading the config"),
+        crate::sync::cli::Command::Credentials { action } => match action {
+            CredentialsCommand::Set { profile } => credentials::execute_credentials_set(profile)?,
+            CredentialsCommand::Get { profile, show } => credentials::execute_credentials_get(profile, show)?,
+            CredentialsCommand::Delete { profile } => credentials::execute_credentials_delete(profile)?,
+        },
+        crate::sync::cli::Command::Verify { dirs } => {
+            execute_verify(dirs_or_config(dirs, &config)?)?;
         }
-        crate::sync::cli::Command::Download { code, dir } => {
-            execute_download(code, dir, cli.server).await?;
+        crate::sync::cli::Command::VerifyArchive { archive } => {
+            execute_verify_archive(&archive)?;
         }
-        crate::sync::cli::Command::List { dir } => {
-            execute_list(dir)?;
+        crate::sync::cli::Command::Rename { old_name, new_name, dirs } => {
+            execute_rename(dirs_or_config(dirs, &config)?, old_name, new_name)?;
+        }
+        crate::sync::cli::Command::Info { name, history, dirs } => {
+            execute_info(dirs_or_config(dirs, &config)?, name, history)?;
+        }
+        crate::sync::cli::Command::Git { dirs, args } => {
+            let code = git::execute_git(dirs_or_config(dirs, &config)?, args)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        crate::sync::cli::Command::Restore { name, from, list, dirs } => {
+            execute_restore(dirs_or_config(dirs, &config)?, name, from, list)?;
+        }
+        crate::sync::cli::Command::Validate { schema, dirs } => {
+            execute_validate(dirs_or_config(dirs, &config)?, schema)?;
+        }
+        crate::sync::cli::Command::Bump { name, level, dirs } => {
+            execute_bump(dirs_or_config(dirs, &config)?, name, level)?;
+        }
+        crate::sync::cli::Command::Migrate { archives, dry_run } => {
+            execute_migrate(archives, dry_run)?;
+        }
+        crate::sync::cli::Command::SelfUpdate { check, force } => {
+            execute_self_update(check, force).await?;
+        }
+        crate::sync::cli::Command::Admin { admin_token, action } => {
+            let options = AdminOptions {
+                server,
+                token: admin_token,
+            };
+            match action {
+                AdminCommand::Gc { dry_run } => execute_admin_gc(options, dry_run).await?,
+                AdminCommand::Codes { limit } => execute_admin_codes(options, limit).await?,
+                AdminCommand::Usage => execute_admin_usage(options).await?,
+                AdminCommand::Revoke { code } => execute_admin_revoke(options, code).await?,
+                AdminCommand::Tokens { action } => match action {
+                    AdminTokenCommand::List => execute_admin_token_list(options).await?,
+                    AdminTokenCommand::Create { name, quota } => {
+                        execute_admin_token_create(options, name, quota).await?
+                    }
+                    AdminTokenCommand::Revoke { id } => execute_admin_token_revoke(options, id).await?,
+                },
+            }
+        }
+        crate::sync::cli::Command::Catalog { dirs, out } => {
+            execute_catalog(dirs_or_config(dirs, &config)?, out)?;
+        }
+        crate::sync::cli::Command::Export {
+            dirs,
+            out,
+            name,
+            version,
+            description,
+            author,
+            skills,
+            marketplace,
+            bundle,
+            include_private,
+        } => {
+            if let Some(bundle) = bundle {
+                let pack = PackOptions {
+                    format: Default::default(),
+                    compression_level: config.compression_level,
+                    jobs,
+                    base: None,
+                    filter: config.filter.clone(),
+                    redact: redactor.clone(),
+                };
+                execute_export_bundle(dirs_or_config(dirs, &config)?, &bundle, &skills, include_private, pack)?;
+            } else {
+                let options = ExportOptions {
+                    out: out.context("--out is required / 缺少 --out")?,
+                    name: name.context("--name is required / 缺少 --name")?,
+                    version,
+                    description,
+                    author,
+                    skills,
+                    marketplace,
+                    include_private,
+                };
+                execute_export(dirs_or_config(dirs, &config)?, options)?;
+            }
+        }
+        crate::sync::cli::Command::Import {
+            bundle,
+            require_signature,
+            trusted_keys,
+        } => {
+            execute_import(&bundle, require_signature, &trusted_keys)?;
+        }
+        crate::sync::cli::Command::Mcp => {
+            execute_mcp(McpOptions {
+                server: server_arg,
+                jobs,
+            })
+            .await?;
+        }
+        crate::sync::cli::Command::External(args) => {
+            let code = execute_plugin(args, server_arg, jobs)?;
+            if code != 0 {
+                std::process::exit(code);
+            }
         }
     }
     Ok(())
 }
+
+/// 未指定 --dir 时使用配置（或 SKILLS_SYNC_DIRS）中的扫描目录，都没有时为空（使用默认目录）
+fn dirs_or_config(dirs: Vec<PathBuf>, config: &Config) -> Result<Vec<PathBuf>, anyhow::Error> {
+    if dirs.is_empty() {
+        config.scan_dirs()
+    } else {
+        Ok(dirs)
+    }
+}
+
+/// 只由配置决定的上传参数（定时同步和 pick 使用，不对应 upload 的命令行选项）
+fn config_upload_options(config: &Config, jobs: usize, max_file_size: Option<u64>, redactor: &Redactor) -> UploadOptions {
+    UploadOptions {
+        pack: PackOptions {
+            format: Default::default(),
+            compression_level: config.compression_level,
+            jobs,
+            base: None,
+            filter: config.filter.clone(),
+            redact: redactor.clone(),
+        },
+        max_part_size: None,
+        keep: None,
+        delta: false,
+        exclude: Vec::new(),
+        skills: Vec::new(),
+        names: Vec::new(),
+        gitignore: false,
+        whole_dir: config.whole_dir,
+        max_file_size,
+        code: None,
+        alias: None,
+        skip_unchanged: false,
+        include_private: false,
+        policy: None,
+        hooks: config.hooks.clone(),
+    }
+}
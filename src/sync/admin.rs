@@ -0,0 +1,300 @@
+use anyhow::{Context, Result};
+use comfy_table::{presets::UTF8_FULL, ContentArrangement, Table};
+use indicatif::HumanBytes;
+use reqwest::{Client, Method, RequestBuilder, StatusCode, Url};
+use serde_json::{json, Value};
+
+use crate::sync::api;
+use crate::sync::client::resolve_code;
+use crate::sync::http;
+use crate::sync::pin;
+
+/// 未通过 --admin-token 指定时读取的环境变量
+pub const ADMIN_TOKEN_ENV: &str = "SKILLS_SYNC_ADMIN_TOKEN";
+
+/// 管理命令的连接参数
+pub struct AdminOptions {
+    pub server: Option<String>,
+    pub token: Option<String>,
+}
+
+/// 自建服务器的管理接口（/sync/admin/*），使用管理员令牌鉴权
+struct AdminClient {
+    client: Client,
+    server_url: String,
+    /// 管理接口的基础地址（{server}/sync/admin）
+    base_url: Url,
+    token: String,
+}
+
+impl AdminClient {
+    async fn connect(options: AdminOptions) -> Result<Self> {
+        let token = options
+            .token
+            .or_else(|| std::env::var(ADMIN_TOKEN_ENV).ok())
+            .filter(|t| !t.is_empty())
+            .with_context(|| {
+                format!(
+                    "Admin token required: pass --admin-token or set {} / 需要管理员令牌：使用 --admin-token 或设置 {}",
+                    ADMIN_TOKEN_ENV, ADMIN_TOKEN_ENV
+                )
+            })?;
+        // 不根据地理位置自动选择服务器，避免把管理员令牌发给非自建的服务器
+        let server_url = options.server.filter(|s| !s.is_empty()).context(
+            "Admin commands need an explicit server: pass --server or set server in the config / 管理命令需要明确的服务器：使用 --server 或在配置中设置 server",
+        )?;
+        let mut base_url = Url::parse(&server_url)
+            .ok()
+            .filter(|u| !u.cannot_be_a_base())
+            .with_context(|| format!("Invalid server URL / 无效的服务器地址: {}", server_url))?;
+        base_url
+            .path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid server URL / 无效的服务器地址: {}", server_url))?
+            .pop_if_empty()
+            .extend(["sync", "admin"]);
+        println!("🌐 Using server / 使用服务器: {}", server_url);
+        http::negotiate_http3(&server_url).await;
+        api::negotiate(&server_url).await?;
+        Ok(AdminClient {
+            client: http::client()?,
+            server_url,
+            base_url,
+            token,
+        })
+    }
+
+    /// 构造管理请求，路径段逐个转义（令牌 ID 等参数中的 / ? # 不会改变请求的接口）
+    fn request(&self, method: Method, segments: &[&str]) -> RequestBuilder {
+        let mut url = self.base_url.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.extend(segments);
+        }
+        self.client.request(method, url).bearer_auth(&self.token)
+    }
+
+    /// 发送请求并返回响应中的 body 字段
+    async fn send(&self, request: RequestBuilder) -> Result<Value> {
+        let response = http::with_version(request)
+            .send()
+            .await
+            .context("Admin request failed / 管理请求失败")?;
+        pin::check(&response)?;
+        let status = response.status();
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                return Err(anyhow::anyhow!("Admin token rejected / 管理员令牌无效"));
+            }
+            StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED => {
+                return Err(anyhow::anyhow!(
+                    "Server does not support admin commands / 服务器不支持管理命令: {}",
+                    self.server_url
+                ));
+            }
+            _ if !status.is_success() => {
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(anyhow::anyhow!("Admin request failed / 管理请求失败: {} - {}", status, error_text));
+            }
+            _ => {}
+        }
+        let result: Value = response.json().await.context("Failed to parse response / 解析响应失败")?;
+        Ok(api::payload(&self.server_url, &result).clone())
+    }
+}
+
+/// 执行服务器垃圾回收：清理过期业务码、无引用的存储对象和超出配额的数据
+///
+/// dry_run 时只报告可回收的空间，不删除任何数据
+pub async fn execute_admin_gc(options: AdminOptions, dry_run: bool) -> Result<()> {
+    let admin = AdminClient::connect(options).await?;
+    if dry_run {
+        println!("🔍 Dry run, nothing will be deleted / 演练模式，不会删除任何数据");
+    }
+
+    let body = admin
+        .send(admin.request(Method::POST, &["gc"]).json(&json!({ "dry_run": dry_run })))
+        .await?;
+
+    let count = |key: &str| body[key].as_u64().unwrap_or(0);
+    let bytes = |key: &str| HumanBytes(body[key].as_u64().unwrap_or(0)).to_string();
+
+    let mut table = new_table(vec!["Category / 类别", "Items / 数量", "Size / 大小"]);
+    table.add_row(vec![
+        "Expired codes / 过期业务码".to_string(),
+        count("expired_codes").to_string(),
+        bytes("expired_bytes"),
+    ]);
+    table.add_row(vec![
+        "Orphaned blobs / 无引用数据".to_string(),
+        count("orphaned_blobs").to_string(),
+        bytes("orphaned_bytes"),
+    ]);
+    table.add_row(vec![
+        "Over quota / 超出配额".to_string(),
+        count("over_quota_codes").to_string(),
+        bytes("over_quota_bytes"),
+    ]);
+    println!("{}", table);
+
+    let total = HumanBytes(body["reclaimed_bytes"].as_u64().unwrap_or(0));
+    if dry_run {
+        println!("♻️  Reclaimable / 可回收: {}", total);
+    } else {
+        println!("✅ Reclaimed / 已回收: {}", total);
+    }
+    Ok(())
+}
+
+/// 新建显示表格
+fn new_table(header: Vec<&str>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+    table
+}
+
+/// 读取字符串字段，缺失时显示 "-"
+fn text(value: &Value, key: &str) -> String {
+    match &value[key] {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => "-".to_string(),
+    }
+}
+
+/// 读取字节数字段并以易读形式显示
+fn size(value: &Value, key: &str) -> String {
+    value[key].as_u64().map(|b| HumanBytes(b).to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/// 列出服务器上的所有业务码
+pub async fn execute_admin_codes(options: AdminOptions, limit: usize) -> Result<()> {
+    let admin = AdminClient::connect(options).await?;
+    let body = admin
+        .send(admin.request(Method::GET, &["codes"]).query(&[("limit", limit)]))
+        .await?;
+
+    let codes = body["codes"].as_array().cloned().unwrap_or_default();
+    if codes.is_empty() {
+        println!("📭 No codes on the server / 服务器上没有业务码");
+        return Ok(());
+    }
+
+    let mut table = new_table(vec![
+        "Code / 业务码",
+        "Alias / 别名",
+        "Owner / 所有者",
+        "Size / 大小",
+        "Downloads / 下载次数",
+        "Created / 创建时间",
+        "Expires / 过期时间",
+    ]);
+    for code in &codes {
+        table.add_row(vec![
+            text(code, "code"),
+            text(code, "alias"),
+            text(code, "owner"),
+            size(code, "size"),
+            text(code, "downloads"),
+            text(code, "created_at"),
+            text(code, "expires_at"),
+        ]);
+    }
+    println!("{}", table);
+
+    let total = body["total"].as_u64().unwrap_or(codes.len() as u64);
+    println!("📊 Showing {} of {} codes / 显示 {} 个，共 {} 个业务码", codes.len(), total, codes.len(), total);
+    Ok(())
+}
+
+/// 查看存储用量（总量及每个令牌的用量和配额）
+pub async fn execute_admin_usage(options: AdminOptions) -> Result<()> {
+    let admin = AdminClient::connect(options).await?;
+    let body = admin.send(admin.request(Method::GET, &["usage"])).await?;
+
+    println!("📦 Codes / 业务码: {}", text(&body, "codes"));
+    println!("🧱 Blobs / 存储对象: {}", text(&body, "blobs"));
+    println!("💾 Stored / 已用存储: {}", size(&body, "bytes"));
+    if body["capacity"].is_u64() {
+        println!("🗄️  Capacity / 总容量: {}", size(&body, "capacity"));
+    }
+
+    let users = body["users"].as_array().cloned().unwrap_or_default();
+    if !users.is_empty() {
+        let mut table = new_table(vec!["Token / 令牌", "Codes / 业务码", "Used / 已用", "Quota / 配额"]);
+        for user in &users {
+            table.add_row(vec![text(user, "name"), text(user, "codes"), size(user, "bytes"), size(user, "quota")]);
+        }
+        println!("{}", table);
+    }
+    Ok(())
+}
+
+/// 吊销业务码，别名先解析为实际业务码
+pub async fn execute_admin_revoke(options: AdminOptions, code: String) -> Result<()> {
+    let admin = AdminClient::connect(options).await?;
+    let (code, _) = resolve_code(&admin.server_url, &code).await?;
+    admin
+        .send(admin.request(Method::POST, &["revoke"]).json(&json!({ "code": code })))
+        .await?;
+    println!("🗑️  Code revoked / 业务码已吊销: {}", code);
+    Ok(())
+}
+
+/// 列出 API 令牌
+pub async fn execute_admin_token_list(options: AdminOptions) -> Result<()> {
+    let admin = AdminClient::connect(options).await?;
+    let body = admin.send(admin.request(Method::GET, &["tokens"])).await?;
+
+    let tokens = body["tokens"].as_array().cloned().unwrap_or_default();
+    if tokens.is_empty() {
+        println!("📭 No API tokens / 没有 API 令牌");
+        return Ok(());
+    }
+
+    let mut table = new_table(vec![
+        "ID",
+        "Name / 名称",
+        "Quota / 配额",
+        "Used / 已用",
+        "Created / 创建时间",
+        "Last used / 最近使用",
+    ]);
+    for token in &tokens {
+        table.add_row(vec![
+            text(token, "id"),
+            text(token, "name"),
+            size(token, "quota"),
+            size(token, "bytes"),
+            text(token, "created_at"),
+            text(token, "last_used_at"),
+        ]);
+    }
+    println!("{}", table);
+    Ok(())
+}
+
+/// 签发新的 API 令牌，令牌明文只在此时显示一次
+pub async fn execute_admin_token_create(options: AdminOptions, name: String, quota: Option<u64>) -> Result<()> {
+    let admin = AdminClient::connect(options).await?;
+    let body = admin
+        .send(admin.request(Method::POST, &["tokens"]).json(&json!({ "name": name, "quota": quota })))
+        .await?;
+
+    let token = body["token"]
+        .as_str()
+        .context("Token not found in response / 响应中未找到令牌")?;
+    println!("🔑 API token for {} (ID {}) / {} 的 API 令牌:", name, text(&body, "id"), name);
+    println!("{}", token);
+    println!("⚠️  Store it now, it will not be shown again / 请立即保存，之后不会再显示");
+    Ok(())
+}
+
+/// 吊销 API 令牌
+pub async fn execute_admin_token_revoke(options: AdminOptions, id: String) -> Result<()> {
+    let admin = AdminClient::connect(options).await?;
+    admin.send(admin.request(Method::DELETE, &["tokens", &id])).await?;
+    println!("🗑️  API token revoked / API 令牌已吊销: {}", id);
+    Ok(())
+}
@@ -0,0 +1,185 @@
+//! 与同步服务器协商 API 版本：首次访问某个服务器时请求 GET /sync/version，
+//! 版本不兼容时直接给出“客户端过旧 / 服务器过旧”的提示，而不是在上传、下载的中途才因响应格式不符而失败；
+//! 兼容的服务器则按协商出的版本解析响应（见 payload）
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::sync::grpc;
+use crate::sync::http;
+
+/// 客户端使用的服务器 API 版本
+pub const API_VERSION: u32 = 1;
+
+/// 客户端能使用的最低服务器 API 版本
+const MIN_SERVER_API_VERSION: u32 = 1;
+
+/// 查询版本的超时时间
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 请求中告知服务器客户端 API 版本的头，服务器可据此按客户端能理解的格式响应
+pub const API_VERSION_HEADER: &str = "X-Skills-Sync-Api";
+
+/// 服务器 -> 协商结果；每个进程对每个服务器只查询一次
+static NEGOTIATED: OnceLock<Mutex<HashMap<String, Negotiated>>> = OnceLock::new();
+
+/// 与一个服务器的协商结果
+#[derive(Debug, Clone)]
+struct Negotiated {
+    /// 服务器的 API 版本，没有 /sync/version 的旧版服务器按 v1 处理
+    version: u32,
+    /// 服务器公布的功能列表，None 表示未公布
+    capabilities: Option<Vec<String>>,
+}
+
+/// GET /sync/version 的响应
+#[derive(Debug, Deserialize)]
+struct ServerApi {
+    /// 服务器实现的 API 版本
+    api_version: u32,
+    /// 服务器仍接受的最低客户端 API 版本
+    #[serde(default = "default_min_client_api")]
+    min_client_api: u32,
+    /// 服务器支持的可选功能（如 "websocket"）
+    #[serde(default)]
+    capabilities: Vec<String>,
+}
+
+fn default_min_client_api() -> u32 {
+    1
+}
+
+/// 检查客户端和服务器的 API 版本是否兼容
+fn compatibility(server: &ServerApi) -> Result<()> {
+    if server.min_client_api > API_VERSION {
+        return Err(anyhow::anyhow!(
+            "Client too old: server requires API v{}, this client speaks v{}; run `skills-sync self-update` / 客户端过旧：服务器要求 API v{}，当前客户端为 v{}，请运行 `skills-sync self-update`",
+            server.min_client_api,
+            API_VERSION,
+            server.min_client_api,
+            API_VERSION
+        ));
+    }
+    if server.api_version < MIN_SERVER_API_VERSION {
+        return Err(anyhow::anyhow!(
+            "Server too old: server speaks API v{}, this client requires at least v{}; upgrade the server or use an older client / 服务器过旧：服务器为 API v{}，当前客户端至少需要 v{}，请升级服务器或使用旧版客户端",
+            server.api_version,
+            MIN_SERVER_API_VERSION,
+            server.api_version,
+            MIN_SERVER_API_VERSION
+        ));
+    }
+    Ok(())
+}
+
+/// 请求服务器的版本信息；请求失败、服务器没有该接口（如 404）或响应无法解析时返回 None，按旧版服务器处理；
+/// 服务器证书与记录不一致时报错
+async fn fetch(server_url: &str) -> Result<Option<ServerApi>> {
+    let client = http::client_builder().timeout(VERSION_TIMEOUT).build()?;
+    let Some(response) = http::try_send(client.get(format!("{}/sync/version", server_url))).await? else {
+        return Ok(None);
+    };
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+    let result: serde_json::Value = response.json().await.unwrap_or_default();
+    Ok(serde_json::from_value(unwrap(1, &result).clone()).ok())
+}
+
+/// 首次访问服务器时协商 API 版本，不兼容时报错；gRPC 服务器的接口由 proto 定义，不做协商，
+/// 网络错误不在这里报告（之后的实际请求会给出更具体的错误）
+pub async fn negotiate(server_url: &str) -> Result<()> {
+    let negotiated = NEGOTIATED.get_or_init(Default::default);
+    if grpc::is_grpc_url(server_url) || negotiated.lock().unwrap().contains_key(server_url) {
+        return Ok(());
+    }
+    let result = match fetch(server_url).await? {
+        Some(server) => {
+            compatibility(&server)?;
+            Negotiated { version: server.api_version, capabilities: Some(server.capabilities) }
+        }
+        None => Negotiated { version: 1, capabilities: None },
+    };
+    negotiated.lock().unwrap().insert(server_url.to_string(), result);
+    Ok(())
+}
+
+fn negotiated(server_url: &str) -> Option<Negotiated> {
+    NEGOTIATED.get()?.lock().unwrap().get(server_url).cloned()
+}
+
+/// 与服务器协商出的 API 版本，未协商时按 v1 处理
+pub fn version(server_url: &str) -> u32 {
+    negotiated(server_url).map_or(1, |n| n.version)
+}
+
+/// 服务器是否支持某个可选功能；没有公布功能列表的旧版服务器视为支持（与协商前的行为一致）
+pub fn supports(server_url: &str, capability: &str) -> bool {
+    match negotiated(server_url).and_then(|n| n.capabilities) {
+        Some(capabilities) => capabilities.iter().any(|c| c == capability),
+        None => true,
+    }
+}
+
+/// 服务器是否为没有 /sync/version 的旧版服务器（gRPC 服务器不协商，也视为旧版）
+pub fn legacy(server_url: &str) -> bool {
+    negotiated(server_url).is_none_or(|n| n.capabilities.is_none())
+}
+
+/// 服务器是否明确公布支持某个功能；没有公布功能列表的服务器视为不支持，用于需要服务器配合的新功能
+pub fn advertises(server_url: &str, capability: &str) -> bool {
+    negotiated(server_url)
+        .and_then(|n| n.capabilities)
+        .is_some_and(|capabilities| capabilities.iter().any(|c| c == capability))
+}
+
+/// 按 API 版本取出响应中的数据：v1 包在 body 中，v2 起包在 data 中，更早的服务器不包装；
+/// 找不到该版本的包装时依次尝试其他格式，服务器调整格式后已发布的客户端仍能解析
+fn unwrap(version: u32, response: &serde_json::Value) -> &serde_json::Value {
+    let (expected, other) = if version >= 2 { ("data", "body") } else { ("body", "data") };
+    [expected, other]
+        .into_iter()
+        .find_map(|envelope| response.get(envelope).filter(|value| value.is_object()))
+        .unwrap_or(response)
+}
+
+/// 服务器 JSON 响应中的数据部分
+pub fn payload<'a>(server_url: &str, response: &'a serde_json::Value) -> &'a serde_json::Value {
+    unwrap(version(server_url), response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_too_old_client_or_server() {
+        let server = |api_version, min_client_api| ServerApi { api_version, min_client_api, capabilities: Vec::new() };
+        assert!(compatibility(&server(API_VERSION, 1)).is_ok());
+        assert!(compatibility(&server(API_VERSION + 3, API_VERSION)).is_ok());
+
+        let e = compatibility(&server(API_VERSION + 1, API_VERSION + 1)).unwrap_err();
+        assert!(e.to_string().starts_with("Client too old"));
+        let e = compatibility(&server(0, 0)).unwrap_err();
+        assert!(e.to_string().starts_with("Server too old"));
+
+        let parsed: ServerApi = serde_json::from_str(r#"{"api_version": 2}"#).unwrap();
+        assert_eq!((parsed.min_client_api, parsed.capabilities.len()), (1, 0));
+    }
+
+    #[test]
+    fn unwraps_current_legacy_and_future_shapes() {
+        let current = serde_json::json!({ "body": { "code": "A" } });
+        let future = serde_json::json!({ "data": { "code": "B" }, "meta": {} });
+        let legacy = serde_json::json!({ "code": "C" });
+        assert_eq!(unwrap(1, &current)["code"], "A");
+        assert_eq!(unwrap(2, &future)["code"], "B");
+        assert_eq!(unwrap(1, &legacy)["code"], "C");
+        // 协商出的版本与实际格式不符时也能解析
+        assert_eq!(unwrap(2, &current)["code"], "A");
+        assert_eq!(unwrap(1, &future)["code"], "B");
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use zip::ZipWriter;
+
+/// zip 文件头魔数
+const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+
+/// zstd 帧头魔数
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// 归档格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+pub enum ArchiveFormat {
+    /// zip（Deflate 压缩）
+    #[default]
+    #[serde(rename = "zip")]
+    Zip,
+    /// tar + zstd 压缩
+    #[value(name = "tar.zst")]
+    #[serde(rename = "tar.zst")]
+    TarZst,
+}
+
+impl ArchiveFormat {
+    /// 归档文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarZst => "tar.zst",
+        }
+    }
+
+    /// 上传时使用的 MIME 类型
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "application/zip",
+            ArchiveFormat::TarZst => "application/zstd",
+        }
+    }
+
+    /// 校验压缩级别是否在该格式支持的范围内
+    /// zip: 0-9（0 表示仅存储不压缩）；tar.zst: 0-22（0 表示 zstd 默认级别）
+    pub fn validate_level(&self, level: i64) -> Result<()> {
+        let range = match self {
+            ArchiveFormat::Zip => 0..=9,
+            ArchiveFormat::TarZst => 0..=22,
+        };
+        if !range.contains(&level) {
+            return Err(anyhow::anyhow!(
+                "Invalid compression level {} for {} (allowed {}-{}) / 压缩级别 {} 超出 {} 支持范围 ({}-{})",
+                level,
+                self.extension(),
+                range.start(),
+                range.end(),
+                level,
+                self.extension(),
+                range.start(),
+                range.end()
+            ));
+        }
+        Ok(())
+    }
+
+    /// 根据文件头魔数识别归档格式
+    pub fn detect(path: &Path) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        let mut file = fs::File::open(path).context("Failed to open archive / 打开归档文件失败")?;
+        file.read_exact(&mut magic)
+            .context("Archive is too small / 归档文件过小")?;
+
+        match magic {
+            ZIP_MAGIC => Ok(ArchiveFormat::Zip),
+            ZSTD_MAGIC => Ok(ArchiveFormat::TarZst),
+            _ => Err(anyhow::anyhow!("Unknown archive format / 无法识别的归档格式")),
+        }
+    }
+}
+
+/// 边写边计算 SHA256 的文件写入器，打包后不必重新读取整个归档
+///
+/// zip 写完一个条目后会回到该条目的文件头补写 CRC 和大小，之后不再改写更早的内容：
+/// 因此向回定位时，目标位置之前的内容已经定稿，从磁盘读回计入摘要（使用固定大小的缓冲区，
+/// 不在内存中暂存整个条目），之后的内容允许被改写。tar.zst 顺序写入，内容直接计入摘要
+pub struct HashingWriter {
+    file: fs::File,
+    hasher: Sha256,
+    /// 是否允许向回定位改写（zip）
+    rewritable: bool,
+    /// 已计入摘要的长度，之前的内容不能再改写
+    hashed: u64,
+    /// 已写入内容的末尾
+    end: u64,
+    position: u64,
+}
+
+impl HashingWriter {
+    fn new(file: fs::File, rewritable: bool) -> Self {
+        HashingWriter {
+            file,
+            hasher: Sha256::new(),
+            rewritable,
+            hashed: 0,
+            end: 0,
+            position: 0,
+        }
+    }
+
+    /// upto 之前的内容不会再被改写，从磁盘读回计入摘要，之后文件位置停在 upto
+    fn commit(&mut self, upto: u64) -> std::io::Result<()> {
+        let upto = upto.min(self.end);
+        if upto > self.hashed {
+            self.file.seek(SeekFrom::Start(self.hashed))?;
+            let mut buffer = [0u8; 64 * 1024];
+            let mut remaining = upto - self.hashed;
+            while remaining > 0 {
+                let chunk = &mut buffer[..remaining.min(64 * 1024) as usize];
+                self.file.read_exact(chunk)?;
+                self.hasher.update(&chunk[..]);
+                remaining -= chunk.len() as u64;
+            }
+            self.hashed = upto;
+        }
+        self.file.seek(SeekFrom::Start(upto))?;
+        Ok(())
+    }
+
+    /// 计入剩余内容，返回整个文件的 SHA256
+    fn finish(mut self) -> Result<String> {
+        self.commit(self.end)?;
+        self.file.flush()?;
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+impl Write for HashingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.file.write(buf)?;
+        if !self.rewritable {
+            self.hasher.update(&buf[..written]);
+            return Ok(written);
+        }
+        self.position += written as u64;
+        self.end = self.end.max(self.position);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Seek for HashingWriter {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+            SeekFrom::End(delta) => self.end.checked_add_signed(delta),
+        };
+        // 已计入摘要的内容不能再改写，也不支持跳过未写入的区域
+        let target = target
+            .filter(|&target| self.rewritable && target >= self.hashed && target <= self.end)
+            .ok_or_else(|| std::io::Error::other("seek outside the unhashed part of the archive"))?;
+        if target < self.position {
+            self.commit(target)?;
+        } else {
+            self.file.seek(SeekFrom::Start(target))?;
+        }
+        self.position = target;
+        Ok(self.position)
+    }
+}
+
+/// 归档写入器，屏蔽 zip 与 tar.zst 的差异
+///
+/// 所有条目使用固定的时间戳和权限写入，保证打包结果可复现
+pub enum ArchiveWriter {
+    Zip {
+        writer: Box<ZipWriter<HashingWriter>>,
+        options: zip::write::FileOptions<'static, ()>,
+    },
+    TarZst(tar::Builder<zstd::Encoder<'static, HashingWriter>>),
+}
+
+impl ArchiveWriter {
+    /// 创建指定格式的归档文件，level 为 None 时使用格式的默认压缩级别
+    pub fn create(path: &Path, format: ArchiveFormat, level: Option<i64>) -> Result<Self> {
+        if let Some(level) = level {
+            format.validate_level(level)?;
+        }
+
+        // zip 计算摘要时需要读回已定稿的内容
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .context("Failed to create archive / 创建归档文件失败")?;
+
+        match format {
+            ArchiveFormat::Zip => {
+                let options = match level {
+                    Some(0) => zip::write::FileOptions::default()
+                        .compression_method(zip::CompressionMethod::Stored),
+                    _ => zip::write::FileOptions::default()
+                        .compression_method(zip::CompressionMethod::Deflated)
+                        .compression_level(level),
+                };
+                Ok(ArchiveWriter::Zip {
+                    writer: Box::new(ZipWriter::new(HashingWriter::new(file, true))),
+                    options: options
+                        .last_modified_time(zip::DateTime::default())
+                        .unix_permissions(0o644),
+                })
+            }
+            ArchiveFormat::TarZst => {
+                let level = level.map(|l| l as i32).unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL);
+                let encoder = zstd::Encoder::new(HashingWriter::new(file, false), level)?;
+                let mut builder = tar::Builder::new(encoder);
+                builder.mode(tar::HeaderMode::Deterministic);
+                Ok(ArchiveWriter::TarZst(builder))
+            }
+        }
+    }
+
+    /// 写入一个文件条目
+    pub fn add_file(&mut self, name: &str, content: &[u8]) -> Result<()> {
+        match self {
+            ArchiveWriter::Zip { writer, options } => {
+                writer.start_file(name, *options)?;
+                writer.write_all(content)?;
+            }
+            ArchiveWriter::TarZst(builder) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(content.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(0);
+                header.set_cksum();
+                builder.append_data(&mut header, name, content)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 完成写入并刷新到磁盘，返回归档的 SHA256
+    pub fn finish(self) -> Result<String> {
+        match self {
+            ArchiveWriter::Zip { writer, .. } => writer.finish()?.finish(),
+            ArchiveWriter::TarZst(builder) => builder.into_inner()?.finish()?.finish(),
+        }
+    }
+}
+
+/// 依次遍历归档中的文件条目（自动识别格式），对每个条目调用 f(文件名, 内容读取器)
+pub fn for_each_entry<F>(path: &Path, mut f: F) -> Result<()>
+where
+    F: FnMut(&str, &mut dyn Read) -> Result<()>,
+{
+    let file = fs::File::open(path).context("Failed to open archive / 打开归档文件失败")?;
+
+    match ArchiveFormat::detect(path)? {
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(file)?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                f(&name, &mut entry)?;
+            }
+        }
+        ArchiveFormat::TarZst => {
+            let decoder = zstd::Decoder::new(file)?;
+            let mut archive = tar::Archive::new(decoder);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().replace('\\', "/");
+                f(&name, &mut entry)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_archives_while_writing() {
+        let dir = std::env::temp_dir().join(format!("skills-sync-archive-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for format in [ArchiveFormat::Zip, ArchiveFormat::TarZst] {
+            let path = dir.join(format!("a.{}", format.extension()));
+            let mut writer = ArchiveWriter::create(&path, format, None).unwrap();
+            writer.add_file("a/SKILL.md", b"---\nname: a\n---\n").unwrap();
+            writer.add_file("a/big.bin", &vec![7u8; 300_000]).unwrap();
+            writer.add_file("b/SKILL.md", b"---\nname: b\n---\n").unwrap();
+            let sha256 = writer.finish().unwrap();
+            assert_eq!(sha256, format!("{:x}", Sha256::digest(fs::read(&path).unwrap())));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
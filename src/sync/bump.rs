@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::sync::cli::BumpLevel;
+use crate::sync::lock::lock_roots;
+use crate::sync::rename::{find_skill, write_atomic};
+use crate::sync::skill;
+
+/// 按语义化版本递增版本号：major 和 minor 将后面的部分归零，预发布和构建后缀被去掉
+///
+/// 缺少的部分按 0 处理（1.2 视为 1.2.0），保留 v 前缀；没有版本时从 0.0.0 开始
+fn next_version(current: Option<&str>, level: BumpLevel) -> Result<String> {
+    let current = current.map(str::trim).unwrap_or("0.0.0");
+    let (prefix, rest) = match current.strip_prefix(['v', 'V']) {
+        Some(rest) => (&current[..1], rest),
+        None => ("", current),
+    };
+    let core = rest.split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<u64> = core
+        .split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<_, _>>()
+        .ok()
+        .filter(|parts: &Vec<u64>| (1..=3).contains(&parts.len()))
+        .with_context(|| format!("Not a semantic version / 不是语义化版本号: {}", current))?;
+    let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+    let (major, minor, patch) = match level {
+        BumpLevel::Major => (part(0) + 1, 0, 0),
+        BumpLevel::Minor => (part(0), part(1) + 1, 0),
+        BumpLevel::Patch => (part(0), part(1), part(2) + 1),
+    };
+    Ok(format!("{}{}.{}.{}", prefix, major, minor, patch))
+}
+
+/// 执行版本递增命令：更新 SKILL.md front matter 中的 version，没有时添加
+pub fn execute_bump(dirs: Vec<PathBuf>, name: String, level: BumpLevel) -> Result<()> {
+    let (root, _, skill) = find_skill(dirs, &name)?;
+    let skill_file = skill.file;
+    let _lock = lock_roots(std::slice::from_ref(&root), "bump")?;
+
+    let content = fs::read_to_string(&skill_file).context("Failed to read SKILL.md / 读取 SKILL.md 失败")?;
+    let current = skill::parse(&content).and_then(|m| m.version);
+    let next = next_version(current.as_deref(), level)?;
+    let updated = skill::set_version(&content, &next).with_context(|| {
+        format!("SKILL.md has no front matter / SKILL.md 没有 front matter: {}", skill_file.display())
+    })?;
+    write_atomic(&skill_file, &updated)?;
+
+    println!(
+        "🔖 {}: {} -> {}",
+        name,
+        current.as_deref().unwrap_or("(none / 无)"),
+        next
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bumps_semantic_versions() {
+        assert_eq!(next_version(Some("1.2.3"), BumpLevel::Patch).unwrap(), "1.2.4");
+        assert_eq!(next_version(Some("1.2.3"), BumpLevel::Minor).unwrap(), "1.3.0");
+        assert_eq!(next_version(Some("v1.2.3-beta.1"), BumpLevel::Major).unwrap(), "v2.0.0");
+        assert_eq!(next_version(Some("1.2"), BumpLevel::Patch).unwrap(), "1.2.1");
+        assert_eq!(next_version(None, BumpLevel::Minor).unwrap(), "0.1.0");
+        assert!(next_version(Some("latest"), BumpLevel::Patch).is_err());
+        assert!(next_version(Some("1.2.3.4"), BumpLevel::Patch).is_err());
+    }
+}
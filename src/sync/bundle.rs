@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::sync::cli::OnConflict;
+use crate::sync::client::{
+    create_skills_zip, exclude_private, expand_skill_dirs, extract_zip, extraction_roots, resolve_skill_dirs,
+    scan_skill_files, select_skills, PackOptions,
+};
+use crate::sync::locations;
+use crate::sync::lock::lock_roots;
+use crate::sync::manifest::Manifest;
+use crate::sync::parts::file_sha256;
+use crate::sync::temp::TempArchive;
+use crate::sync::update::parse_version;
+
+/// bundle.json 中的类型标识
+const BUNDLE_KIND: &str = "skills-sync-bundle";
+
+/// 当前支持的最高 bundle 格式版本
+const BUNDLE_VERSION: u32 = 1;
+
+/// bundle 中的描述文件
+const BUNDLE_JSON: &str = "bundle.json";
+
+/// bundle 中的签名文件：对 bundle.json 原始字节的 Ed25519 签名（十六进制）
+const BUNDLE_SIG: &str = "bundle.sig";
+
+/// 本机签名私钥（PKCS#8），首次导出时生成
+const SIGNING_KEY_FILE: &str = "bundle-signing.pk8";
+
+/// 受信任的公钥列表（每行一个十六进制公钥，# 开头为注释）
+const TRUSTED_KEYS_FILE: &str = "trusted_keys";
+
+/// bundle 描述：签名覆盖该文件，其中的归档 SHA256 又覆盖归档内容
+#[derive(Debug, Serialize, Deserialize)]
+struct BundleInfo {
+    kind: String,
+    version: u32,
+    /// 生成 bundle 的工具版本
+    tool_version: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    /// bundle 中归档的文件名
+    archive: String,
+    archive_sha256: String,
+    archive_size: u64,
+    /// 包含的 skills
+    skills: Vec<String>,
+    /// 签名公钥（十六进制）
+    public_key: String,
+}
+
+/// 读取本机签名密钥，不存在时生成（权限 0600）
+fn signing_key() -> Result<Ed25519KeyPair> {
+    let path = locations::config_dir()?.join(SIGNING_KEY_FILE);
+    if !path.exists() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| anyhow::anyhow!("Failed to generate signing key / 生成签名密钥失败"))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options
+            .open(&path)
+            .with_context(|| format!("Failed to save signing key / 保存签名密钥失败: {}", path.display()))?;
+        std::io::Write::write_all(&mut file, pkcs8.as_ref())?;
+        println!("🔑 Created bundle signing key / 已生成签名密钥: {}", path.display());
+    }
+    let pkcs8 = fs::read(&path).context("Failed to read signing key / 读取签名密钥失败")?;
+    Ed25519KeyPair::from_pkcs8(&pkcs8)
+        .map_err(|_| anyhow::anyhow!("Invalid signing key / 签名密钥无效: {}", path.display()))
+}
+
+/// 受信任的公钥：--trusted-key、配置目录下的 trusted_keys 文件，以及本机自己的公钥
+fn trusted_keys(extra: &[String]) -> Result<HashSet<String>> {
+    let mut keys: HashSet<String> = extra.iter().map(|k| k.trim().to_lowercase()).collect();
+    let config_dir = locations::config_dir()?;
+    if let Ok(content) = fs::read_to_string(config_dir.join(TRUSTED_KEYS_FILE)) {
+        keys.extend(
+            content
+                .lines()
+                .map(|l| l.split('#').next().unwrap_or_default().trim().to_lowercase())
+                .filter(|l| !l.is_empty()),
+        );
+    }
+    if config_dir.join(SIGNING_KEY_FILE).exists() {
+        keys.insert(hex::encode(signing_key()?.public_key().as_ref()));
+    }
+    Ok(keys)
+}
+
+/// 校验 signature（十六进制）是否为 public_key（十六进制）对 content 的有效 Ed25519 签名
+fn signature_valid(content: &[u8], signature: &str, public_key: &str) -> bool {
+    let (Ok(signature), Ok(key)) = (hex::decode(signature), hex::decode(public_key)) else {
+        return false;
+    };
+    UnparsedPublicKey::new(&ED25519, key).verify(content, &signature).is_ok()
+}
+
+/// 导出签名 bundle：打包选定 skills 的完整目录，与描述文件和签名一起写入一个 tar 文件，便于离线拷贝
+pub fn execute_export_bundle(
+    dirs: Vec<PathBuf>,
+    out: &Path,
+    skills: &[String],
+    include_private: bool,
+    pack: PackOptions,
+) -> Result<()> {
+    let base_dirs = resolve_skill_dirs(dirs)?;
+    let _lock = lock_roots(&base_dirs, "export")?;
+
+    let skill_files = select_skills(scan_skill_files(&base_dirs, false)?, skills)?;
+    let skill_files = exclude_private(skill_files, include_private);
+    if skill_files.is_empty() {
+        return Err(anyhow::anyhow!("No skills to export / 没有可导出的 skills"));
+    }
+
+    let archive = TempArchive::new("bundle", pack.format.extension());
+    let packed = create_skills_zip(&expand_skill_dirs(skill_files, false), archive.path(), &pack)?;
+    let mut names: Vec<String> = packed.manifest.files.iter().filter_map(|e| e.skill_name.clone()).collect();
+    names.sort();
+    names.dedup();
+
+    let key = signing_key()?;
+    let archive_name = format!("skills.{}", pack.format.extension());
+    let info = BundleInfo {
+        kind: BUNDLE_KIND.to_string(),
+        version: BUNDLE_VERSION,
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now(),
+        archive: archive_name.clone(),
+        archive_sha256: packed.sha256.clone(),
+        archive_size: fs::metadata(archive.path())?.len(),
+        skills: names,
+        public_key: hex::encode(key.public_key().as_ref()),
+    };
+    let info_json = serde_json::to_vec_pretty(&info)?;
+    let signature = hex::encode(key.sign(&info_json).as_ref());
+
+    // 先写临时文件再重命名，避免留下不完整的 bundle
+    let tmp = out.with_extension("bundle.tmp");
+    let result = (|| -> Result<()> {
+        let mut builder = tar::Builder::new(fs::File::create(&tmp)?);
+        builder.mode(tar::HeaderMode::Deterministic);
+        for (name, content) in [(BUNDLE_JSON, info_json.as_slice()), (BUNDLE_SIG, signature.as_bytes())] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, content)?;
+        }
+        builder.append_path_with_name(archive.path(), &archive_name)?;
+        builder.into_inner()?.sync_all()?;
+        fs::rename(&tmp, out)?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.context(format!("Failed to write bundle / 写入 bundle 失败: {}", out.display())));
+    }
+
+    println!("📦 Bundle written / 已生成 bundle: {} ({} skills)", out.display(), info.skills.len());
+    println!("🔏 Signed with public key / 签名公钥: {}", info.public_key);
+    println!("   Trust it on the target machine with --trusted-key or the trusted_keys file / 在目标机器上通过 --trusted-key 或 trusted_keys 文件信任该公钥");
+    Ok(())
+}
+
+/// 导入 bundle：校验归档 SHA256 和签名后按 manifest 解压
+///
+/// 签名无效（bundle 被改动过）时总是拒绝；require_signature 时未签名或签名公钥不受信任也拒绝
+pub fn execute_import(bundle: &Path, require_signature: bool, trusted: &[String]) -> Result<()> {
+    println!("📥 Importing bundle / 导入 bundle: {}", bundle.display());
+    let not_a_bundle = || anyhow::anyhow!("Not a skills-sync bundle / 不是 skills-sync bundle: {}", bundle.display());
+
+    let archive = TempArchive::new("import", "bundle");
+    let mut info_json = None;
+    let mut signature = None;
+    let mut archive_name = None;
+    let file = fs::File::open(bundle).context("Failed to open bundle / 打开 bundle 失败")?;
+    for entry in tar::Archive::new(file).entries().map_err(|_| not_a_bundle())? {
+        let mut entry = entry.map_err(|_| not_a_bundle())?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        match name.as_str() {
+            BUNDLE_JSON => {
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content)?;
+                info_json = Some(content);
+            }
+            BUNDLE_SIG => {
+                let mut content = String::new();
+                entry.read_to_string(&mut content)?;
+                signature = Some(content.trim().to_string());
+            }
+            _ if archive_name.is_none() && name.starts_with("skills.") && !name.contains('/') => {
+                let mut out = fs::File::create(archive.path())?;
+                std::io::copy(&mut entry, &mut out)?;
+                archive_name = Some(name);
+            }
+            _ => println!("⚠️  Ignoring unexpected entry / 忽略未知条目: {}", name),
+        }
+    }
+
+    let info_json = info_json.ok_or_else(not_a_bundle)?;
+    let info: BundleInfo = serde_json::from_slice(&info_json).map_err(|_| not_a_bundle())?;
+    if info.kind != BUNDLE_KIND {
+        return Err(not_a_bundle());
+    }
+    if info.version > BUNDLE_VERSION {
+        return Err(anyhow::anyhow!(
+            "Bundle format v{} is newer than supported (v{}), please upgrade skills-sync / bundle 格式 v{} 高于支持的版本（v{}），请升级 skills-sync",
+            info.version,
+            BUNDLE_VERSION,
+            info.version,
+            BUNDLE_VERSION
+        ));
+    }
+    if archive_name.as_deref() != Some(info.archive.as_str()) {
+        return Err(anyhow::anyhow!("Bundle is missing its archive / bundle 中缺少归档: {}", info.archive));
+    }
+    let sha256 = file_sha256(archive.path())?;
+    if sha256 != info.archive_sha256 || fs::metadata(archive.path())?.len() != info.archive_size {
+        return Err(anyhow::anyhow!(
+            "Integrity check failed: bundle archive does not match bundle.json / 完整性校验失败: bundle 中的归档与 bundle.json 不一致"
+        ));
+    }
+
+    // 签名覆盖 bundle.json，进而覆盖归档 SHA256
+    match signature {
+        None if require_signature => {
+            return Err(anyhow::anyhow!("Bundle is not signed, refusing to import / bundle 未签名，拒绝导入"));
+        }
+        None => println!("⚠️  Bundle is not signed / bundle 未签名"),
+        Some(signature) => {
+            if !signature_valid(&info_json, &signature, &info.public_key) {
+                return Err(anyhow::anyhow!(
+                    "Invalid bundle signature, the bundle was modified / bundle 签名无效，内容已被改动"
+                ));
+            }
+            if trusted_keys(trusted)?.contains(&info.public_key.to_lowercase()) {
+                println!("🔏 Signature verified / 签名校验通过: {}", info.public_key);
+            } else if require_signature {
+                return Err(anyhow::anyhow!(
+                    "Bundle is signed by an untrusted key, refusing to import / bundle 的签名公钥不受信任，拒绝导入: {}\n   trust it with --trusted-key {} / 可通过 --trusted-key {} 信任该公钥",
+                    info.public_key,
+                    info.public_key,
+                    info.public_key
+                ));
+            } else {
+                println!("⚠️  Signed by an untrusted key / 签名公钥不受信任: {}", info.public_key);
+            }
+        }
+    }
+
+    if let (Some(made_by), Some(current)) = (parse_version(&info.tool_version), parse_version(env!("CARGO_PKG_VERSION"))) {
+        if made_by > current {
+            println!(
+                "⚠️  Bundle was created by a newer skills-sync ({}) / bundle 由更新版本的 skills-sync（{}）生成",
+                info.tool_version, info.tool_version
+            );
+        }
+    }
+    println!(
+        "   Created / 创建时间: {} (skills-sync {}), skills: {}",
+        info.created_at.format("%Y-%m-%d %H:%M:%S"),
+        info.tool_version,
+        info.skills.join(", ")
+    );
+
+    // 解压位置由 manifest 中的原始路径决定，解压前对默认目录和这些目录加锁
+    let manifest = Manifest::read_from_archive(archive.path())?.unwrap_or_default();
+    let mut roots = resolve_skill_dirs(Vec::new())?;
+    roots.extend(extraction_roots(&manifest)?);
+    let _lock = lock_roots(&roots, "import")?;
+    let target = roots.first().cloned().unwrap_or_default();
+    extract_zip(archive.path(), &target, &[], None, OnConflict::Merge, None)?;
+    println!("✅ Bundle imported / bundle 已导入");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_tampered_or_foreign_signatures() {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let public_key = hex::encode(key.public_key().as_ref());
+        let content = br#"{"archive_sha256":"aa"}"#;
+        let signature = hex::encode(key.sign(content).as_ref());
+
+        assert!(signature_valid(content, &signature, &public_key));
+        assert!(!signature_valid(br#"{"archive_sha256":"bb"}"#, &signature, &public_key));
+        assert!(!signature_valid(content, "not hex", &public_key));
+
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let other = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        assert!(!signature_valid(content, &signature, &hex::encode(other.public_key().as_ref())));
+    }
+}
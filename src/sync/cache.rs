@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::sync::locations;
+use crate::sync::parts::file_sha256;
+
+/// 下载缓存目录，每个业务码一个子目录
+pub(crate) fn downloads_dir() -> Result<PathBuf> {
+    Ok(locations::cache_dir()?.join("downloads"))
+}
+
+/// 某个业务码的下载缓存目录，按服务器 + 业务码区分
+fn entry_dir(server_url: &str, code: &str) -> Result<PathBuf> {
+    let key: String = format!("{}_{}", server_url, code)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    Ok(downloads_dir()?.join(key))
+}
+
+/// 记录服务器端归档 SHA256 的文件名（分卷归档时为分卷索引的 SHA256）
+const REMOTE_FILE: &str = "remote";
+
+/// 查找业务码对应的缓存归档，文件名即为 SHA256，内容校验不通过的缓存会被丢弃
+///
+/// remote 为服务器当前报告的 SHA256，与缓存时记录的不一致说明业务码已更新为新版本，此时不使用缓存；
+/// 服务器没有报告 SHA256 时无法判断缓存是否过期，同样不使用缓存
+pub fn lookup(server_url: &str, code: &str, remote: Option<&str>) -> Option<(PathBuf, String)> {
+    let remote = remote?;
+    let dir = entry_dir(server_url, code).ok()?;
+    let cached = fs::read_to_string(dir.join(REMOTE_FILE)).ok()?;
+    if cached.trim() != remote {
+        return None;
+    }
+    let entry = fs::read_dir(&dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_name() != REMOTE_FILE)?;
+    let path = entry.path();
+    let expected = path.file_name()?.to_str()?.to_string();
+
+    match file_sha256(&path) {
+        Ok(actual) if actual == expected => {
+            // 记录使用时间，保留策略按最近使用时间淘汰
+            let _ = fs::File::options()
+                .write(true)
+                .open(dir.join(REMOTE_FILE))
+                .and_then(|f| f.set_modified(SystemTime::now()));
+            Some((path, actual))
+        }
+        _ => {
+            let _ = fs::remove_dir_all(&dir);
+            None
+        }
+    }
+}
+
+/// 将下载并校验通过的归档写入缓存，同一业务码只保留最新的一份
+pub fn store(server_url: &str, code: &str, archive_path: &Path, sha256: &str, remote: &str) -> Result<()> {
+    let dir = entry_dir(server_url, code)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+    fs::copy(archive_path, dir.join(sha256)).context("Failed to write download cache / 写入下载缓存失败")?;
+    fs::write(dir.join(REMOTE_FILE), remote)?;
+    Ok(())
+}
+
+/// 清空下载缓存，返回释放的字节数
+pub fn clear() -> Result<u64> {
+    let dir = downloads_dir()?;
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let size = WalkDir::new(&dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum();
+
+    fs::remove_dir_all(&dir).context("Failed to clear cache / 清空缓存失败")?;
+    Ok(size)
+}
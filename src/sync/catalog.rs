@@ -0,0 +1,205 @@
+use anyhow::{Context, Result};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sync::client::collect_skills;
+use crate::sync::skill::strip_front_matter;
+
+/// HTML 转义
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// 链接目标是否可以放入页面：只允许 http、https 和相对链接（含 #锚点），拒绝 javascript: 等其他协议
+fn is_safe_url(url: &str) -> bool {
+    let url = url.trim();
+    match url.find(':') {
+        // 冒号出现在 / ? # 之前才是协议
+        Some(colon) if !url[..colon].contains(['/', '?', '#']) => {
+            let scheme = url[..colon].to_ascii_lowercase();
+            scheme == "http" || scheme == "https"
+        }
+        _ => true,
+    }
+}
+
+/// 将不安全的链接目标替换为 "#"
+fn sanitize_url(url: CowStr<'_>) -> CowStr<'_> {
+    if is_safe_url(&url) {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+/// 将 Markdown 正文渲染为 HTML，正文中的原始 HTML 会被转义，链接和图片只保留安全的地址
+fn render_markdown(body: &str) -> String {
+    let parser = Parser::new_ext(body, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: sanitize_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: sanitize_url(dest_url),
+            title,
+            id,
+        }),
+        other => other,
+    });
+    let mut out = String::new();
+    html::push_html(&mut out, parser);
+    out
+}
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; margin: 0 auto; max-width: 960px; padding: 24px; color: #222; }
+header { display: flex; align-items: baseline; justify-content: space-between; gap: 16px; }
+#search { width: 100%; padding: 10px; font-size: 16px; margin: 16px 0; box-sizing: border-box; }
+.skill { border: 1px solid #ddd; border-radius: 6px; padding: 12px 16px; margin-bottom: 12px; }
+.skill h2 { margin: 0 0 4px; font-size: 18px; }
+.meta { color: #666; font-size: 13px; }
+.tag { display: inline-block; background: #eef; border-radius: 3px; padding: 0 6px; margin-right: 4px; font-size: 12px; }
+.version { color: #888; font-size: 14px; font-weight: normal; }
+.body { border-top: 1px solid #eee; margin-top: 8px; }
+pre { background: #f6f8fa; padding: 8px; overflow-x: auto; }
+"#;
+
+const SCRIPT: &str = r#"
+const search = document.getElementById('search');
+const skills = Array.from(document.querySelectorAll('.skill'));
+const count = document.getElementById('count');
+search.addEventListener('input', () => {
+  const terms = search.value.toLowerCase().split(/\s+/).filter(Boolean);
+  let shown = 0;
+  for (const skill of skills) {
+    const text = skill.dataset.search;
+    const match = terms.every(t => text.includes(t));
+    skill.hidden = !match;
+    if (match) shown++;
+  }
+  count.textContent = shown + ' / ' + skills.length;
+});
+"#;
+
+/// 执行目录生成命令：将本地 skills 渲染为带搜索的静态 HTML 页面（out/index.html）
+pub fn execute_catalog(dirs: Vec<PathBuf>, out: PathBuf) -> Result<()> {
+    let skills_by_source = collect_skills(dirs)?;
+
+    let mut cards = String::new();
+    let mut total = 0;
+    for (source, skills) in &skills_by_source {
+        for skill in skills {
+            let content = fs::read_to_string(&skill.file).unwrap_or_default();
+
+            let search_text = format!(
+                "{} {} {} {}",
+                skill.name,
+                skill.description,
+                skill.tags.join(" "),
+                source
+            )
+            .to_lowercase();
+            let tags: String = skill
+                .tags
+                .iter()
+                .map(|t| format!(r#"<span class="tag">{}</span>"#, escape_html(t)))
+                .collect();
+
+            cards.push_str(&format!(
+                r#"<article class="skill" data-search="{search}">
+<h2>{name}{version}</h2>
+<div class="meta">{source} · <code>{path}</code></div>
+<p>{description}</p>
+<div>{tags}</div>
+<details><summary>SKILL.md</summary><div class="body">{body}</div></details>
+</article>
+"#,
+                search = escape_html(&search_text),
+                name = escape_html(&skill.name),
+                version = skill
+                    .version
+                    .as_deref()
+                    .map(|v| format!(r#" <span class="version">{}</span>"#, escape_html(v)))
+                    .unwrap_or_default(),
+                source = escape_html(source),
+                path = escape_html(&skill.path),
+                description = escape_html(&skill.description),
+                tags = tags,
+                body = render_markdown(strip_front_matter(&content)),
+            ));
+            total += 1;
+        }
+    }
+
+    if total == 0 {
+        println!("❌ No skills found / 未找到任何 skills");
+        return Ok(());
+    }
+
+    let page = format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Skills Catalog</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<header><h1>Skills Catalog</h1><span id="count">{total} / {total}</span></header>
+<input id="search" type="search" placeholder="Search name, description or tag / 搜索名称、描述或标签" autofocus>
+{cards}<script>{SCRIPT}</script>
+</body>
+</html>
+"#
+    );
+
+    write_catalog(&out, &page)?;
+    println!(
+        "✅ Catalog with {} skills written / 已生成包含 {} 个 skills 的目录: {}",
+        total,
+        total,
+        out.join("index.html").display()
+    );
+    Ok(())
+}
+
+fn write_catalog(out: &Path, page: &str) -> Result<()> {
+    fs::create_dir_all(out)?;
+    fs::write(out.join("index.html"), page).context("Failed to write catalog / 写入目录页面失败")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_only_http_and_relative_links() {
+        assert!(is_safe_url("https://example.com/a"));
+        assert!(is_safe_url("HTTP://example.com"));
+        assert!(is_safe_url("docs/guide.md"));
+        assert!(is_safe_url("#usage"));
+        assert!(is_safe_url("./a:b"));
+        assert!(!is_safe_url("javascript:alert(1)"));
+        assert!(!is_safe_url(" JavaScript:alert(1)"));
+        assert!(!is_safe_url("data:text/html,hi"));
+        assert!(!is_safe_url("vbscript:x"));
+    }
+
+    #[test]
+    fn rendered_links_drop_unsafe_targets() {
+        let html = render_markdown("[a](javascript:alert(1)) ![b](data:image/png;base64,x) [c](https://ok.example)");
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("data:"));
+        assert!(html.contains(r#"href="https://ok.example""#));
+    }
+}
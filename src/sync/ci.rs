@@ -0,0 +1,50 @@
+//! CI 模式（--ci，stdout 不是终端或设置了 CI 环境变量时自动开启）：不绘制进度条和 spinner，
+//! 只输出逐行的结果，不询问用户（除非指定 --yes，按默认回答继续），避免控制序列把 CI 日志弄乱、
+//! 或因等待输入而卡住
+
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Mode {
+    enabled: bool,
+    yes: bool,
+}
+
+/// CI 环境变量是否表示正在 CI 中运行（GitHub Actions、GitLab CI 等都会设置 CI=true）
+fn ci_env(value: Option<&str>) -> bool {
+    value.is_some_and(|v| !v.is_empty() && !v.eq_ignore_ascii_case("false") && v != "0")
+}
+
+/// 设置 CI 模式：force 为 --ci，yes 为 --yes
+pub fn init(force: bool, yes: bool) {
+    let enabled = force || !std::io::stdout().is_terminal() || ci_env(std::env::var("CI").ok().as_deref());
+    let _ = MODE.set(Mode { enabled, yes });
+}
+
+/// 是否处于 CI 模式
+pub fn enabled() -> bool {
+    MODE.get().copied().unwrap_or_default().enabled
+}
+
+/// 是否指定了 --yes：需要询问时直接采用默认回答
+pub fn assume_yes() -> bool {
+    MODE.get().copied().unwrap_or_default().yes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_ci_environment() {
+        assert!(ci_env(Some("true")));
+        assert!(ci_env(Some("1")));
+        assert!(!ci_env(Some("false")));
+        assert!(!ci_env(Some("0")));
+        assert!(!ci_env(Some("")));
+        assert!(!ci_env(None));
+    }
+}
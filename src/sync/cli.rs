@@ -1,4 +1,13 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::sync::archive::ArchiveFormat;
+use crate::sync::encoding::UploadEncoding;
+use crate::sync::paths::expand_path;
+use crate::sync::table::{ListColumn, PathStyle, DEFAULT_TRUNCATE};
 
 #[derive(Debug, Parser)]
 #[command(name = "skills-sync")]
@@ -9,6 +18,31 @@ EXAMPLES / 示例:
   Upload skills to default server / 上传 skills 到默认服务器:
     cargo run -- upload
 
+  Upload several directories, with globs / 上传多个目录（支持 glob）:
+    cargo run -- upload -d '~/projects/*/.claude/skills' -d ~/my-skills
+
+  Upload everything except personal skills / 上传除个人 skills 外的全部:
+    cargo run -- upload --exclude 'personal-*'
+
+  Share every rust-* skill / 分享所有 rust-* skill:
+    cargo run -- upload --name 'rust-*'
+
+  Share a single skill / 只分享一个 skill:
+    cargo run -- upload --skill humanizer-zh
+
+  Download only skills with a prefix / 只下载指定前缀的 skills:
+    cargo run -- download -c ABC123 --include 'rust-*'
+
+  Upload with a memorable alias / 上传并指定易记的别名:
+    cargo run -- upload --alias alice-skills-2024
+    cargo run -- download -c alice-skills-2024
+
+  Extend a code's expiry by a week / 将业务码有效期延长一周:
+    cargo run -- renew -c ABC123 --expire 7d
+
+  Preview server garbage collection / 预览服务器垃圾回收:
+    cargo run -- admin gc --dry-run --admin-token $TOKEN
+
   Upload to specified server / 上传到指定服务器:
     cargo run -- upload -s http://localhost:8080
 
@@ -18,6 +52,100 @@ EXAMPLES / 示例:
   List locally installed skills / 列出本地已安装的 skills:
     cargo run -- list
 
+  Export the skill inventory as CSV / 以 CSV 导出 skills 清单:
+    cargo run -- list --format csv > skills.csv
+
+  Show only some columns / 只显示部分列:
+    cargo run -- list --columns name,path
+
+  Absolute paths for scripts / 输出绝对路径供脚本使用:
+    cargo run -- list --paths absolute --format tsv
+
+  Fuzzy-search skills and act on one / 模糊搜索 skill 并操作:
+    cargo run -- pick
+
+  Open the terminal dashboard (tui builds) / 打开终端界面（tui 构建）:
+    cargo run --features tui -- ui
+
+  Decide per skill when local edits meet remote changes / 本地与远端都修改时逐个选择:
+    cargo run -- download -c ABC123 --on-conflict prompt
+
+  Download several shared collections / 一次下载多个业务码:
+    cargo run -- download -c ABC123 -c DEF456
+
+  Upload to two servers for redundancy / 同时上传到两个服务器:
+    cargo run -- upload -s https://a.example.com -s https://b.example.com
+
+  Show what a download would change / 查看下载会改变哪些文件:
+    cargo run -- conflicts -c ABC123
+
+  Preview the skills in a code / 预览业务码中的 skills:
+    cargo run -- show -c ABC123
+
+  Upload changes every 30 minutes / 每 30 分钟上传一次变化:
+    cargo run -- sync --every 30m --push
+
+  Clear the download cache / 清空下载缓存:
+    cargo run -- cache clear
+
+  See the usage statistics that would be sent (telemetry = true) / 查看将发送的使用统计:
+    cargo run -- report
+
+  Preview what the retention policy would delete / 预览保留策略将删除的内容:
+    cargo run -- prune --dry-run --max-age 90d
+
+  Audit local edits since the last sync / 检查上次同步后的本地修改:
+    cargo run -- verify
+
+  Check an archive received out-of-band / 校验从其他渠道收到的归档:
+    cargo run -- verify-archive ./skills.zip
+
+  Rename a local skill / 重命名本地 skill:
+    cargo run -- rename old-name new-name
+
+  Show what changed in a skill across syncs / 查看 skill 在各次同步中的变更:
+    cargo run -- info my-skill --history
+
+  Browse the git history of a skills directory (git_history = true) / 查看 skills 目录的 git 历史:
+    cargo run -- git -d ~/.claude/skills log --stat
+
+  Restore one skill to its last synced content / 将一个 skill 恢复为上次同步的内容:
+    cargo run -- restore my-skill
+
+  Find skills not synced for a month / 查找一个月未同步的 skills:
+    cargo run -- list --stale 30d --columns name,uploaded,downloaded
+
+  Refuse to upload skills that break the metadata policy / 元数据不符合规范时拒绝上传:
+    cargo run -- upload --strict
+
+  Lint front matter against your own JSON Schema / 按自己的 JSON Schema 检查 front matter:
+    cargo run -- validate --schema skill-schema.json
+
+  Bump a skill's minor version / 递增 skill 的次版本号:
+    cargo run -- bump my-skill minor
+
+  Upgrade files written by an older version / 升级旧版本写入的文件:
+    cargo run -- migrate --dry-run
+
+  Update to the latest release / 更新到最新版本:
+    skills-sync self-update
+
+  Generate an HTML catalog / 生成 HTML 目录:
+    cargo run -- catalog --out ./site
+
+  Export skills as a Claude Code plugin / 导出为 Claude Code 插件:
+    cargo run -- export -o ./my-plugin --name my-skills
+
+  Move skills to an air-gapped machine / 拷贝 skills 到隔离环境:
+    cargo run -- export --bundle ./skills.bundle
+    cargo run -- import ./skills.bundle --require-signature --trusted-key <KEY>
+
+  Run as an MCP server / 作为 MCP 服务运行:
+    cargo run -- mcp
+
+  Run a plugin (skills-sync-<name> on PATH) / 运行插件（PATH 中的 skills-sync-<name>）:
+    cargo run -- stats --top 10
+
 DEFAULT SCAN DIRECTORIES / 默认扫描目录:
   ~/.claude/skills/
   ~/.codex/skills/
@@ -26,39 +154,834 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
 
-    /// 远端服务器地址 (未指定时根据 IP 自动选择 / Auto-selected by IP if not specified)
+    /// 远端服务器地址，可以是 unix:///path/to.sock；upload 可重复指定以推送到多个服务器 (未指定时使用配置中的 server，否则根据 IP 自动选择 / May be unix:///path/to.sock; repeat with upload to push to several servers; defaults to `server` in the config, otherwise auto-selected by IP)
     #[arg(
         short = 's',
         long,
         global = true
     )]
-    pub server: Option<String>,
+    pub server: Vec<String>,
+
+    /// 并发任务数，默认为 CPU 核数 / Number of parallel jobs (defaults to CPU count)
+    #[arg(short = 'j', long, global = true)]
+    pub jobs: Option<usize>,
+
+    /// 访问同步服务器的 API 令牌（默认读取 SKILLS_SYNC_TOKEN 或系统密钥环）/ API token for the sync server (defaults to SKILLS_SYNC_TOKEN or the OS keyring)
+    #[arg(long, global = true, value_name = "TOKEN")]
+    pub token: Option<String>,
+
+    /// 上传、下载完成或失败时发送桌面通知 / Show a desktop notification when uploads or downloads finish
+    #[arg(long, global = true)]
+    pub notify: bool,
+
+    /// 通过 WebSocket 传输归档（支持断点续传和新版本推送，需要 websocket 特性）/ Transfer archives over WebSocket (resumable, with update push; needs the websocket feature)
+    #[arg(long, global = true)]
+    pub websocket: bool,
+
+    /// 优先通过 HTTP/3（QUIC）访问 https 服务器，不可用时回退到 HTTP/1.1（需要 http3 特性）/ Prefer HTTP/3 (QUIC) for https servers, falling back to HTTP/1.1 (needs the http3 feature)
+    #[arg(long, global = true)]
+    pub http3: bool,
+
+    /// HTTP 下载时的并发连接数（1-16，服务器支持 Range 时分段并行下载大归档）/ Parallel connections for HTTP downloads (1-16; large archives are fetched in ranged segments when the server supports it)
+    #[arg(long, global = true, value_name = "N", default_value_t = 1)]
+    pub connections: usize,
+
+    /// 压缩上传请求体（服务器公布支持时生效，默认读取配置中的 upload_encoding）/ Compress upload request bodies when the server advertises support (defaults to upload_encoding in the config)
+    #[arg(long, global = true, value_enum, value_name = "ENCODING")]
+    pub upload_encoding: Option<UploadEncoding>,
+
+    /// 只通过 IPv4 连接服务器 / Connect to the server over IPv4 only
+    #[arg(short = '4', long, global = true, conflicts_with = "ipv6")]
+    pub ipv4: bool,
+
+    /// 只通过 IPv6 连接服务器 / Connect to the server over IPv6 only
+    #[arg(short = '6', long, global = true)]
+    pub ipv6: bool,
+
+    /// 服务器证书与首次连接时记录的不一致时，信任新证书并更新记录 / Accept and re-pin a server certificate that changed since first contact
+    #[arg(long, global = true)]
+    pub trust_server_cert: bool,
+
+    /// 命令结束时将结果（业务码、SHA256、文件列表及各文件的哈希、用时、警告）以 JSON 写入该文件 / Write the result (codes, SHA256, files with hashes, timings, warnings) as JSON to PATH when the command ends
+    #[arg(long, global = true, value_name = "PATH")]
+    pub summary_json: Option<PathBuf>,
+
+    /// CI 模式：不绘制进度条，不询问（stdout 不是终端或设置了 CI 环境变量时自动开启）/ CI mode: no progress bars and no prompts (on automatically when stdout is not a terminal or CI is set)
+    #[arg(long, global = true)]
+    pub ci: bool,
+
+    /// 需要询问时按默认回答继续（冲突时合并）/ Answer prompts with the default (merge on conflicts) instead of asking
+    #[arg(short = 'y', long, global = true)]
+    pub yes: bool,
+
+    /// 将主机名解析到指定地址，格式同 curl（可重复）/ Resolve HOST to ADDR instead of using DNS, like curl (repeatable)
+    #[arg(long, global = true, value_name = "HOST:PORT:ADDR", value_parser = parse_resolve)]
+    pub resolve: Vec<(String, SocketAddr)>,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
     /// 上传本地 skills 到远端仓库 / Upload local skills to remote repository
     Upload {
-        /// 本地 skills 目录路径 / Local skills directory path
-        #[arg(short = 'd', long)]
-        dir: Option<String>,
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+
+        /// 归档格式 / Archive format
+        #[arg(long, value_enum, default_value_t = ArchiveFormat::Zip)]
+        archive_format: ArchiveFormat,
+
+        /// 压缩级别（zip: 0-9，0 为仅存储；tar.zst: 0-22）/ Compression level (zip: 0-9, 0 = store only; tar.zst: 0-22)
+        #[arg(long)]
+        compression_level: Option<i64>,
+
+        /// 单个分卷最大大小，超出时拆分为多个分卷上传（如 50M、1G）/ Split the archive into parts of at most this size (e.g. 50M, 1G)
+        #[arg(long, value_parser = parse_size)]
+        max_part_size: Option<u64>,
+
+        /// 单个文件最大大小，超出时中止上传（未指定时超过 1M 的文件只给出警告）/ Abort when a file is larger than this size (without it, files over 1M only trigger a warning)
+        #[arg(long, value_parser = parse_size)]
+        max_file_size: Option<u64>,
+
+        /// 保留上传的归档，可指定保存路径 / Keep the uploaded archive, optionally at PATH
+        #[arg(long, alias = "keep", value_name = "PATH", num_args = 0..=1, default_missing_value = "")]
+        keep_archive: Option<String>,
+
+        /// 仅上传自上次上传到该服务器以来变化的文件 / Only upload files changed since the last upload to this server
+        #[arg(long)]
+        delta: bool,
+
+        /// 排除匹配的 skill（按 skill 名称或相对 skills 目录的路径匹配，可重复）/ Leave out skills matching this glob (by name or path relative to the skills directory; repeatable)
+        #[arg(long, value_name = "GLOB", value_parser = parse_glob)]
+        exclude: Vec<glob::Pattern>,
+
+        /// 只上传指定名称的 skill（连同目录下的全部文件），可重复 / Only upload the skill with this name, with all files in its directory (repeatable)
+        #[arg(long = "skill", value_name = "NAME")]
+        skills: Vec<String>,
+
+        /// 只上传匹配的 skill（按 skill 名称或相对 skills 目录的路径匹配，可重复）/ Only upload skills matching this glob (by name or path relative to the skills directory; repeatable)
+        #[arg(long = "name", value_name = "GLOB", value_parser = parse_glob)]
+        names: Vec<glob::Pattern>,
+
+        /// 作为已有业务码的新版本上传（需为本机上传的业务码）/ Upload as a new version of an existing code (codes uploaded from this machine)
+        #[arg(short = 'c', long, value_name = "CODE")]
+        code: Option<String>,
+
+        /// 请求一个映射到业务码的自定义别名，之后可在任何需要业务码的地方使用 / Request a custom alias for the code, usable anywhere a code is accepted
+        #[arg(long, value_name = "ALIAS", value_parser = parse_alias)]
+        alias: Option<String>,
+
+        /// 扫描时遵循 .gitignore / .ignore 规则 / Respect .gitignore / .ignore rules while scanning
+        #[arg(long)]
+        gitignore: bool,
+
+        /// 打包每个 skill 目录下的全部文件（脚本、模板等），而不只是 SKILL.md / Package every file in each skill directory (scripts, templates, ...), not just SKILL.md
+        #[arg(long)]
+        whole_dir: bool,
+
+        /// 同时上传 front matter 中标记为 sync: false 或 private: true 的 skill / Also upload skills marked sync: false or private: true in their front matter
+        #[arg(long)]
+        include_private: bool,
+
+        /// 打包前按配置中的 [policy] 检查元数据，有不符合的 skill 时中止 / Check metadata against [policy] in the config before packaging, abort if any skill fails
+        #[arg(long)]
+        strict: bool,
     },
 
     /// 从远端仓库下载 skills / Download skills from remote repository
     Download {
-        /// 业务码 / Business code
-        #[arg(short = 'c', long)]
-        code: String,
+        /// 业务码，可重复，依次下载 / Business code (repeatable, downloaded one after another)
+        #[arg(short = 'c', long = "code", value_name = "CODE", required_unless_present = "codes_file")]
+        codes: Vec<String>,
+
+        /// 从文件读取业务码（每行一个，# 开头为注释）/ Read business codes from FILE (one per line, # starts a comment)
+        #[arg(long, value_name = "FILE", value_parser = parse_path)]
+        codes_file: Option<PathBuf>,
 
         /// 解压目标目录 / Extract target directory
-        #[arg(short = 'd', long)]
-        dir: Option<String>,
+        #[arg(short = 'd', long, value_parser = parse_path)]
+        dir: Option<PathBuf>,
+
+        /// 保留下载的归档，可指定保存路径 / Keep the downloaded archive, optionally at PATH
+        #[arg(long, alias = "keep", value_name = "PATH", num_args = 0..=1, default_missing_value = "")]
+        keep_archive: Option<String>,
+
+        /// 仅将归档保存到指定路径，不解压 / Save the archive to PATH without extracting
+        #[arg(long, value_name = "PATH", conflicts_with = "keep_archive")]
+        archive_out: Option<String>,
+
+        /// 不使用本地下载缓存 / Bypass the local download cache
+        #[arg(long)]
+        no_cache: bool,
+
+        /// 只解压匹配的 skill（按 skill 名称或原始路径匹配，可重复）/ Only extract skills matching this glob (by name or original path; repeatable)
+        #[arg(long, value_name = "GLOB", value_parser = parse_glob, conflicts_with = "archive_out")]
+        include: Vec<glob::Pattern>,
+
+        /// 本地与远端都修改过的 skill 如何处理 / How to handle skills changed both locally and remotely
+        #[arg(long, value_enum, default_value_t = OnConflict::Merge, conflicts_with = "archive_out")]
+        on_conflict: OnConflict,
     },
 
     /// 列出本地已安装的 skills / List locally installed skills
     List {
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+
+        /// 输出格式 / Output format
+        #[arg(long, value_enum, default_value_t = ListFormat::Table)]
+        format: ListFormat,
+
+        /// 显示的列，逗号分隔（默认为配置中的 list.columns）/ Columns to show, comma separated (default: list.columns in the config)
+        #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Option<Vec<ListColumn>>,
+
+        /// 表格中显示完整描述，不截断 / Show full descriptions in tables instead of truncating them
+        #[arg(long)]
+        wide: bool,
+
+        /// 表格中描述最多显示的字符数 / Truncate descriptions in tables to N characters
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_TRUNCATE, conflicts_with = "wide")]
+        truncate: usize,
+
+        /// 路径显示方式 / How to show paths
+        #[arg(long, value_enum, default_value_t = PathStyle::Home)]
+        paths: PathStyle,
+
+        /// 只列出超过该时间未上传或下载（包括从未同步）的 skills，如 30d / Only list skills not uploaded or downloaded for this long (or never), e.g. 30d
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, conflicts_with = "interactive")]
+        stale: Option<Duration>,
+
+        /// 打开模糊搜索选择器（同 pick）/ Open the fuzzy-search picker (same as pick)
+        #[arg(short = 'i', long, conflicts_with = "format")]
+        interactive: bool,
+    },
+
+    /// 终端界面：浏览本地 skills 和同步状态，上传、下载、查看修改和回滚（需要 tui 特性）/ Terminal UI to browse skills and sync status, upload, download, diff and roll back (needs the tui feature)
+    Ui {
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 模糊搜索本地 skills，选中后查看详情、编辑、上传或卸载 / Fuzzy-search local skills, then show info, edit, upload or uninstall the selected one
+    Pick {
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 列出本地与远端不一致的 skills（不修改文件）/ List skills that differ between local and remote without changing anything
+    Conflicts {
+        /// 业务码 / Business code
+        #[arg(short = 'c', long)]
+        code: String,
+
+        /// 不使用本地下载缓存 / Bypass the local download cache
+        #[arg(long)]
+        no_cache: bool,
+    },
+
+    /// 预览业务码中的 skills（名称、版本、描述、标签），不解压任何文件 / Preview the skills in a code (name, version, description, tags) without extracting anything
+    Show {
+        /// 业务码 / Business code
+        #[arg(short = 'c', long)]
+        code: String,
+
+        /// 不使用本地下载缓存 / Bypass the local download cache
+        #[arg(long)]
+        no_cache: bool,
+    },
+
+    /// 延长业务码的有效期，无需重新上传 / Extend a code's expiry without re-uploading
+    Renew {
+        /// 业务码或别名 / Business code or alias
+        #[arg(short = 'c', long)]
+        code: String,
+
+        /// 从现在起的有效期（如 7d、12h）/ New time to live from now (e.g. 7d, 12h)
+        #[arg(long, value_parser = parse_duration)]
+        expire: Duration,
+    },
+
+    /// 常驻运行，按间隔定时同步 / Stay resident and sync periodically
+    #[command(group(ArgGroup::new("direction").required(true).multiple(true).args(["push", "pull"])))]
+    Sync {
+        /// 同步间隔（如 30m、1h、1h30m）/ Sync interval (e.g. 30m, 1h, 1h30m)
+        #[arg(long, value_parser = parse_duration)]
+        every: Duration,
+
         /// 本地 skills 目录路径 / Local skills directory path
-        #[arg(short = 'd', long)]
-        dir: Option<String>,
+        #[arg(short = 'd', long, value_parser = parse_path)]
+        dir: Option<PathBuf>,
+
+        /// 每轮上传本地变化（无变化时跳过）/ Upload local changes every cycle (skipped when unchanged)
+        #[arg(long)]
+        push: bool,
+
+        /// 每轮下载该业务码 / Download this business code every cycle
+        #[arg(long, value_name = "CODE")]
+        pull: Option<String>,
+
+        /// 推送为该业务码（或别名）的新版本，默认沿用本机上次上传的业务码 / Push as new versions of this code (or alias); defaults to the code of the last upload
+        #[arg(short = 'c', long, value_name = "CODE", requires = "push")]
+        code: Option<String>,
+
+        /// 为推送的业务码请求自定义别名 / Request a custom alias for the pushed code
+        #[arg(long, value_name = "ALIAS", value_parser = parse_alias, requires = "push")]
+        alias: Option<String>,
+    },
+
+    /// 管理本地下载缓存 / Manage the local download cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+
+    /// 按保留策略清理下载缓存、无人引用的同步基准和 skill 变更记录 / Prune the download cache, unreferenced sync bases and skill history by the retention policy
+    Prune {
+        /// 只显示将删除的内容 / Only show what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+
+        /// 保留最近使用的缓存条目数和每个 skill 的变更记录条数（覆盖配置）/ Cache entries and history entries per skill to keep (overrides the config)
+        #[arg(long, value_name = "N")]
+        keep_last: Option<usize>,
+
+        /// 删除超过该时间未使用的内容，如 90d（覆盖配置）/ Delete what was not used for this long, e.g. 90d (overrides the config)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        max_age: Option<Duration>,
+
+        /// 下载缓存总大小上限，如 1G（覆盖配置）/ Total download cache size limit, e.g. 1G (overrides the config)
+        #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+        max_size: Option<u64>,
+    },
+
+    /// 显示本地累计的匿名使用统计（telemetry = true 时收集）/ Show the anonymous usage statistics collected locally (with telemetry = true)
+    Report {
+        /// 输出下次将发送的原始 JSON / Print the exact JSON that will be sent next
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// 查看和修改配置文件 / View and edit the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+
+    /// 管理系统密钥环中的服务器令牌 / Manage server tokens stored in the OS keyring
+    Credentials {
+        #[command(subcommand)]
+        action: CredentialsCommand,
     },
+
+    /// 校验已安装的 skills 与上次同步的内容是否一致 / Check installed skills against the last sync
+    Verify {
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 校验本地归档（清单格式、文件校验值、越界路径、版本兼容性）/ Check a local archive (manifest, file hashes, path traversal, version compatibility)
+    VerifyArchive {
+        /// 归档路径（zip 或 tar.zst）/ Archive path (zip or tar.zst)
+        #[arg(value_parser = parse_path)]
+        archive: PathBuf,
+    },
+
+    /// 重命名本地 skill（目录、front matter 中的 name 及同步状态）/ Rename a local skill (directory, front matter name and sync state)
+    Rename {
+        /// 当前名称（目录名）/ Current name (directory name)
+        old_name: String,
+
+        /// 新名称 / New name
+        new_name: String,
+
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 显示本地 skill 的详情 / Show details of a local skill
+    Info {
+        /// skill 名称（目录名）/ Skill name (directory name)
+        name: String,
+
+        /// 列出每次同步时记录的变更 / List the changes recorded at each sync
+        #[arg(long)]
+        history: bool,
+
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 在 skills 目录的 git 历史仓库中运行 git（需要配置 git_history = true）/ Run git in the history repository of a skills directory (needs git_history = true)
+    Git {
+        /// skills 目录路径，只有一个目录有历史时可省略 / Skills directory (optional when only one has history)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+
+        /// 传给 git 的参数，如 log --stat / Arguments passed to git, e.g. log --stat
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        args: Vec<OsString>,
+    },
+
+    /// 恢复单个 skill：默认恢复为上次同步的内容，--from 指定 git 历史中的快照 / Restore one skill to its last synced content, or to a git history snapshot with --from
+    Restore {
+        /// skill 名称（目录名）/ Skill name (directory name)
+        name: String,
+
+        /// 快照（git_history 的提交 ID，见 --list）/ Snapshot to restore from (a git_history commit, see --list)
+        #[arg(long, value_name = "SNAPSHOT")]
+        from: Option<String>,
+
+        /// 列出涉及该 skill 的快照 / List the snapshots that touch this skill
+        #[arg(long, conflicts_with = "from")]
+        list: bool,
+
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 检查本地 skills 的 front matter：默认按内置规则，--schema 指定自己的 JSON Schema / Lint the front matter of local skills against the built-in rules or your own JSON Schema
+    Validate {
+        /// 描述 front matter 的 JSON Schema 文件（替代内置规则）/ JSON Schema file describing the front matter (replaces the built-in rules)
+        #[arg(long, value_name = "FILE", value_parser = parse_path)]
+        schema: Option<PathBuf>,
+
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 递增 SKILL.md front matter 中的版本号 / Bump the version in a skill's front matter
+    Bump {
+        /// skill 名称（目录名）/ Skill name (directory name)
+        name: String,
+
+        /// 要递增的部分 / Part of the version to increment
+        #[arg(value_enum, default_value_t = BumpLevel::Patch)]
+        level: BumpLevel,
+
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+    },
+
+    /// 将旧版本的配置、状态和归档升级为当前格式 / Upgrade config, state and archives written by older versions
+    Migrate {
+        /// 要升级清单的归档（保留的归档、备份），可重复 / Archives (kept archives, backups) whose manifest to upgrade (repeatable)
+        #[arg(value_parser = parse_path)]
+        archives: Vec<PathBuf>,
+
+        /// 只报告需要迁移的内容，不修改文件 / Only report what would change
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// 从 GitHub Releases 更新到最新版本 / Update this binary to the latest GitHub release
+    SelfUpdate {
+        /// 只检查是否有新版本，不下载 / Only check for a newer release, do not download
+        #[arg(long)]
+        check: bool,
+
+        /// 即使已是最新版本也重新安装 / Reinstall even if already up to date
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// 管理自建服务器 / Manage a self-hosted server
+    Admin {
+        /// 管理员令牌（默认读取 SKILLS_SYNC_ADMIN_TOKEN）/ Admin token (defaults to SKILLS_SYNC_ADMIN_TOKEN)
+        #[arg(long, global = true, value_name = "TOKEN")]
+        admin_token: Option<String>,
+
+        #[command(subcommand)]
+        action: AdminCommand,
+    },
+
+    /// 生成可搜索的静态 HTML 目录 / Generate a searchable static HTML catalog
+    Catalog {
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+
+        /// 输出目录 / Output directory
+        #[arg(short = 'o', long, default_value = "./site", value_parser = parse_path)]
+        out: PathBuf,
+    },
+
+    /// 导出为 Claude Code 插件结构 / Export skills as a Claude Code plugin
+    Export {
+        /// 本地 skills 目录路径，可重复，支持 glob / Local skills directory path (repeatable, globs allowed)
+        #[arg(short = 'd', long = "dir", value_name = "DIR", value_parser = parse_path)]
+        dirs: Vec<PathBuf>,
+
+        /// 输出目录 / Output directory
+        #[arg(short = 'o', long, value_parser = parse_path, required_unless_present = "bundle")]
+        out: Option<PathBuf>,
+
+        /// 插件名称 / Plugin name
+        #[arg(long, required_unless_present = "bundle")]
+        name: Option<String>,
+
+        /// 插件版本 / Plugin version
+        #[arg(long, default_value = "1.0.0")]
+        version: String,
+
+        /// 插件描述 / Plugin description
+        #[arg(long)]
+        description: Option<String>,
+
+        /// 作者 / Author
+        #[arg(long)]
+        author: Option<String>,
+
+        /// 只导出指定的 skill，可重复 / Only export this skill (repeatable)
+        #[arg(long = "skill", value_name = "NAME")]
+        skills: Vec<String>,
+
+        /// 同时生成 marketplace 清单 / Also write a marketplace manifest
+        #[arg(long)]
+        marketplace: bool,
+
+        /// 改为生成签名的离线 bundle 文件（归档 + 清单 + 签名），用于拷贝到隔离环境 / Write a signed offline bundle (archive + manifest + signature) instead, for air-gapped transfer
+        #[arg(long, value_name = "FILE", value_parser = parse_path, conflicts_with_all = ["out", "name", "marketplace"])]
+        bundle: Option<PathBuf>,
+
+        /// 同时导出 front matter 中标记为 sync: false 或 private: true 的 skill / Also export skills marked sync: false or private: true in their front matter
+        #[arg(long)]
+        include_private: bool,
+    },
+
+    /// 导入 export --bundle 生成的 bundle / Import a bundle written by export --bundle
+    Import {
+        /// bundle 文件 / Bundle file
+        #[arg(value_parser = parse_path)]
+        bundle: PathBuf,
+
+        /// 拒绝未签名或签名公钥不受信任的 bundle / Refuse unsigned bundles and bundles signed by an untrusted key
+        #[arg(long)]
+        require_signature: bool,
+
+        /// 信任该签名公钥（十六进制），可重复 / Trust this signing public key (hex, repeatable)
+        #[arg(long = "trusted-key", value_name = "KEY")]
+        trusted_keys: Vec<String>,
+    },
+
+    /// 通过 stdio 运行 MCP 服务，供 Claude / Codex 管理和分享 skills / Run a Model Context Protocol server on stdio
+    Mcp,
+
+    /// 未知子命令交给 PATH 中的 skills-sync-<name> 插件执行
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
+}
+
+/// list 命令的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// 按来源分组的表格 / Tables grouped by source
+    Table,
+    /// CSV 清单 / CSV inventory
+    Csv,
+    /// TSV 清单 / TSV inventory
+    Tsv,
+}
+
+/// 下载时本地与远端都修改过的 skill 的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    /// 按行三方合并，无法合并处写入冲突标记 / Merge line by line, writing conflict markers where needed
+    #[default]
+    Merge,
+    /// 显示差异并逐个询问保留本地、采用远端、合并、另存或跳过 / Show a diff and ask per skill: keep local, take remote, merge, rename or skip
+    Prompt,
+}
+
+/// bump 命令递增的版本部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BumpLevel {
+    /// 主版本号，如 1.2.3 -> 2.0.0 / Major version, e.g. 1.2.3 -> 2.0.0
+    Major,
+    /// 次版本号，如 1.2.3 -> 1.3.0 / Minor version, e.g. 1.2.3 -> 1.3.0
+    Minor,
+    /// 修订号，如 1.2.3 -> 1.2.4 / Patch version, e.g. 1.2.3 -> 1.2.4
+    Patch,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheCommand {
+    /// 清空下载缓存 / Clear the download cache
+    Clear,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// 输出一个配置项的值 / Print the value of a setting
+    Get {
+        /// 配置项，嵌套的表用点分隔（如 hooks.post-upload）/ Setting name, nested tables separated by dots (e.g. hooks.post-upload)
+        key: String,
+    },
+
+    /// 修改一个配置项 / Change a setting
+    Set {
+        /// 配置项，嵌套的表用点分隔 / Setting name, nested tables separated by dots
+        key: String,
+
+        /// 值（按 TOML 解析，否则作为字符串）/ Value (parsed as TOML, otherwise taken as a string)
+        value: String,
+    },
+
+    /// 列出所有生效的配置项 / List all effective settings
+    List,
+
+    /// 用 $VISUAL / $EDITOR 打开配置文件 / Open the config file in $VISUAL / $EDITOR
+    Edit,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CredentialsCommand {
+    /// 保存令牌（终端中提示输入，否则从标准输入读取）/ Store a token (prompted on a terminal, otherwise read from stdin)
+    Set {
+        /// 服务器地址或配置名（默认 default）/ Server URL or profile name (default: default)
+        profile: Option<String>,
+    },
+
+    /// 查看是否保存了令牌 / Show whether a token is stored
+    Get {
+        /// 服务器地址或配置名（默认 default）/ Server URL or profile name (default: default)
+        profile: Option<String>,
+
+        /// 输出完整令牌 / Print the full token
+        #[arg(long)]
+        show: bool,
+    },
+
+    /// 删除令牌 / Delete a stored token
+    Delete {
+        /// 服务器地址或配置名（默认 default）/ Server URL or profile name (default: default)
+        profile: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminCommand {
+    /// 清理过期业务码、无引用数据和超出配额的数据 / Purge expired codes, orphaned blobs and over-quota data
+    Gc {
+        /// 只报告可回收的空间，不删除 / Only report reclaimable space, delete nothing
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// 列出服务器上的所有业务码 / List all codes on the server
+    Codes {
+        /// 最多显示的数量 / Maximum number of codes to show
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+    },
+
+    /// 查看存储用量 / Inspect storage usage
+    Usage,
+
+    /// 吊销业务码，立即删除其归档 / Revoke a code and delete its archive
+    Revoke {
+        /// 业务码或别名 / Business code or alias
+        #[arg(short = 'c', long)]
+        code: String,
+    },
+
+    /// 管理 API 令牌 / Manage API tokens
+    Tokens {
+        #[command(subcommand)]
+        action: AdminTokenCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AdminTokenCommand {
+    /// 列出 API 令牌 / List API tokens
+    List,
+
+    /// 签发新的 API 令牌 / Issue a new API token
+    Create {
+        /// 令牌所属用户或用途 / User or purpose the token belongs to
+        #[arg(long)]
+        name: String,
+
+        /// 存储配额（如 500M、2G）/ Storage quota (e.g. 500M, 2G)
+        #[arg(long, value_parser = parse_size)]
+        quota: Option<u64>,
+    },
+
+    /// 吊销 API 令牌 / Revoke an API token
+    Revoke {
+        /// 令牌 ID / Token ID
+        id: String,
+    },
+}
+
+/// 解析带单位的大小，如 512、64K、50M、1G、10MiB
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size / 无效的大小: {}", s))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("invalid size unit / 无效的大小单位: {}", unit)),
+    };
+
+    let size = number * multiplier as f64;
+    if size >= u64::MAX as f64 {
+        return Err(format!("size too large / 大小过大: {}", s));
+    }
+    let size = size as u64;
+    if size == 0 {
+        return Err("size must be greater than 0 / 大小必须大于 0".to_string());
+    }
+    Ok(size)
+}
+
+/// 解析时间间隔，如 90s、30m、1h30m、1d，纯数字按秒处理
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if let Ok(secs) = s.parse::<u64>() {
+        return if secs == 0 {
+            Err("interval must be greater than 0 / 间隔必须大于 0".to_string())
+        } else {
+            Ok(Duration::from_secs(secs))
+        };
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid interval / 无效的间隔: {}", s))?;
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(format!("invalid interval unit / 无效的间隔单位: {}", c)),
+        };
+        total = value
+            .checked_mul(unit)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(|| format!("interval too large / 间隔过大: {}", s))?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(format!("missing unit in interval / 间隔缺少单位: {}", s));
+    }
+
+    if total == 0 {
+        return Err("interval must be greater than 0 / 间隔必须大于 0".to_string());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// 解析路径参数：展开 ~ 和环境变量，相对路径转换为绝对路径
+pub fn parse_path(s: &str) -> Result<PathBuf, String> {
+    expand_path(s).map_err(|e| e.to_string())
+}
+
+/// 校验别名：3-64 个字母、数字、"-" 或 "_"
+pub fn parse_alias(s: &str) -> Result<String, String> {
+    let valid = (3..=64).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(s.to_string())
+    } else {
+        Err("alias must be 3-64 letters, digits, '-' or '_' / 别名须为 3-64 个字母、数字、\"-\" 或 \"_\"".to_string())
+    }
+}
+
+/// 解析 glob 模式
+pub fn parse_glob(s: &str) -> Result<glob::Pattern, String> {
+    glob::Pattern::new(s).map_err(|e| format!("invalid glob pattern / 无效的 glob 模式: {}", e))
+}
+
+/// 解析 DNS 覆盖 HOST:PORT:ADDR，ADDR 为 IPv6 时可以带方括号，如 example.com:443:[::1]
+pub fn parse_resolve(s: &str) -> Result<(String, SocketAddr), String> {
+    let invalid = || format!("expected HOST:PORT:ADDR / 格式应为 HOST:PORT:ADDR: {}", s);
+    let mut parts = s.splitn(3, ':');
+    let (Some(host), Some(port), Some(addr)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    let addr: IpAddr = addr
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .map_err(|_| format!("invalid IP address / 无效的 IP 地址: {}", addr))?;
+    Ok((host.to_ascii_lowercase(), SocketAddr::new(addr, port)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("256K"), Ok(256 * 1024));
+        assert_eq!(parse_size("1.5m"), Ok(1024 * 1024 * 3 / 2));
+        assert_eq!(parse_size(" 2 GiB "), Ok(2 * 1024 * 1024 * 1024));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("10X").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("99999999999999999999G").is_err());
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("1D"), Ok(Duration::from_secs(24 * 60 * 60)));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("1h30").is_err());
+        assert!(parse_duration("3w").is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_durations() {
+        assert!(parse_duration("18446744073709551615d").is_err());
+        assert!(parse_duration("18446744073709551615s1s").is_err());
+    }
+
+    #[test]
+    fn parses_resolve_overrides() {
+        assert_eq!(
+            parse_resolve("Sync.Example.com:443:10.0.0.5"),
+            Ok(("sync.example.com".to_string(), "10.0.0.5:443".parse().unwrap()))
+        );
+        assert_eq!(
+            parse_resolve("sync.example.com:8080:[::1]"),
+            Ok(("sync.example.com".to_string(), "[::1]:8080".parse().unwrap()))
+        );
+        assert!(parse_resolve("sync.example.com:10.0.0.5").is_err());
+        assert!(parse_resolve(":443:10.0.0.5").is_err());
+        assert!(parse_resolve("sync.example.com:443:not-an-ip").is_err());
+    }
 }
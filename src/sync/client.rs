@@ -1,16 +1,51 @@
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use comfy_table::{presets::UTF8_FULL, ContentArrangement, Table};
-use indicatif::{ProgressBar, ProgressStyle};
-use regex::Regex;
-use reqwest::Client;
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use reqwest::{Client, StatusCode};
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use ignore::WalkBuilder;
 use walkdir::WalkDir;
-use zip::ZipWriter;
+
+use crate::sync::api;
+use crate::sync::archive::{self, ArchiveFormat, ArchiveWriter};
+use crate::sync::cache;
+use crate::sync::cli::{ListFormat, OnConflict};
+use crate::sync::encoding;
+use crate::sync::filter::AssetFilter;
+use crate::sync::grpc;
+use crate::sync::history::{self, HistoryEvent};
+use crate::sync::hooks::{HookContext, HookEvent, HooksConfig};
+use crate::sync::http;
+use crate::sync::lock::lock_roots;
+use crate::sync::manifest::{
+    source_from_path, upgrade_notice, Manifest, ManifestEntry, MANIFEST_JSON, MANIFEST_TXT,
+    UPGRADE_NOTICE,
+};
+use crate::sync::merge::{store_blob, three_way_merge, MergeOutcome};
+use crate::sync::paths::{
+    encode_path, expand_path, local_path, long_path, normalize_nfc, original_path, strip_long_prefix, was_renamed,
+};
+use crate::sync::parts::{file_sha256, join_files, split_file, PartInfo, PartsIndex};
+use crate::sync::policy::Policy;
+use crate::sync::progress;
+use crate::sync::redact::Redactor;
+use crate::sync::resolve::{ContestedFile, Resolution, Resolver};
+use crate::sync::server_error;
+use crate::sync::skill::{self, SkillMetadata};
+use crate::sync::state::{SkillSyncTimes, SyncState, UploadRecord};
+use crate::sync::summary::{self, FileRecord};
+use crate::sync::table::{self, CellFormat, ListColumn, ListStyle};
+use crate::sync::temp::TempArchive;
+use crate::sync::ws;
 
 /// IP API 响应结构（ipapi.co）
 #[derive(Deserialize)]
@@ -52,6 +87,23 @@ pub async fn auto_select_server() -> String {
     }
 }
 
+/// 使用指定的服务器，未指定时根据地理位置自动选择；请求了 HTTP/3 时探测服务器是否支持，
+/// 并与服务器协商 API 版本，不兼容时报错
+pub async fn resolve_server(server: Option<String>) -> Result<String> {
+    let server_url = if let Some(s) = server {
+        match http::unix_socket() {
+            Some(path) => println!("🌐 Using server socket / 使用服务器套接字: {}", path.display()),
+            None => println!("🌐 Using specified server / 使用指定服务器: {}", s),
+        }
+        s
+    } else {
+        auto_select_server().await
+    };
+    http::negotiate_http3(&server_url).await;
+    api::negotiate(&server_url).await?;
+    Ok(server_url)
+}
+
 /// 检测用户所在国家代码（支持多个备用 API）
 async fn detect_country() -> Result<String> {
     let mut errors = Vec::new();
@@ -79,7 +131,7 @@ async fn detect_country() -> Result<String> {
 
 /// 通过 ipapi.co 检测国家
 async fn detect_via_ipapi() -> Result<String> {
-    let client = Client::builder()
+    let client = http::apply_network(Client::builder())
         .timeout(std::time::Duration::from_secs(5))
         .build()?;
 
@@ -95,7 +147,7 @@ async fn detect_via_ipapi() -> Result<String> {
 
 /// 通过 ipinfo.io 检测国家
 async fn detect_via_ipinfo() -> Result<String> {
-    let client = Client::builder()
+    let client = http::apply_network(Client::builder())
         .timeout(std::time::Duration::from_secs(5))
         .build()?;
 
@@ -111,7 +163,7 @@ async fn detect_via_ipinfo() -> Result<String> {
 
 /// 通过 ifconfig.co 检测国家
 async fn detect_via_ifconfig() -> Result<String> {
-    let client = Client::builder()
+    let client = http::apply_network(Client::builder())
         .timeout(std::time::Duration::from_secs(5))
         .build()?;
 
@@ -125,6 +177,239 @@ async fn detect_via_ifconfig() -> Result<String> {
     info.country.context("No country field in response")
 }
 
+/// 打包参数
+#[derive(Debug, Clone)]
+pub struct PackOptions {
+    /// 归档格式
+    pub format: ArchiveFormat,
+    /// 压缩级别，None 表示使用格式默认值
+    pub compression_level: Option<i64>,
+    /// 并发读取文件的线程数
+    pub jobs: usize,
+    /// 增量打包的基础：与该次上传相比未变化的文件不再存入归档
+    pub base: Option<UploadRecord>,
+    /// 按扩展名、MIME 类型或二进制内容过滤文件
+    pub filter: AssetFilter,
+    /// 改写归档中文本内容的脱敏规则
+    pub redact: Redactor,
+}
+
+/// 上传参数
+#[derive(Debug, Clone)]
+pub struct UploadOptions {
+    /// 打包参数
+    pub pack: PackOptions,
+    /// 单个分卷最大大小
+    pub max_part_size: Option<u64>,
+    /// 保留归档（空字符串表示原地保留）
+    pub keep: Option<String>,
+    /// 仅上传自上次上传以来变化的文件
+    pub delta: bool,
+    /// 排除匹配的 skill
+    pub exclude: Vec<glob::Pattern>,
+    /// 只上传这些名称的 skill（为空时上传全部）
+    pub skills: Vec<String>,
+    /// 只上传名称或相对路径匹配的 skill（为空时上传全部）
+    pub names: Vec<glob::Pattern>,
+    /// 扫描时遵循 .gitignore 规则
+    pub gitignore: bool,
+    /// 打包每个 skill 目录下的全部文件，而不只是 SKILL.md
+    pub whole_dir: bool,
+    /// 单个文件最大大小，超出时中止上传
+    pub max_file_size: Option<u64>,
+    /// 作为该业务码的新版本上传（需为本机上传的业务码）
+    pub code: Option<String>,
+    /// 请求映射到业务码的自定义别名
+    pub alias: Option<String>,
+    /// 文件集合与该服务器上次上传相同时跳过上传（定时同步使用）
+    pub skip_unchanged: bool,
+    /// 同时打包 front matter 中标记为私有（sync: false / private: true）的 skill
+    pub include_private: bool,
+    /// 打包前检查的元数据规范（upload --strict），有不符合的 skill 时中止
+    pub policy: Option<Policy>,
+    /// 钩子配置
+    pub hooks: HooksConfig,
+}
+
+/// 下载参数
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// 并发下载数
+    pub jobs: usize,
+    /// 保留归档（空字符串表示原地保留）
+    pub keep: Option<String>,
+    /// 仅保存归档到该路径，不解压
+    pub archive_out: Option<String>,
+    /// 不使用本地下载缓存
+    pub no_cache: bool,
+    /// 只解压匹配的 skill（为空时解压全部）
+    pub include: Vec<glob::Pattern>,
+    /// 本地与远端都修改过的 skill 的处理方式
+    pub on_conflict: OnConflict,
+    /// 钩子配置
+    pub hooks: HooksConfig,
+}
+
+/// 列表参数
+#[derive(Debug, Clone)]
+pub struct ListOptions {
+    /// 输出格式
+    pub format: ListFormat,
+    /// 命令行指定的列（同时作用于 CSV / TSV 清单），未指定时表格使用样式中的列
+    pub columns: Option<Vec<ListColumn>>,
+    /// 单元格显示方式（描述截断只作用于表格，CSV / TSV 清单中的描述始终完整）
+    pub cells: CellFormat,
+    /// 表格样式（配置中的 [list] 段）
+    pub style: ListStyle,
+    /// 只列出超过该时间未同步（或从未同步）的 skills
+    pub stale: Option<std::time::Duration>,
+}
+
+/// 解压结果
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    /// 直接写入远端内容的文件
+    pub extracted: Vec<String>,
+    /// 自动三方合并成功的文件
+    pub merged: Vec<String>,
+    /// 存在合并冲突、已写入冲突标记的文件
+    pub conflicts: Vec<String>,
+    /// 仅本地修改过而保留本地内容的文件
+    pub kept_local: Vec<String>,
+}
+
+impl ExtractReport {
+    /// 实际写入磁盘的文件（不含保留本地内容的文件）
+    pub fn written(self) -> impl Iterator<Item = String> {
+        self.extracted.into_iter().chain(self.merged).chain(self.conflicts)
+    }
+
+    /// 显示解压的文件列表
+    fn print(&self) {
+        if self.extracted.is_empty()
+            && self.merged.is_empty()
+            && self.conflicts.is_empty()
+            && self.kept_local.is_empty()
+        {
+            return;
+        }
+
+        println!();
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        println!("  Extracted files / 解压文件:");
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        for file in &self.extracted {
+            println!("  ✓ {}", file);
+        }
+        for file in &self.merged {
+            println!("  ⇄ {} (merged / 已合并)", file);
+        }
+        for file in &self.kept_local {
+            println!("  • {} (local changes kept / 保留本地修改)", file);
+        }
+        for file in &self.conflicts {
+            println!("  ✗ {} (conflict / 冲突)", file);
+        }
+        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+        if !self.conflicts.is_empty() {
+            println!(
+                "⚠️  {} files have merge conflicts, resolve the <<<<<<< markers manually / {} 个文件存在合并冲突，请手动处理冲突标记",
+                self.conflicts.len(),
+                self.conflicts.len()
+            );
+        }
+    }
+}
+
+/// 打包结果
+pub struct PackedArchive {
+    /// 归档 SHA256
+    pub sha256: String,
+    /// 写入归档的 manifest
+    pub manifest: Manifest,
+    /// 实际存入归档的文件数（增量打包时不含未变化的文件）
+    pub stored_count: usize,
+    /// 被脱敏规则改写的文件：原始路径 -> 本地原文的 SHA256
+    pub local: BTreeMap<String, String>,
+}
+
+/// 默认并发数：CPU 核数（获取失败时为 4）
+pub fn default_jobs() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+/// 按输入顺序逐个取出文件内容的读取器，见 read_files_ordered
+struct OrderedReader {
+    /// 第 w 个读取线程的结果通道，该线程依次读取第 w、w+jobs、w+2*jobs ... 个文件
+    receivers: Vec<mpsc::Receiver<std::io::Result<Vec<u8>>>>,
+    next: usize,
+    len: usize,
+}
+
+impl Iterator for OrderedReader {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.len {
+            return None;
+        }
+        let receiver = &self.receivers[self.next % self.receivers.len()];
+        self.next += 1;
+        Some(
+            receiver
+                .recv()
+                .unwrap_or_else(|_| Err(std::io::Error::other("file reader thread stopped / 读取线程已退出"))),
+        )
+    }
+}
+
+/// 使用最多 jobs 个线程并行读取文件内容，按输入顺序返回
+///
+/// 每个线程读完一个文件后等待它被取走才读取下一个，已读取但尚未处理的文件最多 jobs 个，
+/// 内存占用不随文件数增长；读取器被提前丢弃时各线程随之退出
+fn read_files_ordered(files: &[PathBuf], jobs: usize) -> OrderedReader {
+    let workers = jobs.max(1).min(files.len());
+    let receivers = (0..workers)
+        .map(|worker| {
+            let (sender, receiver) = mpsc::sync_channel(0);
+            let assigned: Vec<PathBuf> = files.iter().skip(worker).step_by(workers).cloned().collect();
+            std::thread::spawn(move || {
+                for path in assigned {
+                    if sender.send(fs::read(long_path(&path))).is_err() {
+                        break;
+                    }
+                }
+            });
+            receiver
+        })
+        .collect();
+    OrderedReader { receivers, next: 0, len: files.len() }
+}
+
+/// 是否有内容相同的文件（打包时只存储一次，清单需为 v3）：使用最多 jobs 个线程流式计算 SHA256，不把文件读入内存；
+/// 读取失败的文件不参与比较（打包时会报告）
+fn has_duplicate_content(files: &[PathBuf], jobs: usize) -> bool {
+    if files.is_empty() {
+        return false;
+    }
+    let chunk_size = files.len().div_ceil(jobs.max(1));
+    let hashes: Vec<String> = std::thread::scope(|scope| {
+        let handles: Vec<_> = files
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().filter_map(|p| file_sha256(&long_path(p)).ok()).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("file hashing thread panicked"))
+            .collect()
+    });
+    let mut seen = HashSet::new();
+    hashes.into_iter().any(|hash| !seen.insert(hash))
+}
+
 /// 获取默认的 skills 目录路径列表（.claude/skills 和 .codex/skills）
 fn get_default_skills_dirs() -> Result<Vec<PathBuf>> {
     let home_dir = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
@@ -134,50 +419,323 @@ fn get_default_skills_dirs() -> Result<Vec<PathBuf>> {
     ])
 }
 
+/// 确定要扫描的 skills 目录：未指定时使用默认目录，指定的路径中含有 glob 通配符时展开为所有匹配的目录
+pub(crate) fn resolve_skill_dirs(dirs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    if dirs.is_empty() {
+        return get_default_skills_dirs();
+    }
+
+    let mut resolved = Vec::new();
+    for dir in dirs {
+        let pattern = dir.to_string_lossy();
+        if !pattern.contains(['*', '?', '[']) {
+            resolved.push(dir);
+            continue;
+        }
+
+        let matches: Vec<PathBuf> = glob::glob(&pattern)
+            .with_context(|| format!("Invalid glob pattern / 无效的 glob 模式: {}", pattern))?
+            .filter_map(|m| m.ok())
+            .filter(|p| p.is_dir())
+            .collect();
+        if matches.is_empty() {
+            // 输出到 stderr，避免混入 list --format csv 等机器可读输出
+            eprintln!("⚠️  No directories match / 没有匹配的目录: {}", pattern);
+        }
+        resolved.extend(matches);
+    }
+
+    // 多个参数可能匹配到同一个目录
+    let mut seen = HashSet::new();
+    resolved.retain(|d| seen.insert(d.clone()));
+    Ok(resolved)
+}
+
+/// 是否是 skill 的入口文件（SKILL.md / skill.md）
+fn is_skill_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|n| n == std::ffi::OsStr::new("SKILL.md") || n == std::ffi::OsStr::new("skill.md"))
+}
+
+/// 列出目录下的所有文件（不跟随符号链接），max_depth 为 None 时不限深度；每遍历一项（文件或目录）推进一次 progress
+///
+/// gitignore 为 true 时遵循 .gitignore / .ignore 规则（不要求目录位于 git 仓库中），被忽略的文件和目录会被跳过
+fn walk_files(dir: &Path, max_depth: Option<usize>, gitignore: bool, progress: &ProgressBar) -> Vec<PathBuf> {
+    // Windows 上深层目录可能超过 MAX_PATH，遍历时使用长路径
+    let paths: Vec<PathBuf> = if gitignore {
+        WalkBuilder::new(long_path(dir))
+            .max_depth(max_depth)
+            .standard_filters(false)
+            .git_ignore(true)
+            .git_global(true)
+            .git_exclude(true)
+            .ignore(true)
+            .parents(true)
+            .require_git(false)
+            .build()
+            .inspect(|_| progress.inc(1))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.depth() >= 1 && e.file_type().is_some_and(|t| t.is_file()))
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        let mut walker = WalkDir::new(long_path(dir)).min_depth(1);
+        if let Some(depth) = max_depth {
+            walker = walker.max_depth(depth);
+        }
+        walker
+            .into_iter()
+            .inspect(|_| progress.inc(1))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect()
+    };
+    paths.iter().map(|p| strip_long_prefix(p)).collect()
+}
+
 /// 扫描目录列表下所有子目录中的 SKILL.md 文件
-pub fn scan_skill_files(base_dirs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+///
+/// gitignore 为 true 时遵循 .gitignore / .ignore 规则（不要求目录位于 git 仓库中），被忽略的文件和目录不会被打包
+///
+/// 网络文件系统或条目很多的目录可能扫描较久，扫描时显示已遍历的条目数和用时，每个目录扫描完后给出统计
+pub fn scan_skill_files(base_dirs: &[PathBuf], gitignore: bool) -> Result<Vec<PathBuf>> {
     let mut skill_files = Vec::new();
 
     for base_dir in base_dirs {
-        println!("🔍 Scanning directory / 扫描目录: {}", base_dir.display());
-
         if !base_dir.exists() {
-            println!("⚠️  Directory not found, skipping / 目录不存在，跳过: {}", base_dir.display());
+            summary::warn(&format!("Directory not found, skipping / 目录不存在，跳过: {}", base_dir.display()));
             continue;
         }
 
-        for entry in WalkDir::new(base_dir)
-            .min_depth(1)
-            .max_depth(3)
+        let pb = progress::spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] 🔍 {pos} entries scanned / 已扫描 {pos} 项: {msg}")?,
+        );
+        pb.set_message(base_dir.display().to_string());
+        pb.enable_steady_tick(std::time::Duration::from_millis(100));
+
+        let found: Vec<PathBuf> = walk_files(base_dir, Some(3), gitignore, &pb)
             .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            if path.file_name() == Some(std::ffi::OsStr::new("SKILL.md"))
-                || path.file_name() == Some(std::ffi::OsStr::new("skill.md"))
-            {
-                skill_files.push(path.to_path_buf());
-            }
-        }
+            .filter(|p| is_skill_file(p))
+            .collect();
+        pb.finish_and_clear();
+        println!(
+            "🔍 Scanned {}: {} SKILL.md in {} entries ({:.1}s) / 已扫描 {}：{} 项中有 {} 个 SKILL.md（用时 {:.1}s）",
+            base_dir.display(),
+            found.len(),
+            pb.position(),
+            pb.elapsed().as_secs_f64(),
+            base_dir.display(),
+            pb.position(),
+            found.len(),
+            pb.elapsed().as_secs_f64()
+        );
+        skill_files.extend(found);
     }
 
     println!("📄 Found {} SKILL.md files / 找到 {} 个 SKILL.md 文件", skill_files.len(), skill_files.len());
+    summary::record(|s| s.scanned += skill_files.len());
     Ok(skill_files)
 }
 
-/// 创建包含所有 SKILL.md 的 zip 文件
-/// Zip 结构：
+/// 整目录打包：把每个 SKILL.md 展开为所在 skill 目录下的全部文件（含 SKILL.md 本身）
+///
+/// gitignore 为 true 时同样遵循 .gitignore 规则，build 产物、node_modules 等不会进入归档；嵌套 skill 的文件只出现一次
+pub(crate) fn expand_skill_dirs(skill_files: Vec<PathBuf>, gitignore: bool) -> Vec<PathBuf> {
+    let skill_count = skill_files.len();
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+    for skill_file in skill_files {
+        let dir_files = match skill_file.parent() {
+            Some(dir) => walk_files(dir, None, gitignore, &ProgressBar::hidden()),
+            None => Vec::new(),
+        };
+        // 入口文件已在扫描时确认存在，即使被 .gitignore 忽略也保留
+        for file in std::iter::once(skill_file).chain(dir_files) {
+            if seen.insert(file.clone()) {
+                files.push(file);
+            }
+        }
+    }
+
+    println!(
+        "📁 {} files in {} skill directories / {} 个 skill 目录共 {} 个文件",
+        files.len(),
+        skill_count,
+        skill_count,
+        files.len()
+    );
+    files
+}
+
+/// skill 是否匹配任一模式：模式与 skill 名称（目录名）或相对所在 skills 目录的路径匹配
+fn skill_matches(file: &Path, base_dirs: &[PathBuf], patterns: &[glob::Pattern]) -> bool {
+    let name = file
+        .parent()
+        .and_then(|p| p.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let relative = base_dirs
+        .iter()
+        .find_map(|base| file.strip_prefix(base).ok())
+        .map(|r| r.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+    patterns
+        .iter()
+        .any(|p| p.matches(&name) || (!relative.is_empty() && p.matches(&relative)))
+}
+
+/// 只保留匹配任一名称模式的 skill（patterns 为空时全部保留）
+fn include_skills(skill_files: Vec<PathBuf>, base_dirs: &[PathBuf], patterns: &[glob::Pattern]) -> Vec<PathBuf> {
+    if patterns.is_empty() {
+        return skill_files;
+    }
+    let kept: Vec<PathBuf> = skill_files
+        .into_iter()
+        .filter(|file| skill_matches(file, base_dirs, patterns))
+        .collect();
+    println!("🔎 {} skills match --name / {} 个 skill 匹配 --name", kept.len(), kept.len());
+    kept
+}
+
+/// 去掉匹配任一排除模式的 skill
+fn exclude_skills(skill_files: Vec<PathBuf>, base_dirs: &[PathBuf], exclude: &[glob::Pattern]) -> Vec<PathBuf> {
+    if exclude.is_empty() {
+        return skill_files;
+    }
+
+    let mut excluded = Vec::new();
+    let kept: Vec<PathBuf> = skill_files
+        .into_iter()
+        .filter(|file| {
+            let matched = skill_matches(file, base_dirs, exclude);
+            if matched {
+                excluded.push(file.display().to_string());
+            }
+            !matched
+        })
+        .collect();
+
+    if !excluded.is_empty() {
+        println!("🚫 Excluded {} files / 已排除 {} 个文件:", excluded.len(), excluded.len());
+        for file in &excluded {
+            println!("  - {}", file);
+        }
+    }
+    kept
+}
+
+/// 只保留目录名为 names 之一的 skill（names 为空时全部保留），有名称未找到时报错
+pub(crate) fn select_skills(skill_files: Vec<PathBuf>, names: &[String]) -> Result<Vec<PathBuf>> {
+    if names.is_empty() {
+        return Ok(skill_files);
+    }
+    let skill_name = |file: &PathBuf| {
+        file.parent()
+            .and_then(|p| p.file_name())
+            .map(|n| normalize_nfc(&n.to_string_lossy()))
+            .unwrap_or_default()
+    };
+    let wanted: HashSet<String> = names.iter().map(|n| normalize_nfc(n)).collect();
+    let selected: Vec<PathBuf> = skill_files.into_iter().filter(|f| wanted.contains(&skill_name(f))).collect();
+    let found: HashSet<String> = selected.iter().map(skill_name).collect();
+    let mut missing: Vec<&String> = wanted.iter().filter(|n| !found.contains(*n)).collect();
+    if !missing.is_empty() {
+        missing.sort();
+        let missing: Vec<&str> = missing.into_iter().map(String::as_str).collect();
+        return Err(anyhow::anyhow!("Skill not found / 未找到 skill: {}", missing.join(", ")));
+    }
+    Ok(selected)
+}
+
+/// 去掉 front matter 中标记为私有（sync: false / private: true）的 skill，include_private 为 true 时全部保留
+pub(crate) fn exclude_private(skill_files: Vec<PathBuf>, include_private: bool) -> Vec<PathBuf> {
+    if include_private {
+        return skill_files;
+    }
+    let (private, kept): (Vec<PathBuf>, Vec<PathBuf>) = skill_files.into_iter().partition(|file| {
+        fs::read_to_string(long_path(file))
+            .ok()
+            .and_then(|content| skill::parse(&content))
+            .is_some_and(|metadata| metadata.private)
+    });
+    if !private.is_empty() {
+        println!(
+            "🔒 Skipped {} private skills (use --include-private to include them) / 跳过了 {} 个私有 skill（--include-private 可包含）:",
+            private.len(),
+            private.len()
+        );
+        for file in &private {
+            println!("  - {}", file.display());
+        }
+    }
+    kept
+}
+
+/// 未指定 --max-file-size 时，超过该大小的文件只给出警告
+const LARGE_FILE_WARNING: u64 = 1024 * 1024;
+
+/// 打包前检查文件大小：列出过大的文件，超过 max_file_size 时中止，否则超过 LARGE_FILE_WARNING 时警告
+///
+/// 整目录打包时检查的是展开后的全部文件，脚本目录中的大文件也会被列出
+fn check_file_sizes(skill_files: &[PathBuf], max_file_size: Option<u64>) -> Result<()> {
+    let limit = max_file_size.unwrap_or(LARGE_FILE_WARNING);
+    let mut oversized: Vec<(u64, &PathBuf)> = skill_files
+        .iter()
+        .filter_map(|f| fs::metadata(long_path(f)).ok().map(|m| (m.len(), f)))
+        .filter(|(size, _)| *size > limit)
+        .collect();
+    if oversized.is_empty() {
+        return Ok(());
+    }
+    oversized.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+    let total: u64 = oversized.iter().map(|(size, _)| size).sum();
+
+    println!(
+        "⚠️  {} files larger than {} / {} 个文件超过 {}:",
+        oversized.len(),
+        HumanBytes(limit),
+        oversized.len(),
+        HumanBytes(limit)
+    );
+    for (size, file) in &oversized {
+        println!("  - {} ({})", file.display(), HumanBytes(*size));
+    }
+    println!("  {} in total / 共 {}", HumanBytes(total), HumanBytes(total));
+
+    if max_file_size.is_some() {
+        return Err(anyhow::anyhow!(
+            "{} files exceed --max-file-size {} / {} 个文件超过 --max-file-size {}",
+            oversized.len(),
+            HumanBytes(limit),
+            oversized.len(),
+            HumanBytes(limit)
+        ));
+    }
+    Ok(())
+}
+
+/// 创建包含所有 SKILL.md 的归档文件（zip 或 tar.zst）
+/// 归档结构：
 ///   - skill1.md
+///   - skill1/scripts/run.sh (整目录打包时 skill 目录下的其他文件)
 ///   - skill2.md
 ///   - ...
-///   - manifest.txt (记录每个文件来源：文件名=原始路径)
-pub fn create_skills_zip(skill_files: &[PathBuf], zip_path: &Path) -> Result<String> {
-    let file = fs::File::create(zip_path).context("Failed to create zip file / 创建 zip 文件失败")?;
-    let mut zip = ZipWriter::new(file);
-    let options: zip::write::FileOptions<'_, ()> =
-        zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-
-    let pb = ProgressBar::new(skill_files.len() as u64);
+///   - manifest.json (v2/v3 清单：每个文件的来源、SHA256、大小及 skill 信息)
+///   - manifest.txt (v1 清单：归档内文件名=原始路径，供旧版客户端使用)
+///
+/// 文件按路径排序，归档条目的时间戳和权限固定；manifest 记录创建时间，设置了 SOURCE_DATE_EPOCH 时
+/// 使用该时间，此时相同输入总是得到相同的字节和 SHA256
+pub fn create_skills_zip(skill_files: &[PathBuf], zip_path: &Path, options: &PackOptions) -> Result<PackedArchive> {
+    let mut writer = ArchiveWriter::create(zip_path, options.format, options.compression_level)?;
+
+    // 按路径排序，保证条目顺序和重名后缀与扫描顺序无关
+    let mut skill_files = skill_files.to_vec();
+    skill_files.sort();
+
+    let pb = progress::bar(skill_files.len() as u64);
     pb.set_style(
         ProgressStyle::default_bar()
             .template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")?
@@ -186,64 +744,220 @@ pub fn create_skills_zip(skill_files: &[PathBuf], zip_path: &Path) -> Result<Str
 
     println!("📦 Starting to package SKILL.md files / 开始打包 SKILL.md 文件...");
 
-    let mut manifest_lines = Vec::new();
+    let mut manifest = Manifest::new();
+    manifest.created_at = Some(source_date_epoch().unwrap_or_else(chrono::Utc::now));
+    manifest.base_code = options.base.as_ref().map(|b| b.code.clone());
+    let mut stored_count = 0;
+    let mut unchanged_count = 0;
+    let mut filtered_count = 0;
+    let mut local = BTreeMap::new();
     let mut name_count: HashMap<String, usize> = HashMap::new();
+    // 每个 skill 目录在归档中的名称前缀（SKILL.md 存为 前缀.md，其他文件存为 前缀/相对路径）
+    let mut prefixes: HashMap<PathBuf, String> = HashMap::new();
+    let mut used_prefixes: HashSet<String> = HashSet::new();
+    // skill 目录：文件归属于最近的一个包含 SKILL.md 的上级目录
+    let skill_dirs: HashSet<PathBuf> = skill_files
+        .iter()
+        .filter(|f| is_skill_file(f))
+        .filter_map(|f| f.parent().map(Path::to_path_buf))
+        .collect();
     let mut packaged_files = Vec::new();
+    // 内容去重：SHA256 -> (归档内文件名, 首次出现的显示路径)
+    let mut stored_by_hash: HashMap<String, (String, String)> = HashMap::new();
+    // 已打包的原始路径（规范化后），用于检测冲突
+    let mut seen_paths: HashSet<String> = HashSet::new();
+
+    // 增量或内容去重时清单为 v3，升级说明需在所有文件之前写入，旧版客户端才会在写入任何文件前报错
+    let may_need_v3 = options.base.is_some() || has_duplicate_content(&skill_files, options.jobs);
+    if may_need_v3 {
+        writer.add_file(UPGRADE_NOTICE, upgrade_notice().as_bytes())?;
+    }
 
-    for skill_file in skill_files {
+    // 并行读取文件内容（同时在内存中的最多 jobs 个），写入归档仍按原顺序串行进行
+    let contents = read_files_ordered(&skill_files, options.jobs);
+    for (skill_file, content) in skill_files.iter().zip(contents) {
+        let skill_file = skill_file.as_path();
         pb.set_message(format!("Adding / 添加: {}", skill_file.display()));
 
-        // 读取文件内容
-        let content = fs::read(skill_file).context("Failed to read file / 读取文件失败")?;
+        let content = content.context("Failed to read file / 读取文件失败")?;
 
-        // 获取技能目录名称作为文件名
-        let skill_name = if let Some(parent) = skill_file.parent() {
-            parent
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-        } else {
-            "unknown"
+        // 过滤规则只作用于 skill 目录中的其他文件，SKILL.md 本身总是打包
+        let is_entry = is_skill_file(skill_file);
+        if let Some(reason) = options.filter.skip_reason(skill_file, &content).filter(|_| !is_entry) {
+            pb.suspend(|| println!("🚫 Filtered / 已过滤: {} ({})", skill_file.display(), reason));
+            filtered_count += 1;
+            pb.inc(1);
+            continue;
+        }
+
+        // 脱敏规则只改写归档中的副本（包括写入清单的描述），本地原文另作为同步基准
+        let (content, original) = match options.redact.apply(&content) {
+            Some((redacted, count)) => {
+                pb.suspend(|| println!("✂️  Redacted {} match(es) / 脱敏了 {} 处: {}", count, count, skill_file.display()));
+                (redacted, Some(content))
+            }
+            None => (content, None),
         };
 
-        // 处理重复文件名
-        let count = name_count.entry(skill_name.to_string()).or_insert(0);
-        let new_filename = if *count == 0 {
-            format!("{}.md", skill_name)
+        let skill_dir = skill_file
+            .ancestors()
+            .skip(1)
+            .find(|dir| skill_dirs.contains(*dir))
+            .or_else(|| skill_file.parent())
+            .unwrap_or(Path::new(""));
+
+        // 获取技能目录名称作为文件名（非 UTF-8 名称以替换字符显示，重名由下方计数区分）
+        let skill_name = skill_dir
+            .file_name()
+            .map(|n| normalize_nfc(&n.to_string_lossy()))
+            .unwrap_or_else(|| "unknown".to_string());
+        let skill_name = skill_name.as_str();
+        // SKILL.md 的 front matter 写入清单，远端预览时不必下载解析每个 SKILL.md
+        let (description, metadata) = if is_entry {
+            let text = String::from_utf8_lossy(&content);
+            (Some(skill::description(&text)), skill::parse(&text).unwrap_or_default())
         } else {
-            format!("{}_{}.md", skill_name, count)
+            (None, SkillMetadata::default())
         };
-        *count += 1;
 
-        // 添加到 zip 根目录
-        zip.start_file(&new_filename, options)?;
-        zip.write_all(&content)?;
-
-        // 记录到 manifest，使用正斜杠以支持跨平台
-        if let Some(home) = dirs::home_dir() {
+        // 记录到 manifest，使用正斜杠以支持跨平台，非 UTF-8 字节无损编码，Unicode 统一为 NFC
+        let (original_path, display_path) = if let Some(home) = dirs::home_dir() {
             // 使用 Path::strip_prefix 获取相对路径
             let relative = skill_file.strip_prefix(&home).unwrap_or(skill_file);
-            // 转换为字符串，统一使用正斜杠
-            let relative_str = relative.to_string_lossy().replace('\\', "/");
-            manifest_lines.push(format!("{}={}", new_filename, relative_str));
-            packaged_files.push(format!("~/{}", relative_str));
+            let relative_str = normalize_nfc(&encode_path(relative));
+            let display = format!("~/{}", relative_str);
+            (relative_str, display)
         } else {
-            let path_str = skill_file.display().to_string().replace('\\', "/");
-            manifest_lines.push(format!("{}={}", new_filename, path_str));
-            packaged_files.push(path_str);
+            let path_str = normalize_nfc(&encode_path(skill_file));
+            (path_str.clone(), path_str)
+        };
+
+        // 仅 Unicode 规范化形式不同的两个路径在其他平台上是同一个文件，只保留第一个
+        if !seen_paths.insert(original_path.clone()) {
+            pb.suspend(|| {
+                summary::warn(&format!(
+                    "Skipping {}: same name as another skill after Unicode normalization / 跳过 {}：Unicode 规范化后与另一个 skill 同名",
+                    skill_file.display(),
+                    skill_file.display()
+                ))
+            });
+            pb.inc(1);
+            continue;
+        }
+        if let Some(original) = &original {
+            local.insert(original_path.clone(), format!("{:x}", Sha256::digest(original)));
+        }
+
+        let sha256 = format!("{:x}", Sha256::digest(&content));
+        let (modified, mode) = file_attributes(skill_file);
+
+        // 保存同步基准内容（与同步状态记录的一致：被脱敏的文件为本地原文），供下载时三方合并使用
+        if let Err(e) = store_blob(original.as_deref().unwrap_or(&content)) {
+            pb.suspend(|| {
+                summary::warn(&format!(
+                    "Failed to save merge base for {} / 保存合并基准失败 {}: {:#}",
+                    skill_file.display(),
+                    skill_file.display(),
+                    e
+                ))
+            });
         }
 
+        // 增量打包：与上次上传相同的文件只记录在 manifest 中
+        let unchanged = options
+            .base
+            .as_ref()
+            .and_then(|b| b.files.get(&original_path))
+            .is_some_and(|h| *h == sha256);
+        if unchanged {
+            unchanged_count += 1;
+            manifest.files.push(ManifestEntry {
+                archive_name: String::new(),
+                source: source_from_path(&original_path),
+                original_path,
+                skill_name: Some(skill_name.to_string()),
+                description,
+                name: metadata.name,
+                version: metadata.version,
+                tags: metadata.tags,
+                sha256: Some(sha256),
+                size: Some(content.len() as u64),
+                modified,
+                mode,
+                from_base: true,
+            });
+            pb.inc(1);
+            continue;
+        }
+
+        // 相同内容（如 .claude 与 .codex 中的同一个 skill）只存储一次，manifest 中多次引用
+        let archive_name = if let Some((existing, first_path)) = stored_by_hash.get(&sha256) {
+            packaged_files.push(format!("{} (same as / 同 {})", display_path, first_path));
+            existing.clone()
+        } else {
+            // 处理重复的 skill 名称：同名目录依次使用 name、name_1、name_2 ...
+            let prefix = prefixes.entry(skill_dir.to_path_buf()).or_insert_with(|| {
+                let count = name_count.entry(skill_name.to_string()).or_insert(0);
+                loop {
+                    let candidate = if *count == 0 {
+                        skill_name.to_string()
+                    } else {
+                        format!("{}_{}", skill_name, count)
+                    };
+                    *count += 1;
+                    if used_prefixes.insert(candidate.clone()) {
+                        break candidate;
+                    }
+                }
+            });
+            let new_filename = if is_entry {
+                format!("{}.md", prefix)
+            } else {
+                let relative = skill_file.strip_prefix(skill_dir).unwrap_or(skill_file);
+                format!("{}/{}", prefix, normalize_nfc(&encode_path(relative)))
+            };
+
+            // 添加到归档根目录
+            writer.add_file(&new_filename, &content)?;
+            stored_count += 1;
+            stored_by_hash.insert(sha256.clone(), (new_filename.clone(), display_path.clone()));
+            packaged_files.push(display_path);
+            new_filename
+        };
+
+        manifest.files.push(ManifestEntry {
+            archive_name,
+            source: source_from_path(&original_path),
+            original_path,
+            skill_name: Some(skill_name.to_string()),
+            description,
+            name: metadata.name,
+            version: metadata.version,
+            tags: metadata.tags,
+            sha256: Some(sha256),
+            size: Some(content.len() as u64),
+            modified,
+            mode,
+            from_base: false,
+        });
+
         pb.inc(1);
     }
 
-    // 写入 manifest.txt
-    zip.start_file("manifest.txt", options)?;
-    for line in &manifest_lines {
-        writeln!(zip, "{}", line)?;
-    }
+    // 写入 manifest.json，并保留 v1 的 manifest.txt 供旧版客户端使用
+    manifest.version = manifest.required_version();
+    writer.add_file(MANIFEST_JSON, manifest.to_json()?.as_bytes())?;
+    writer.add_file(MANIFEST_TXT, manifest.to_v1().as_bytes())?;
 
-    zip.finish()?;
+    let sha256 = writer.finish()?;
     pb.finish_with_message("Packaging complete / 打包完成!");
+    summary::record(|s| {
+        s.packaged += stored_count;
+        s.files.extend(manifest.files.iter().map(|e| FileRecord {
+            path: e.original_path.clone(),
+            sha256: e.sha256.clone(),
+        }));
+    });
 
     // 显示打包的文件列表
     if !packaged_files.is_empty() {
@@ -257,15 +971,242 @@ pub fn create_skills_zip(skill_files: &[PathBuf], zip_path: &Path) -> Result<Str
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     }
 
-    // 计算 SHA256
-    let zip_bytes = fs::read(zip_path)?;
-    let hash = Sha256::digest(&zip_bytes);
-    Ok(format!("{:x}", hash))
+    if filtered_count > 0 {
+        println!(
+            "🚫 {} files left out by filter rules / {} 个文件被过滤规则排除",
+            filtered_count, filtered_count
+        );
+    }
+
+    if unchanged_count > 0 {
+        println!(
+            "♻️  {} unchanged files reference the previous upload / {} 个未变化的文件引用上次上传",
+            unchanged_count, unchanged_count
+        );
+    }
+
+    Ok(PackedArchive {
+        sha256,
+        manifest,
+        stored_count,
+        local,
+    })
+}
+
+/// 读取文件的修改时间（Unix 秒）和权限位（仅 Unix）
+///
+/// 设置了 SOURCE_DATE_EPOCH 时修改时间不晚于该时间，保证可复现
+fn file_attributes(path: &Path) -> (Option<i64>, Option<u32>) {
+    let Ok(metadata) = fs::metadata(long_path(path)) else {
+        return (None, None);
+    };
+
+    let modified = metadata
+        .modified()
+        .ok()
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp())
+        .map(|t| match source_date_epoch() {
+            Some(epoch) => t.min(epoch.timestamp()),
+            None => t,
+        });
+
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        Some(metadata.permissions().mode() & 0o7777)
+    };
+    #[cfg(not(unix))]
+    let mode = None;
+
+    (modified, mode)
+}
+
+/// 恢复文件的修改时间和权限位，失败时只给出警告
+fn restore_attributes(path: &Path, entry: &ManifestEntry) {
+    #[cfg(unix)]
+    if let Some(mode) = entry.mode {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(mode)) {
+            summary::warn(&format!("Failed to restore permissions / 恢复权限失败: {}: {}", path.display(), e));
+        }
+    }
+
+    let modified = entry
+        .modified
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(std::time::SystemTime::from);
+    if let Some(modified) = modified {
+        let result = fs::File::options()
+            .write(true)
+            .open(path)
+            .and_then(|f| f.set_modified(modified));
+        if let Err(e) = result {
+            summary::warn(&format!("Failed to restore modification time / 恢复修改时间失败: {}: {}", path.display(), e));
+        }
+    }
+}
+
+/// 可复现构建的时间戳（SOURCE_DATE_EPOCH），设置后 manifest 的创建时间固定为该值
+fn source_date_epoch() -> Option<chrono::DateTime<chrono::Utc>> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+}
+
+/// 查询服务器公布的单次上传大小上限（字节），服务器不支持该接口或请求失败时返回 None；
+/// 服务器证书与记录不一致时报错
+pub async fn fetch_max_upload_size(server_url: &str) -> Result<Option<u64>> {
+    if grpc::is_grpc_url(server_url) {
+        let limits = tokio::time::timeout(std::time::Duration::from_secs(5), grpc::limits(server_url));
+        return Ok(limits.await.ok().and_then(Result::ok).flatten());
+    }
+    let client = http::client_builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()
+        .context("Failed to create HTTP client / 创建 HTTP 客户端失败")?;
+    let url = format!("{}/sync/limits", server_url);
+
+    let Some(response) = http::try_send(client.get(&url)).await? else {
+        return Ok(None);
+    };
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+
+    let Ok(result) = response.json::<serde_json::Value>().await else {
+        return Ok(None);
+    };
+    Ok(api::payload(server_url, &result)["max_upload_size"].as_u64())
+}
+
+/// 上传前检查大小是否超过服务器限制，超出时直接报错而不是上传后收到 413
+fn check_upload_size(archive_size: u64, max_part_size: Option<u64>, server_limit: Option<u64>) -> Result<()> {
+    let Some(limit) = server_limit else {
+        return Ok(());
+    };
+
+    // 分卷上传时单次请求大小为分卷大小
+    let request_size = match max_part_size {
+        Some(max) => max.min(archive_size),
+        None => archive_size,
+    };
+
+    if request_size > limit {
+        let hint = if max_part_size.is_some() {
+            format!("use a smaller --max-part-size (<= {}) / 请使用更小的 --max-part-size", limit)
+        } else {
+            format!("use --max-part-size {} to split the archive / 可使用 --max-part-size 拆分归档", limit)
+        };
+        return Err(anyhow::anyhow!(
+            "Upload too large for server / 超出服务器上传限制: {} > {} ({} > {} bytes), {}",
+            HumanBytes(request_size),
+            HumanBytes(limit),
+            request_size,
+            limit,
+            hint
+        ));
+    }
+
+    Ok(())
+}
+
+/// 服务器对上传请求的响应
+pub struct UploadReply {
+    /// 业务码
+    pub code: String,
+    /// 业务码所有者令牌（服务器支持时返回），之后凭它在同一业务码下重新上传
+    pub owner_token: Option<String>,
+    /// 服务器确认的别名
+    pub alias: Option<String>,
+    /// 服务器校验并回传的归档 SHA256
+    pub sha256: Option<String>,
+}
+
+/// 上传目标：默认生成新的业务码
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UploadTarget<'a> {
+    /// 作为该业务码的新版本上传
+    pub code: Option<&'a str>,
+    /// 业务码所有者令牌
+    pub owner_token: Option<&'a str>,
+    /// 请求映射到业务码的自定义别名
+    pub alias: Option<&'a str>,
+}
+
+/// 上传归档文件到远端服务器，sha256 为打包时计算的归档 SHA256
+pub async fn upload_zip(
+    zip_path: &Path,
+    sha256: &str,
+    server_url: &str,
+    format: ArchiveFormat,
+    target: UploadTarget<'_>,
+) -> Result<UploadReply> {
+    let file_name = format!("skills.{}", format.extension());
+    upload_file(zip_path, sha256, server_url, &file_name, format.mime_type(), target).await
+}
+
+/// 上传任意文件，返回业务码；随上传发送调用方已计算的 SHA256（不再重新读取文件计算），并核对服务器回传的值
+async fn upload_file(
+    zip_path: &Path,
+    sha256: &str,
+    server_url: &str,
+    file_name: &str,
+    mime: &str,
+    target: UploadTarget<'_>,
+) -> Result<UploadReply> {
+    let started = std::time::Instant::now();
+    let reply = if grpc::is_grpc_url(server_url) {
+        println!("📤 Uploading to / 上传到: {}", server_url);
+        grpc::upload(server_url, zip_path, file_name, sha256, target).await?
+    } else if ws::enabled() && api::supports(server_url, "websocket") {
+        println!("📤 Uploading over WebSocket to / 通过 WebSocket 上传到: {}", server_url);
+        ws::upload(server_url, zip_path, file_name, sha256, target).await?
+    } else {
+        upload_http(zip_path, server_url, file_name, mime, sha256, target).await?
+    };
+    check_upload_sha256(sha256, reply.sha256.as_deref(), api::legacy(server_url))?;
+    let size = fs::metadata(zip_path)?.len();
+    progress::print_summary(true, size, started.elapsed());
+    summary::record(|s| {
+        s.uploaded += size;
+        s.upload_ms += started.elapsed().as_millis() as u64;
+    });
+    Ok(reply)
+}
+
+/// 核对服务器回传的 SHA256：不一致时报错；没有回传时，协商过 API 版本的服务器视为未校验而报错，
+/// 旧版服务器（以及不协商的 gRPC 服务器）只给出警告
+fn check_upload_sha256(local: &str, echoed: Option<&str>, legacy: bool) -> Result<()> {
+    match echoed {
+        Some(echoed) if echoed.eq_ignore_ascii_case(local) => Ok(()),
+        Some(echoed) => Err(anyhow::anyhow!(
+            "Upload corrupted in transit: server received {}, local archive is {} / 上传内容在传输中损坏：服务器收到 {}，本地归档为 {}",
+            echoed,
+            local,
+            echoed,
+            local
+        )),
+        None if legacy => {
+            summary::warn("Server did not confirm the archive checksum / 服务器未确认归档校验和");
+            Ok(())
+        }
+        None => Err(anyhow::anyhow!(
+            "Server did not verify the archive checksum (sha256 missing from the response) / 服务器未校验归档校验和（响应中没有 sha256）"
+        )),
+    }
 }
 
-/// 上传 zip 文件到远端服务器
-pub async fn upload_zip(zip_path: &Path, server_url: &str) -> Result<String> {
-    let client = Client::new();
+/// 以 multipart 形式通过 HTTP 上传文件，sha256 通过 X-Content-SHA256 头和 sha256 字段发送
+async fn upload_http(
+    zip_path: &Path,
+    server_url: &str,
+    file_name: &str,
+    mime: &str,
+    sha256: &str,
+    target: UploadTarget<'_>,
+) -> Result<UploadReply> {
+    let client = http::client()?;
     let url = format!("{}/sync/upload", server_url);
 
     println!("📤 Uploading to / 上传到: {}", url);
@@ -273,135 +1214,726 @@ pub async fn upload_zip(zip_path: &Path, server_url: &str) -> Result<String> {
     // 获取文件大小用于进度条
     let file_size = fs::metadata(zip_path)?.len();
 
-    let file_content = fs::read(zip_path)?;
-
-    // 创建 multipart form
-    let part = reqwest::multipart::Part::bytes(file_content.clone())
-        .file_name("skills.zip")
-        .mime_str("application/zip")?;
-
-    let form = reqwest::multipart::Form::new().part("file", part);
+    // 创建 multipart form（被限流重试时需要重新构造）；文件边读边发，不整体读入内存，发出的字节推进 progress
+    let build_form = |progress: &ProgressBar| -> Result<reqwest::multipart::Form> {
+        let file = tokio::fs::File::from_std(fs::File::open(zip_path)?);
+        let body = reqwest::Body::wrap_stream(progress::ProgressStream::new(file, progress.clone()));
+        let part = reqwest::multipart::Part::stream_with_length(body, file_size)
+            .file_name(file_name.to_string())
+            .mime_str(mime)?;
+        let mut form = reqwest::multipart::Form::new().part("file", part).text("sha256", sha256.to_string());
+        if let Some(code) = target.code {
+            form = form.text("code", code.to_string());
+        }
+        if let Some(alias) = target.alias {
+            form = form.text("alias", alias.to_string());
+        }
+        Ok(form)
+    };
+    // 先构造一次以校验 MIME 类型和文件可读；重试时文件无法重新打开则上传失败
+    build_form(&ProgressBar::hidden())?;
+
+    // 服务器公布支持时整体压缩请求体（写入临时文件，同样边读边发），压缩后没有变小时按原样发送
+    let compressed_body = TempArchive::new("upload_body", "bin");
+    let compressed = match encoding::negotiate(server_url) {
+        Some(encoding) => {
+            let fields: Vec<(&str, &str)> = [("sha256", Some(sha256)), ("code", target.code), ("alias", target.alias)]
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value?)))
+                .collect();
+            let multipart = encoding::Multipart::new(&fields, file_name, mime);
+            let length = multipart.compress(
+                encoding,
+                &mut fs::File::open(zip_path)?,
+                std::io::BufWriter::new(fs::File::create(compressed_body.path())?),
+            )?;
+            let compressed_size = fs::metadata(compressed_body.path())?.len();
+            (compressed_size < length).then(|| {
+                println!(
+                    "🗜️  Request body compressed with {}: {} -> {} / 请求体已用 {} 压缩",
+                    encoding.header_value(),
+                    HumanBytes(length),
+                    HumanBytes(compressed_size),
+                    encoding.header_value()
+                );
+                (encoding, multipart.content_type, compressed_size)
+            })
+        }
+        None => None,
+    };
 
-    let pb = ProgressBar::new(file_size);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] [{bar:40.green/white}] {bytes}/{total_bytes} ({eta})")?
-            .progress_chars("=>-"),
-    );
+    let pb = progress::bar(compressed.as_ref().map_or(file_size, |(_, _, size)| *size));
+    pb.set_style(progress::bar_style()?);
 
     println!("⬆️  Starting upload / 开始上传...");
 
-    let response = client
-        .post(&url)
-        .multipart(form)
-        .send()
-        .await
-        .context("Upload failed / 上传失败")?;
+    let response = http::send_with_retry(|| {
+        // 被限流重试时从头重新发送
+        pb.set_position(0);
+        let mut request = match &compressed {
+            Some((encoding, content_type, size)) => {
+                let request = client
+                    .post(&url)
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .header(reqwest::header::CONTENT_ENCODING, encoding.header_value())
+                    .header(reqwest::header::CONTENT_LENGTH, *size);
+                let file = fs::File::open(compressed_body.path())
+                    .context("Failed to reopen compressed request body / 重新打开压缩后的请求体失败")?;
+                request.body(reqwest::Body::wrap_stream(progress::ProgressStream::new(
+                    tokio::fs::File::from_std(file),
+                    pb.clone(),
+                )))
+            }
+            None => client.post(&url).multipart(build_form(&pb)?),
+        };
+        if let Some(token) = target.owner_token {
+            request = request.header("X-Owner-Token", token);
+        }
+        Ok(request.header(SHA256_HEADER, sha256))
+    })
+    .await
+    .context("Upload failed / 上传失败")?;
 
     pb.finish_with_message("Upload complete / 上传完成!");
 
     let status = response.status();
+    if let (Some(code), StatusCode::FORBIDDEN) = (target.code, status) {
+        return Err(anyhow::anyhow!(
+            "Not allowed to update code {} (only codes uploaded from this machine can be updated) / 无权更新业务码 {}（只能更新本机上传的业务码）",
+            code,
+            code
+        ));
+    }
+    if let (Some(alias), StatusCode::CONFLICT) = (target.alias, status) {
+        return Err(anyhow::anyhow!("Alias already taken / 别名已被占用: {}", alias));
+    }
     if !status.is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(anyhow::anyhow!("Upload failed / 上传失败: {} - {}", status, error_text));
+        return Err(server_error::from_response(response, "Upload failed / 上传失败", target.code).await);
     }
 
     let result: serde_json::Value = response.json().await.context("Failed to parse response / 解析响应失败")?;
 
     // 提取业务码
-    let code = result["body"]["code"]
+    let body = api::payload(server_url, &result);
+    let code = body["code"]
         .as_str()
         .context("Business code not found in response / 响应中未找到业务码")?;
-
-    Ok(code.to_string())
+    let owner_token = body["owner_token"].as_str().map(str::to_string);
+    let alias = body["alias"].as_str().map(str::to_string);
+    let sha256 = body["sha256"].as_str().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+
+    Ok(UploadReply {
+        code: code.to_string(),
+        owner_token,
+        alias,
+        sha256,
+    })
 }
 
-/// 通过业务码下载 zip 文件
-pub async fn download_zip(code: &str, server_url: &str, download_path: &Path) -> Result<String> {
-    let client = Client::new();
-    let url = format!("{}/sync/download/{}", server_url, code);
+/// 并发上传分卷（最多 jobs 个同时进行），按分卷顺序返回分卷信息
+async fn upload_parts(parts: &[PathBuf], server_url: &str, jobs: usize) -> Result<Vec<PartInfo>> {
+    let semaphore = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks = JoinSet::new();
+    let total = parts.len();
+
+    for (i, part) in parts.iter().enumerate() {
+        let part = part.clone();
+        let server_url = server_url.to_string();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let sha256 = file_sha256(&part)?;
+            let size = fs::metadata(&part)?.len();
+            let file_name = format!("skills.part{}", i + 1);
+            let code = upload_file(&part, &sha256, &server_url, &file_name, "application/octet-stream", UploadTarget::default())
+                .await?
+                .code;
+            println!("✅ Part {}/{} uploaded / 分卷已上传: {}", i + 1, total, code);
+            Ok::<_, anyhow::Error>((i, PartInfo { code, sha256, size }))
+        });
+    }
 
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {msg}")?,
-    );
-    pb.set_message("Downloading / 正在下载...");
+    let mut results: Vec<Option<PartInfo>> = vec![None; parts.len()];
+    while let Some(joined) = tasks.join_next().await {
+        let (i, info) = joined??;
+        results[i] = Some(info);
+    }
 
-    let response = client.get(&url).send().await.context("Download request failed / 下载请求失败")?;
+    Ok(results.into_iter().flatten().collect())
+}
 
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response.text().await.unwrap_or_default();
-        return Err(anyhow::anyhow!("Download failed / 下载失败: {} - {}", status, error_text));
+/// 并发下载分卷（最多 jobs 个同时进行）并校验每个分卷的 SHA256，按顺序返回分卷文件路径
+async fn download_parts(index: &PartsIndex, server_url: &str, base_path: &Path, jobs: usize) -> Result<Vec<PathBuf>> {
+    let semaphore = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks = JoinSet::new();
+    let total = index.parts.len();
+
+    for (i, part) in index.parts.iter().enumerate() {
+        let part = part.clone();
+        let server_url = server_url.to_string();
+        let semaphore = semaphore.clone();
+        let part_path = PathBuf::from(format!("{}.part{}", base_path.display(), i + 1));
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await?;
+            let sha256 = download_zip(&part.code, &server_url, &part_path).await?;
+            if sha256 != part.sha256 {
+                return Err(anyhow::anyhow!(
+                    "Integrity check failed for part {}/{} / 分卷 {}/{} 校验失败: expected {}, got {}",
+                    i + 1,
+                    total,
+                    i + 1,
+                    total,
+                    part.sha256,
+                    sha256
+                ));
+            }
+            Ok((i, part_path))
+        });
     }
 
-    let bytes = response.bytes().await.context("Failed to read response / 读取响应内容失败")?;
+    let mut results: Vec<Option<PathBuf>> = vec![None; total];
+    while let Some(joined) = tasks.join_next().await {
+        let (i, path) = joined??;
+        results[i] = Some(path);
+    }
 
-    // 计算 SHA256
-    let hash = Sha256::digest(&bytes);
-    let sha256 = format!("{:x}", hash);
+    Ok(results.into_iter().flatten().collect())
+}
 
-    fs::write(download_path, &bytes).context("Failed to write file / 写入文件失败")?;
+/// 服务器返回归档 SHA256 所用的响应头
+const SHA256_HEADER: &str = "x-content-sha256";
 
-    pb.finish_with_message("Download complete / 下载完成!");
+/// 下载时每写入这么多字节执行一次 fsync，崩溃时最多丢失这部分内容
+const FSYNC_INTERVAL: u64 = 8 * 1024 * 1024;
 
-    Ok(sha256)
+/// 下载过程中写入的文件：<目标文件>.part，校验通过后才由 commit_download 重命名为目标文件，
+/// 中途崩溃或中断时不会留下会被当作完整归档解压的截断文件
+pub(crate) fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".part");
+    PathBuf::from(name)
 }
 
-/// 解压 zip 文件到目标目录，根据 manifest.txt 恢复原始位置
-pub fn extract_zip(zip_path: &Path, _target_dir: &Path) -> Result<()> {
-    let file = fs::File::open(zip_path).context("Failed to open zip file / 打开 zip 文件失败")?;
-    let mut archive = zip::ZipArchive::new(file)?;
+/// 校验通过后将 <目标文件>.part 重命名为目标文件
+pub(crate) fn commit_download(path: &Path) -> Result<()> {
+    fs::rename(part_path(path), path).context("Failed to write file / 写入文件失败")
+}
 
-    // 先读取 manifest.txt
-    let mut manifest_content = String::new();
-    let mut file_map: HashMap<String, String> = HashMap::new();
+/// 将下载内容写入 <目标文件>.part，同时计算 SHA256 并限制总大小（HTTP、gRPC 与 WebSocket 传输共用）
+///
+/// 同时显示下载进度：知道总大小（set_length）前为显示已下载字节数的 spinner，之后为进度条
+pub(crate) struct DownloadWriter {
+    file: std::io::BufWriter<fs::File>,
+    hasher: Sha256,
+    written: u64,
+    /// 上次 fsync 之后写入的字节数
+    unsynced: u64,
+    progress: ProgressBar,
+}
 
-    if let Ok(mut manifest_file) = archive.by_name("manifest.txt") {
-        manifest_file.read_to_string(&mut manifest_content)?;
-        // 解析 manifest.txt: 文件名=原始路径
-        for line in manifest_content.lines() {
-            if let Some((filename, original_path)) = line.split_once('=') {
-                file_map.insert(filename.to_string(), original_path.to_string());
-            }
-        }
+impl DownloadWriter {
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = fs::File::create(part_path(path)).context("Failed to write file / 写入文件失败")?;
+        let progress = progress::spinner();
+        progress.set_style(progress::spinner_style()?);
+        progress.set_message("Downloading / 正在下载...");
+        progress.enable_steady_tick(std::time::Duration::from_millis(100));
+        Ok(DownloadWriter {
+            file: std::io::BufWriter::new(file),
+            hasher: Sha256::new(),
+            written: 0,
+            unsynced: 0,
+            progress,
+        })
     }
 
-    // 获取用户目录
-    let home_dir = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
-
-    // 重新打开 archive（因为已经读取了 manifest.txt）
-    let file = fs::File::open(zip_path).context("Failed to open zip file / 打开 zip 文件失败")?;
-    let mut archive = zip::ZipArchive::new(file)?;
+    /// 已知下载总大小（如 Content-Length）时，将 spinner 换为进度条
+    pub(crate) fn set_length(&self, length: u64) -> Result<()> {
+        self.progress.disable_steady_tick();
+        self.progress.set_style(progress::bar_style()?);
+        self.progress.set_length(length);
+        Ok(())
+    }
 
-    let pb = ProgressBar::new(archive.len() as u64);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("[{elapsed_precise}] [{bar:40.yellow/white}] {pos}/{len} {msg}")?
-            .progress_chars("##-"),
-    );
+    /// 进度条，分段并行下载时各段直接更新
+    fn progress(&self) -> &ProgressBar {
+        &self.progress
+    }
 
-    // 记录解压的文件
-    let mut extracted_files = Vec::new();
+    /// 追加一段内容，累计大小超过 MAX_DOWNLOAD_SIZE 时报错
+    pub(crate) fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.written += chunk.len() as u64;
+        http::check_download_size(self.written)?;
+        self.hasher.update(chunk);
+        self.file.write_all(chunk).context("Failed to write file / 写入文件失败")?;
+        self.unsynced += chunk.len() as u64;
+        // 分段下载时进度已由各段更新，合并写入时不回退
+        if self.written > self.progress.position() {
+            self.progress.set_position(self.written);
+        }
+        if self.unsynced >= FSYNC_INTERVAL {
+            self.file.flush().context("Failed to write file / 写入文件失败")?;
+            self.file.get_ref().sync_data().context("Failed to write file / 写入文件失败")?;
+            self.unsynced = 0;
+        }
+        Ok(())
+    }
 
-    for i in 0..archive.len() {
-        let mut zip_file = archive.by_index(i)?;
-        let filename = zip_file.name();
+    /// 已写入的字节数（WebSocket 续传时作为起始位置）
+    #[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+    pub(crate) fn written(&self) -> u64 {
+        self.written
+    }
 
-        // 跳过 manifest.txt
-        if filename == "manifest.txt" {
-            pb.inc(1);
+    /// 写完、落盘并返回内容的 SHA256；文件仍为 .part，由调用方校验后 commit_download
+    pub(crate) fn finish(self) -> Result<String> {
+        let file = self
+            .file
+            .into_inner()
+            .map_err(|e| e.into_error())
+            .context("Failed to write file / 写入文件失败")?;
+        file.sync_all().context("Failed to write file / 写入文件失败")?;
+        self.progress.finish_with_message("Download complete / 下载完成!");
+        progress::print_summary(false, self.written, self.progress.elapsed());
+        summary::record(|s| {
+            s.downloaded += self.written;
+            s.download_ms += self.progress.elapsed().as_millis() as u64;
+        });
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+/// 分段并行下载时每段的最小大小，小归档不值得多开连接
+const MIN_SEGMENT_SIZE: u64 = 1024 * 1024;
+
+/// 从 Content-Range 头（bytes 0-0/12345）中解析总大小
+fn content_range_total(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit_once('/')?
+        .1
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// 按 connections 把 total 字节切分为若干段（每段不小于 MIN_SEGMENT_SIZE），返回每段的 [start, end] 闭区间
+fn segment_ranges(total: u64, connections: usize) -> Vec<(u64, u64)> {
+    let count = total.div_ceil(MIN_SEGMENT_SIZE).clamp(1, connections.max(1) as u64);
+    let size = total.div_ceil(count).max(1);
+    (0..count)
+        .map(|i| i * size)
+        .take_while(|&start| start < total)
+        .map(|start| (start, (start + size).min(total) - 1))
+        .collect()
+}
+
+/// 通过 Range 请求下载 [start, end] 区间到 path
+async fn download_range(client: &Client, url: &str, start: u64, end: u64, path: &Path, progress: &ProgressBar) -> Result<()> {
+    let mut response = http::send_with_retry(|| Ok(client.get(url).header(reqwest::header::RANGE, format!("bytes={}-{}", start, end))))
+        .await
+        .context("Download request failed / 下载请求失败")?;
+    let status = response.status();
+    let expected_range = format!("bytes {}-{}/", start, end);
+    let range_ok = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with(&expected_range));
+    if status != StatusCode::PARTIAL_CONTENT || !range_ok {
+        return Err(anyhow::anyhow!(
+            "Server did not honor range {}-{} / 服务器未按范围 {}-{} 返回: {}",
+            start,
+            end,
+            start,
+            end,
+            status
+        ));
+    }
+
+    let mut file = std::io::BufWriter::new(fs::File::create(path).context("Failed to write file / 写入文件失败")?);
+    let mut written = 0u64;
+    while let Some(chunk) = response.chunk().await.context("Failed to read response / 读取响应内容失败")? {
+        written += chunk.len() as u64;
+        if written > end - start + 1 {
+            return Err(anyhow::anyhow!("Server sent more data than requested / 服务器返回的数据超出请求范围"));
+        }
+        file.write_all(&chunk).context("Failed to write file / 写入文件失败")?;
+        progress.inc(chunk.len() as u64);
+    }
+    if written != end - start + 1 {
+        return Err(anyhow::anyhow!(
+            "Segment {}-{} incomplete / 分段 {}-{} 不完整",
+            start,
+            end,
+            start,
+            end
+        ));
+    }
+    file.flush().context("Failed to write file / 写入文件失败")
+}
+
+/// 并发下载 total 字节的各段到 <目标文件>.part.<序号>，全部完成后按顺序写入 writer（计算 SHA256 并落盘）
+async fn download_segments(
+    client: &Client,
+    url: &str,
+    total: u64,
+    download_path: &Path,
+    writer: &mut DownloadWriter,
+) -> Result<()> {
+    let ranges = segment_ranges(total, http::connections());
+    writer.progress().suspend(|| {
+        println!(
+            "🔀 Downloading {} in {} segments / 分 {} 段并行下载 {}",
+            HumanBytes(total),
+            ranges.len(),
+            ranges.len(),
+            HumanBytes(total)
+        )
+    });
+    let segments: Vec<PathBuf> = (0..ranges.len())
+        .map(|i| PathBuf::from(format!("{}.{}", part_path(download_path).display(), i)))
+        .collect();
+
+    let result = async {
+        let mut tasks = JoinSet::new();
+        for (&(start, end), path) in ranges.iter().zip(&segments) {
+            let (client, url, path, progress) = (client.clone(), url.to_string(), path.clone(), writer.progress().clone());
+            tasks.spawn(async move { download_range(&client, &url, start, end, &path, &progress).await });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            joined??;
+        }
+        let mut buffer = vec![0u8; 64 * 1024];
+        for path in &segments {
+            let mut file = fs::File::open(path).context("Failed to read file / 读取文件失败")?;
+            loop {
+                let n = std::io::Read::read(&mut file, &mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                writer.write(&buffer[..n])?;
+            }
+        }
+        Ok(())
+    }
+    .await;
+    for path in &segments {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+/// 通过 HTTP 下载归档到 download_path，返回内容的 SHA256 和响应头中的 SHA256
+///
+/// 并发连接数大于 1 时先请求第一个字节：服务器支持 Range（返回 206）时分段并行下载，
+/// 否则直接使用返回的完整响应
+async fn download_http(server_url: &str, code: &str, download_path: &Path) -> Result<(String, Option<String>)> {
+    let client = http::client()?;
+    let url = format!("{}/sync/download/{}", server_url, code);
+    let ranged = http::connections() > 1;
+
+    let mut response = http::send_with_retry(|| {
+        let request = client.get(&url);
+        Ok(if ranged {
+            request.header(reqwest::header::RANGE, "bytes=0-0")
+        } else {
+            request
+        })
+    })
+    .await
+    .context("Download request failed / 下载请求失败")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(server_error::from_response(response, "Download failed / 下载失败", Some(code)).await);
+    }
+
+    let header_sha256 = response
+        .headers()
+        .get(SHA256_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.trim().to_lowercase());
+
+    if status == StatusCode::PARTIAL_CONTENT {
+        let total = content_range_total(&response)
+            .context("Invalid Content-Range from server / 服务器返回的 Content-Range 无效")?;
+        http::check_download_size(total)?;
+        drop(response);
+        let mut writer = DownloadWriter::create(download_path)?;
+        writer.set_length(total)?;
+        download_segments(&client, &url, total, download_path, &mut writer).await?;
+        return Ok((writer.finish()?, header_sha256));
+    }
+
+    // 逐块写入磁盘并计算 SHA256，不把整个归档读入内存
+    let mut writer = DownloadWriter::create(download_path)?;
+    if let Some(length) = response.content_length() {
+        http::check_download_size(length)?;
+        writer.set_length(length)?;
+    }
+    while let Some(chunk) = response.chunk().await.context("Failed to read response / 读取响应内容失败")? {
+        writer.write(&chunk)?;
+    }
+    Ok((writer.finish()?, header_sha256))
+}
+
+/// 通过业务码下载 zip 文件，并与服务器提供的 SHA256 进行校验
+pub async fn download_zip(code: &str, server_url: &str, download_path: &Path) -> Result<String> {
+    let result = if grpc::is_grpc_url(server_url) {
+        grpc::download(server_url, code, download_path).await
+    } else if ws::enabled() && api::supports(server_url, "websocket") {
+        ws::download(server_url, code, download_path).await
+    } else {
+        download_http(server_url, code, download_path).await
+    };
+    let (sha256, header_sha256) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = fs::remove_file(part_path(download_path));
+            return Err(e);
+        }
+    };
+
+    // 与服务器提供的校验值比对，不一致时拒绝写入，避免解压损坏的归档
+    let expected = match header_sha256 {
+        Some(h) => Some(h),
+        None => fetch_code_info(server_url, code).await?.sha256,
+    };
+
+    match expected {
+        Some(expected) if expected != sha256 => {
+            // 删除已写入的内容，避免解压损坏的归档
+            let _ = fs::remove_file(part_path(download_path));
+            return Err(anyhow::anyhow!(
+                "Integrity check failed: archive SHA256 mismatch / 完整性校验失败: 归档 SHA256 不匹配\n  expected / 期望: {}\n  actual   / 实际: {}",
+                expected,
+                sha256
+            ));
+        }
+        Some(_) => println!("✅ Checksum verified / 校验通过"),
+        None => summary::warn("Server did not provide a checksum, skipping verification / 服务器未提供校验值，跳过校验"),
+    }
+
+    commit_download(download_path)?;
+    Ok(sha256)
+}
+
+/// 读取归档（含增量基础链）中每个原始路径对应的远端内容，并按 manifest 记录的 SHA256 校验
+/// base_archives 为基础链（从新到旧），only_paths 不为 None 时只读取其中列出的原始路径
+///
+/// 未变化（from_base）的文件沿基础链从新到旧查找，取最近一个实际存储了该路径的基础归档，
+/// 每个路径只得到一份最终内容
+pub(crate) fn read_remote_files(
+    zip_path: &Path,
+    manifest: &Manifest,
+    base_archives: &[PathBuf],
+    only_paths: Option<&HashSet<String>>,
+) -> Result<BTreeMap<String, Vec<u8>>> {
+    let wanted = |path: &str| only_paths.is_none_or(|paths| paths.contains(path));
+    let mut files = BTreeMap::new();
+
+    archive::for_each_entry(zip_path, |name, reader| {
+        let paths: Vec<_> = manifest
+            .entries_for(name)
+            .filter(|e| !e.from_base && wanted(&e.original_path))
+            .map(|e| e.original_path.clone())
+            .collect();
+        if !paths.is_empty() {
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content)?;
+            for path in paths {
+                files.insert(path, content.clone());
+            }
+        }
+        Ok(())
+    })?;
+
+    let mut pending: HashSet<&str> = manifest
+        .files
+        .iter()
+        .filter(|e| e.from_base && wanted(&e.original_path))
+        .map(|e| e.original_path.as_str())
+        .collect();
+    for base_path in base_archives {
+        if pending.is_empty() {
+            break;
+        }
+        let base_manifest = Manifest::read_from_archive(base_path)?.unwrap_or_default();
+        archive::for_each_entry(base_path, |name, reader| {
+            let paths: Vec<_> = base_manifest
+                .entries_for(name)
+                .filter(|e| !e.from_base && pending.contains(e.original_path.as_str()))
+                .map(|e| e.original_path.clone())
+                .collect();
+            if !paths.is_empty() {
+                let mut content = Vec::new();
+                reader.read_to_end(&mut content)?;
+                for path in paths {
+                    files.insert(path, content.clone());
+                }
+            }
+            Ok(())
+        })?;
+        pending.retain(|p| !files.contains_key(*p));
+    }
+    if let Some(missing) = pending.iter().next() {
+        return Err(anyhow::anyhow!(
+            "{} is missing from the delta base chain / 增量基础链中缺少该文件: {}",
+            missing,
+            missing
+        ));
+    }
+
+    // v2 及以上的 manifest 带有每个文件的 SHA256，写入前校验（基础归档中的内容也按本次 manifest 校验）
+    for entry in &manifest.files {
+        let (Some(expected), Some(content)) = (&entry.sha256, files.get(&entry.original_path)) else {
+            continue;
+        };
+        let actual = format!("{:x}", Sha256::digest(content));
+        if &actual != expected {
+            let hint = if entry.from_base {
+                " (base archive has changed / 基础归档已变化)"
+            } else {
+                ""
+            };
+            return Err(anyhow::anyhow!(
+                "Integrity check failed for {}{} / 文件校验失败: expected {}, got {}",
+                entry.original_path,
+                hint,
+                expected,
+                actual
+            ));
+        }
+    }
+
+    Ok(files)
+}
+
+/// 解压归档到目标目录（自动识别 zip / tar.zst），根据 manifest 恢复原始位置（兼容 v1 manifest.txt）
+/// base_archives 为增量归档的基础链（从新到旧），only_paths 不为 None 时只解压其中列出的原始路径，
+/// on_conflict 决定本地与远端都修改过的 skill 是自动合并还是逐个询问，code 记入 skill 变更记录
+pub fn extract_zip(
+    zip_path: &Path,
+    _target_dir: &Path,
+    base_archives: &[PathBuf],
+    only_paths: Option<&HashSet<String>>,
+    on_conflict: OnConflict,
+    code: Option<&str>,
+) -> Result<ExtractReport> {
+    let manifest = Manifest::read_from_archive(zip_path)?.unwrap_or_default();
+    let files = read_remote_files(zip_path, &manifest, base_archives, only_paths)?;
+    extract_files(&manifest, &files, on_conflict, code)
+}
+
+/// 将每个原始路径的远端内容写入本地，每个文件只合并一次
+///
+/// 本地文件在上次同步后被修改过时，以上次同步的内容为基准与远端内容做三方合并，而不是直接覆盖
+fn extract_files(
+    manifest: &Manifest,
+    files: &BTreeMap<String, Vec<u8>>,
+    on_conflict: OnConflict,
+    code: Option<&str>,
+) -> Result<ExtractReport> {
+    let mut state = SyncState::load()?;
+    let before = state.synced.clone();
+    let mut report = ExtractReport::default();
+    // 写入或合并了远端内容的原始路径（跳过的 skill 不算作已下载）
+    let mut downloaded = Vec::new();
+
+    // 获取用户目录
+    let home_dir = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
+
+    // 解压前逐个询问有差异的 skill 如何处理（merge 模式不询问）
+    let (resolutions, contested) = match on_conflict {
+        OnConflict::Merge => (HashMap::new(), HashSet::new()),
+        OnConflict::Prompt => resolve_contested(manifest, files, &state, &home_dir, on_conflict)?,
+    };
+
+    let pb = progress::bar(files.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("[{elapsed_precise}] [{bar:40.yellow/white}] {pos}/{len} {msg}")?
+            .progress_chars("##-"),
+    );
+
+    for entry in &manifest.files {
+        let Some(content) = files.get(&entry.original_path) else {
             continue;
+        };
+        pb.set_message(format!("Extracting / 解压: {}", entry.original_path));
+
+        let skill = skill_root(entry);
+        let resolution = resolutions.get(skill).copied().unwrap_or(Resolution::Merge);
+        let original_path = match resolution {
+            Resolution::Skip => {
+                report.kept_local.push(format!("~/{}", entry.original_path));
+                pb.inc(1);
+                continue;
+            }
+            // 远端内容写到 <skill>-remote 目录下，本地 skill 不动
+            Resolution::Rename => format!("{}-remote{}", skill, &entry.original_path[skill.len()..]),
+            _ => entry.original_path.clone(),
+        };
+        let original_path = &original_path;
+        let contested = contested.contains(original_path.as_str());
+        // 另存的副本同时改写 SKILL.md 中的 name，避免与本地 skill 重名
+        let renamed_entry = (resolution == Resolution::Rename && entry.original_path == format!("{}/SKILL.md", skill))
+            .then(|| std::str::from_utf8(content).ok())
+            .flatten()
+            .and_then(|text| {
+                let name = skill.rsplit('/').next().unwrap_or(skill);
+                skill::set_name(text, &format!("{}-remote", name))
+            });
+        let content: &[u8] = renamed_entry.as_deref().map_or(content.as_slice(), str::as_bytes);
+        // 路径格式: .codex/skills/humanizer-zh/SKILL.md (已统一为正斜杠)
+        // 直接使用这个路径拼接（.claude 和 .codex 中的 . 是目录名的一部分）
+        let full_path = local_path(&home_dir, original_path)?;
+        if was_renamed(original_path) {
+            pb.suspend(|| {
+                summary::warn(&format!(
+                    "Reserved name renamed on Windows / Windows 保留名已重命名: {} -> {}",
+                    original_path,
+                    strip_long_prefix(&full_path).display()
+                ))
+            });
         }
 
-        pb.set_message(format!("Extracting / 解压: {}", filename));
+        // 与本地内容及同步基准做三方合并
+        let local = if full_path.is_file() { fs::read(&full_path).ok() } else { None };
+        let base = state.synced.get(original_path).map(String::as_str);
+        let display_path = format!("~/{}", original_path);
 
-        // 从 file_map 获取原始路径（包含 SKILL.md）
-        if let Some(original_path) = file_map.get(filename) {
-            // 路径格式: .codex/skills/humanizer-zh/SKILL.md (已统一为正斜杠)
-            // 直接使用这个路径拼接（.claude 和 .codex 中的 . 是目录名的一部分）
-            let full_path = home_dir.join(original_path);
+        let outcome = match resolution {
+            Resolution::TakeRemote if contested => MergeOutcome::TakeRemote,
+            Resolution::KeepLocal if contested => MergeOutcome::KeepLocal,
+            _ => three_way_merge(local.as_deref(), content, base),
+        };
+        let to_write = match outcome {
+            MergeOutcome::TakeRemote => {
+                report.extracted.push(display_path);
+                Some(content)
+            }
+            MergeOutcome::KeepLocal => {
+                report.kept_local.push(display_path);
+                None
+            }
+            MergeOutcome::Merged(merged) => {
+                fs::write(&full_path, &merged)?;
+                report.merged.push(display_path);
+                None
+            }
+            MergeOutcome::Conflict(conflicted) => {
+                fs::write(&full_path, &conflicted)?;
+                report.conflicts.push(display_path);
+                None
+            }
+        };
 
+        if let Some(data) = to_write {
             // 检查路径是否已存在且是目录
             if full_path.exists() {
                 if full_path.is_dir() {
@@ -416,195 +1948,904 @@ pub fn extract_zip(zip_path: &Path, _target_dir: &Path) -> Result<()> {
                 fs::create_dir_all(parent)?;
             }
 
-            let mut outfile = fs::File::create(&full_path)?;
-            std::io::copy(&mut zip_file, &mut outfile)?;
+            fs::write(&full_path, data)?;
+            // 直接采用远端内容时同时恢复远端的修改时间和权限；合并产生的新内容保留当前时间
+            restore_attributes(&full_path, entry);
+        }
 
-            // 记录解压的文件
-            extracted_files.push(format!("~/{}", original_path));
+        // 远端内容成为下一次合并的基准
+        if let Ok(sha256) = store_blob(content) {
+            state.synced.insert(original_path.clone(), sha256);
         }
+        downloaded.push(original_path.clone());
+        summary::record(|s| {
+            s.files.push(FileRecord {
+                path: original_path.clone(),
+                sha256: entry.sha256.clone(),
+            })
+        });
 
         pb.inc(1);
     }
 
     pb.finish_with_message("Extraction complete / 解压完成!");
+    state.mark_downloaded(&downloaded);
 
-    // 显示解压的文件列表
-    if !extracted_files.is_empty() {
-        println!();
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("  Extracted files / 解压文件:");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        for file in &extracted_files {
-            println!("  ✓ {}", file);
+    if let Err(e) = state.save() {
+        summary::warn(&format!("Failed to save sync state / 保存同步状态失败: {}", e));
+    }
+    history::record(&before, &state.synced, HistoryEvent::Download, code);
+
+    report.print();
+    summary::record(|s| {
+        s.extracted += report.extracted.len() + report.merged.len() + report.conflicts.len();
+        s.conflicts += report.conflicts.len();
+    });
+    Ok(report)
+}
+
+/// skill 根目录在原始路径中的前缀，例如 .claude/skills/x/scripts/a.sh -> .claude/skills/x
+fn skill_root(entry: &ManifestEntry) -> &str {
+    let path = entry.original_path.as_str();
+    if let Some(name) = &entry.skill_name {
+        let mut end = 0;
+        for component in path.split('/') {
+            end += component.len();
+            if component == name && end < path.len() {
+                return &path[..end];
+            }
+            end += 1;
         }
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     }
+    path.rsplit_once('/').map_or(path, |(dir, _)| dir)
+}
 
-    Ok(())
+/// 找出本地与远端内容不同、且本地在上次同步后被修改过（或从未同步过）的文件，按 skill 分组询问如何处理
+///
+/// 返回每个 skill 根目录的处理方式，以及这些有差异的文件的原始路径
+fn resolve_contested<'a>(
+    manifest: &'a Manifest,
+    files: &BTreeMap<String, Vec<u8>>,
+    state: &SyncState,
+    home_dir: &Path,
+    on_conflict: OnConflict,
+) -> Result<(HashMap<&'a str, Resolution>, HashSet<&'a str>)> {
+    let mut by_skill: BTreeMap<&str, Vec<(&str, Vec<u8>)>> = BTreeMap::new();
+    for entry in &manifest.files {
+        let Some(remote) = files.get(&entry.original_path) else {
+            continue;
+        };
+        let Ok(local) = fs::read(local_path(home_dir, &entry.original_path)?) else {
+            continue;
+        };
+        if local == *remote {
+            continue;
+        }
+        // 只有一方修改过时合并结果是确定的，不需要询问
+        let base = state.synced.get(&entry.original_path);
+        let sha256 = |content: &[u8]| format!("{:x}", Sha256::digest(content));
+        if base.is_some_and(|b| *b == sha256(&local) || *b == sha256(remote)) {
+            continue;
+        }
+        by_skill
+            .entry(skill_root(entry))
+            .or_default()
+            .push((entry.original_path.as_str(), local));
+    }
+
+    let mut resolver = Resolver::new(on_conflict);
+    let mut resolutions = HashMap::new();
+    let mut contested = HashSet::new();
+    for (skill, entries) in by_skill {
+        let diffs: Vec<ContestedFile> = entries
+            .iter()
+            .map(|(path, local)| ContestedFile {
+                path,
+                local,
+                remote: &files[*path],
+            })
+            .collect();
+        resolutions.insert(skill, resolver.resolve(&format!("~/{}", skill), &diffs)?);
+        contested.extend(entries.iter().map(|(path, _)| *path));
+    }
+    Ok((resolutions, contested))
 }
 
-/// 执行上传命令
-pub async fn execute_upload(dir: Option<String>, server: Option<String>) -> Result<()> {
-    // 如果没有指定服务器，则根据地理位置自动选择
-    let server_url = if let Some(s) = server {
-        println!("🌐 Using specified server / 使用指定服务器: {}", s);
-        s
+/// 命令结束时处理临时归档：
+/// - keep 为 None：删除临时文件
+/// - keep 为空字符串：原地保留
+/// - keep 为路径：移动到该路径（已存在的目录或以路径分隔符结尾时放入该目录）
+fn finish_archive(archive: TempArchive, keep: Option<&str>) -> Result<()> {
+    let Some(keep) = keep else {
+        drop(archive);
+        println!("🗑️  Temporary files cleaned / 已清理临时文件");
+        return Ok(());
+    };
+
+    let zip_path = archive.path();
+    let kept_path = if keep.is_empty() {
+        archive.keep()
     } else {
-        auto_select_server().await
+        let target = expand_path(keep)?;
+        if keep.ends_with('/') || keep.ends_with('\\') {
+            fs::create_dir_all(&target)?;
+        }
+        let target = if target.is_dir() {
+            target.join(zip_path.file_name().context("Invalid archive path / 无效的归档路径")?)
+        } else {
+            target
+        };
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        // 跨文件系统时 rename 会失败，退化为复制（临时文件由守卫删除）
+        if fs::rename(zip_path, &target).is_err() {
+            fs::copy(zip_path, &target).context("Failed to keep archive / 保留归档失败")?;
+        }
+        target
     };
 
-    let base_dirs = if let Some(d) = dir {
-        vec![PathBuf::from(d)]
+    println!("📦 Archive kept at / 归档已保留: {}", kept_path.display());
+    summary::record(|s| s.archives_kept += 1);
+    Ok(())
+}
+
+/// 已打包、待推送到各服务器的归档
+struct PackedUpload<'a> {
+    zip_path: &'a Path,
+    sha256: &'a str,
+    /// 原始路径 -> SHA256
+    files: &'a BTreeMap<String, String>,
+    /// 作为同步基准的本地内容：与 files 相同，只是被脱敏改写的文件为本地原文的 SHA256
+    synced: &'a BTreeMap<String, String>,
+    /// 实际存入归档的文件数
+    stored_count: usize,
+    pack: &'a PackOptions,
+    max_part_size: Option<u64>,
+    alias: Option<&'a str>,
+    skip_unchanged: bool,
+    hooks: &'a HooksConfig,
+}
+
+/// 执行上传命令：打包一次，依次推送到 servers 中的每个服务器（为空时根据地理位置自动选择）
+///
+/// 多个服务器时某个服务器失败不影响其余服务器，最后汇总每个服务器的业务码
+pub async fn execute_upload(dirs: Vec<PathBuf>, servers: Vec<String>, options: UploadOptions) -> Result<()> {
+    let UploadOptions {
+        mut pack,
+        max_part_size,
+        keep,
+        delta,
+        exclude,
+        skills,
+        names,
+        gitignore,
+        whole_dir,
+        max_file_size,
+        code: replace_code,
+        alias,
+        skip_unchanged,
+        include_private,
+        policy,
+        hooks,
+    } = options;
+
+    // 增量基础和业务码都属于某一个服务器
+    if servers.len() > 1 && (delta || replace_code.is_some()) {
+        return Err(anyhow::anyhow!(
+            "--delta and --code take a single server / --delta 和 --code 只能用于单个服务器"
+        ));
+    }
+
+    // 如果没有指定服务器，则根据地理位置自动选择
+    let server_urls = if servers.is_empty() {
+        vec![resolve_server(None).await?]
     } else {
-        get_default_skills_dirs()?
+        let mut urls = Vec::new();
+        for server in servers {
+            urls.push(resolve_server(Some(server)).await?);
+        }
+        urls
     };
 
-    // 创建临时 zip 文件
-    let temp_dir = std::env::temp_dir();
-    let zip_path = temp_dir.join(format!("skills_{}.zip", chrono::Utc::now().timestamp()));
+    let base_dirs = resolve_skill_dirs(dirs)?;
+    let _lock = lock_roots(&base_dirs, "upload")?;
+
+    // 增量上传以该服务器上一次上传的文件集合为基础
+    let mut state = SyncState::load()?;
+    if delta {
+        pack.base = state.uploads.get(&server_urls[0]).cloned();
+        // 新版本会替换业务码原来的内容，不能再以它为基础
+        if pack.base.as_ref().is_some_and(|b| Some(&b.code) == replace_code.as_ref()) {
+            pack.base = None;
+        }
+        // 增量链过长时下载需要逐个获取基础归档，超过深度后改为完整上传
+        if let Some(base) = pack.base.as_ref().filter(|b| b.chain_depth >= MAX_DELTA_CHAIN) {
+            println!(
+                "ℹ️  Delta chain of {} reached {} uploads, uploading everything / {} 的增量链已达 {} 次上传，将完整上传",
+                base.code, MAX_DELTA_CHAIN, base.code, MAX_DELTA_CHAIN
+            );
+            pack.base = None;
+        }
+        match &pack.base {
+            Some(base) => println!(
+                "🔁 Delta upload based on / 基于上次上传增量上传: {} ({})",
+                base.code,
+                base.uploaded_at.format("%Y-%m-%d %H:%M:%S")
+            ),
+            None => println!("ℹ️  No previous upload found, uploading everything / 未找到上次上传记录，将完整上传"),
+        }
+    }
+
+    // 创建临时归档文件（出错或中断时由守卫清理）
+    let archive = TempArchive::new("skills", pack.format.extension());
+    let zip_path = archive.path().to_path_buf();
 
     // 扫描文件
-    let skill_files = scan_skill_files(&base_dirs)?;
+    let skill_files = include_skills(scan_skill_files(&base_dirs, gitignore)?, &base_dirs, &names);
+    let skill_files = exclude_skills(skill_files, &base_dirs, &exclude);
+    let skill_files = select_skills(skill_files, &skills)?;
+    let skill_files = exclude_private(skill_files, include_private);
 
     if skill_files.is_empty() {
         println!("❌ No SKILL.md files found / 未找到任何 SKILL.md 文件");
         return Ok(());
     }
+    if let Some(policy) = &policy {
+        policy.check(&skill_files)?;
+    }
+    // 按名称分享的 skill 连同其脚本、模板等一起打包
+    let skill_files = if whole_dir || !skills.is_empty() {
+        expand_skill_dirs(skill_files, gitignore)
+    } else {
+        skill_files
+    };
+    check_file_sizes(&skill_files, max_file_size)?;
 
     // 创建 zip
-    let sha256 = create_skills_zip(&skill_files, &zip_path)?;
-    println!("✅ Zip file SHA256 / Zip 文件 SHA256: {}", sha256);
+    let packed = create_skills_zip(&skill_files, &zip_path, &pack)?;
+    let sha256 = packed.sha256.clone();
+    println!("✅ Archive SHA256 / 归档 SHA256: {}", sha256);
+
+    let files: BTreeMap<String, String> = packed
+        .manifest
+        .files
+        .iter()
+        .filter_map(|e| e.sha256.clone().map(|h| (e.original_path.clone(), h)))
+        .collect();
+    let mut synced = files.clone();
+    synced.extend(packed.local.clone());
+
+    let upload = PackedUpload {
+        zip_path: &zip_path,
+        sha256: &sha256,
+        files: &files,
+        synced: &synced,
+        stored_count: packed.stored_count,
+        pack: &pack,
+        max_part_size,
+        alias: alias.as_deref(),
+        skip_unchanged,
+        hooks: &hooks,
+    };
 
-    // 上传
-    let code = upload_zip(&zip_path, &server_url).await?;
-    println!("✅ Business code / 业务码: {}", code);
+    if server_urls.len() == 1 {
+        push_upload(&server_urls[0], &upload, replace_code, &mut state).await?;
+    } else {
+        let mut results = Vec::new();
+        for server_url in &server_urls {
+            println!();
+            println!("📤 Uploading to / 正在上传到: {}", server_url);
+            let result = push_upload(server_url, &upload, None, &mut state).await;
+            if let Err(e) = &result {
+                println!("❌ {}: {:#}", server_url, e);
+            }
+            results.push((server_url, result));
+        }
+
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec!["Server / 服务器", "Business code / 业务码"]);
+        for (server_url, result) in &results {
+            match result {
+                Ok(code) => table.add_row(vec![server_url.to_string(), code.clone()]),
+                Err(e) => table.add_row(vec![server_url.to_string(), format!("❌ {:#}", e)]),
+            };
+        }
+        println!();
+        println!("{}", table);
+
+        let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+        if failed > 0 {
+            let _ = finish_archive(archive, keep.as_deref());
+            return Err(anyhow::anyhow!(
+                "Upload failed on {} of {} servers / {} 个服务器中有 {} 个上传失败",
+                failed,
+                server_urls.len(),
+                server_urls.len(),
+                failed
+            ));
+        }
+    }
 
-    // 清理临时文件
-    fs::remove_file(&zip_path)?;
-    println!("🗑️  Temporary files cleaned / 已清理临时文件");
+    // 保留或清理临时文件
+    finish_archive(archive, keep.as_deref())?;
 
     Ok(())
 }
 
-/// 执行下载命令
-pub async fn execute_download(code: String, dir: Option<String>, server: Option<String>) -> Result<()> {
-    // 如果没有指定服务器，则根据地理位置自动选择
-    let server_url = if let Some(s) = server {
-        println!("🌐 Using specified server / 使用指定服务器: {}", s);
-        s
-    } else {
-        auto_select_server().await
+/// 将已打包的归档推送到一个服务器并记录到同步状态，返回业务码（内容未变化时为上次上传的业务码）
+async fn push_upload(
+    server_url: &str,
+    upload: &PackedUpload<'_>,
+    replace_code: Option<String>,
+    state: &mut SyncState,
+) -> Result<String> {
+    let PackedUpload {
+        zip_path,
+        sha256,
+        files,
+        synced,
+        stored_count,
+        pack,
+        max_part_size,
+        alias,
+        skip_unchanged,
+        hooks,
+    } = *upload;
+
+    // 在已有业务码下上传时先将别名解析为业务码
+    let replace_code = match replace_code {
+        Some(code) => Some(resolve_code(server_url, &code).await?.0),
+        None => None,
+    };
+
+    // 增量上传，或要求跳过未变化的上传时，没有任何变化则无需上传
+    let previous = state.uploads.get(server_url);
+    let unchanged = match &pack.base {
+        Some(base) => (stored_count == 0 && *files == base.files).then_some(base),
+        None => previous.filter(|p| {
+            skip_unchanged && p.files == *files && replace_code.as_ref().is_none_or(|c| *c == p.code)
+        }),
+    };
+    if let Some(previous) = unchanged {
+        println!(
+            "✅ No changes since last upload / 自上次上传以来没有变化, business code / 业务码: {}",
+            previous.code
+        );
+        return Ok(previous.code.clone());
+    }
+
+    hooks.run(
+        HookEvent::PreUpload,
+        &HookContext {
+            server: Some(server_url.to_string()),
+            archive: Some(zip_path.to_path_buf()),
+            files: files.keys().cloned().collect(),
+            ..Default::default()
+        },
+    )?;
+
+    // 上传前检查服务器大小限制
+    let archive_size = fs::metadata(zip_path)?.len();
+    let server_limit = fetch_max_upload_size(server_url).await?;
+    check_upload_size(archive_size, max_part_size, server_limit)?;
+
+    // 在已有业务码下上传时带上本机保存的所有者令牌
+    let owner_token = replace_code
+        .as_ref()
+        .and_then(|code| state.owner_tokens.get(server_url)?.get(code).cloned());
+    let target = UploadTarget {
+        code: replace_code.as_deref(),
+        owner_token: owner_token.as_deref(),
+        alias,
     };
+    if let Some(code) = &replace_code {
+        println!("🔁 Uploading new version of / 上传新版本到业务码: {}", code);
+    }
+
+    // 上传：超过分卷大小时拆分为多个分卷，再上传分卷索引作为逻辑业务码
+    let reply = match max_part_size {
+        Some(max) if archive_size > max => {
+            let parts = split_file(zip_path, max)?;
+            println!(
+                "✂️  Archive split into {} parts / 归档已拆分为 {} 个分卷",
+                parts.len(),
+                parts.len()
+            );
+
+            let uploaded = upload_parts(&parts, server_url, pack.jobs).await;
+
+            let mut index = PartsIndex::new(pack.format, sha256.to_string(), archive_size);
+            index.parts = uploaded?;
+
+            let index_path = PathBuf::from(format!("{}.parts.json", zip_path.display()));
+            index.write(&index_path)?;
+            let index_sha256 = file_sha256(&index_path)?;
+            upload_file(&index_path, &index_sha256, server_url, "skills.parts.json", "application/json", target).await?
+        }
+        _ => upload_zip(zip_path, sha256, server_url, pack.format, target).await?,
+    };
+    let code = reply.code;
+    println!("✅ Business code / 业务码: {}", code);
+    summary::record(|s| {
+        s.codes.push(code.clone());
+        s.sha256.push(sha256.to_string());
+    });
+    match (&alias, &reply.alias) {
+        (_, Some(confirmed)) => println!("🏷️  Alias / 别名: {}", confirmed),
+        (Some(_), None) => summary::warn("Server does not support aliases, use the business code instead / 服务器不支持别名，请使用业务码"),
+        _ => {}
+    }
+    if let Some(token) = reply.owner_token {
+        state
+            .owner_tokens
+            .entry(server_url.to_string())
+            .or_default()
+            .insert(code.clone(), token);
+    }
+
+    hooks.run(
+        HookEvent::PostUpload,
+        &HookContext {
+            code: Some(code.clone()),
+            server: Some(server_url.to_string()),
+            archive: Some(zip_path.to_path_buf()),
+            files: files.keys().cloned().collect(),
+            ..Default::default()
+        },
+    )?;
+
+    // 记录本次上传的完整文件集合，供下次增量上传和三方合并使用
+    let before = state.synced.clone();
+    state.synced.extend(synced.clone());
+    state.mark_uploaded(files.keys());
+    state.uploads.insert(
+        server_url.to_string(),
+        UploadRecord {
+            code: code.clone(),
+            archive_sha256: sha256.to_string(),
+            uploaded_at: chrono::Utc::now(),
+            files: files.clone(),
+            chain_depth: pack.base.as_ref().map_or(0, |b| b.chain_depth + 1),
+        },
+    );
+    if let Err(e) = state.save() {
+        summary::warn(&format!("Failed to save sync state / 保存同步状态失败: {}", e));
+    }
+    history::record(&before, &state.synced, HistoryEvent::Upload, Some(&code));
+
+    Ok(code)
+}
+
+/// 增量归档基础链的最大深度，防止循环引用
+const MAX_BASE_CHAIN: usize = 64;
+
+/// 增量上传的基础链达到该深度后改为完整上传，使下载时的基础链保持在 MAX_BASE_CHAIN 以内
+const MAX_DELTA_CHAIN: usize = 16;
+
+/// 执行下载命令，返回实际写入的文件数
+pub async fn execute_download(
+    code: String,
+    dir: Option<PathBuf>,
+    server: Option<String>,
+    options: DownloadOptions,
+) -> Result<usize> {
+    let DownloadOptions {
+        jobs,
+        keep,
+        archive_out,
+        no_cache,
+        include,
+        on_conflict,
+        hooks,
+    } = options;
+
+    // 如果没有指定服务器，则根据地理位置自动选择
+    let server_url = resolve_server(server).await?;
 
     let target_dir = if let Some(d) = dir {
-        PathBuf::from(d)
+        d
     } else {
         // 默认解压到 .claude/skills
         let home_dir = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
         home_dir.join(".claude").join("skills")
     };
 
-    // 创建临时 zip 文件
-    let temp_dir = std::env::temp_dir();
-    let zip_path = temp_dir.join(format!("skills_{}.zip", chrono::Utc::now().timestamp()));
+    // 解压会写入各个 skills 根目录，全部加锁
+    let mut roots = get_default_skills_dirs()?;
+    roots.push(target_dir.clone());
+    let mut lock = lock_roots(&roots, &format!("download {}", code))?;
+
+    // 创建临时归档文件（基础链、分卷等派生文件也由守卫清理），格式在下载后才能确定
+    let mut archive = TempArchive::new("skills", "download");
+
+    hooks.run(
+        HookEvent::PreDownload,
+        &HookContext {
+            code: Some(code.clone()),
+            server: Some(server_url.clone()),
+            target: Some(target_dir.clone()),
+            ..Default::default()
+        },
+    )?;
+
+    let sha256 = fetch_archive(&code, &server_url, archive.path(), jobs, no_cache).await?;
+    summary::record(|s| {
+        s.codes.push(code.clone());
+        s.sha256.push(sha256);
+    });
+
+    // 下载前不知道归档格式，按实际格式修正扩展名，保留的归档和派生文件名与内容一致
+    let format = ArchiveFormat::detect(archive.path())?;
+    archive.set_extension(format.extension())?;
+    let zip_path = archive.path().to_path_buf();
+
+    hooks.run(
+        HookEvent::PostDownload,
+        &HookContext {
+            code: Some(code.clone()),
+            server: Some(server_url.clone()),
+            archive: Some(zip_path.clone()),
+            target: Some(target_dir.clone()),
+            ..Default::default()
+        },
+    )?;
+
+    // 仅保存归档，不解压
+    if let Some(out) = archive_out {
+        let suffix = format!(".{}", format.extension());
+        if Path::new(&out).extension().is_some() && !out.ends_with(&suffix) {
+            summary::warn(&format!(
+                "Archive format is {} but --archive-out does not end in {} / 归档格式为 {}，但 --archive-out 的扩展名不是 {}",
+                format.extension(),
+                suffix,
+                format.extension(),
+                suffix
+            ));
+        }
+        return finish_archive(archive, Some(&out)).map(|()| 0);
+    }
+
+    // 增量归档：下载基础链上的归档，未变化的文件从中获取
+    let manifest = Manifest::read_from_archive(&zip_path)?.unwrap_or_default();
+    // 实际写入位置由 manifest 中的原始路径决定，可能不在默认目录下，解压前一并加锁
+    lock.extend(&extraction_roots(&manifest)?, &format!("download {}", code))?;
+    let selected = select_included(&manifest, &include);
+    if selected.as_ref().is_some_and(|s| s.is_empty()) {
+        println!("❌ No skills match --include / 没有匹配 --include 的 skills");
+        return finish_archive(archive, keep.as_deref()).map(|()| 0);
+    }
+    let base_archives = fetch_base_chain(&manifest, &zip_path, &server_url, jobs, no_cache).await?;
+
+    let mut hook_context = HookContext {
+        code: Some(code.clone()),
+        server: Some(server_url.clone()),
+        archive: Some(zip_path.clone()),
+        target: Some(target_dir.clone()),
+        files: manifest
+            .files
+            .iter()
+            .map(|e| e.original_path.clone())
+            .filter(|p| selected.as_ref().is_none_or(|s| s.contains(p)))
+            .collect(),
+    };
+    hooks.run(HookEvent::PreExtract, &hook_context)?;
 
-    // 下载
-    let sha256 = download_zip(&code, &server_url, &zip_path).await?;
-    println!("Zip file SHA256 / Zip 文件 SHA256: {}", sha256);
+    // 解压：未变化的文件从基础链中取最终内容，与本次归档中的文件一起只合并一次
+    let result = extract_zip(&zip_path, &target_dir, &base_archives, selected.as_ref(), on_conflict, Some(&code));
+    for base_path in &base_archives {
+        let _ = fs::remove_file(base_path);
+    }
+    let mut written: Vec<String> = result?.written().collect();
 
-    // 解压
-    extract_zip(&zip_path, &target_dir)?;
+    // post-extract 钩子只收到实际写入的文件
+    written.sort();
+    written.dedup();
+    hook_context.files = written;
+    hooks.run(HookEvent::PostExtract, &hook_context)?;
 
-    // 清理临时文件
-    fs::remove_file(&zip_path)?;
+    // 保留或清理临时文件
+    finish_archive(archive, keep.as_deref())?;
 
-    Ok(())
+    Ok(hook_context.files.len())
 }
 
-/// Skill 信息结构体
-struct SkillInfo {
-    name: String,
-    description: String,
-    path: String,
+/// 读取业务码文件：每行一个业务码，忽略空行和 # 开头的注释
+pub fn read_codes_file(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read codes file / 读取业务码文件失败: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or_default().trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
 }
 
-/// SKILL.md 的 YAML front matter 结构
-#[derive(Deserialize)]
-struct SkillMetadata {
-    name: Option<String>,
-    description: Option<String>,
-    #[serde(rename = "allowed-tools")]
-    allowed_tools: Option<Vec<String>>,
-    metadata: Option<serde_yaml::Value>,
-}
-
-/// 从 SKILL.md 文件中提取描述信息
-fn extract_description(content: &str) -> String {
-    // 提取 YAML front matter (--- 之间的内容)
-    if let Some(yaml_start) = content.find("---") {
-        if let Some(yaml_end) = content[yaml_start + 3..].find("---") {
-            let yaml_content = &content[yaml_start + 3..yaml_start + 3 + yaml_end];
-
-            // 使用 serde_yaml 反序列化
-            if let Ok(metadata) = serde_yaml::from_str::<SkillMetadata>(yaml_content) {
-                if let Some(desc) = metadata.description {
-                    // 清理换行符和多余空格
-                    let cleaned = desc
-                        .lines()
-                        .map(|line| line.trim())
-                        .collect::<Vec<_>>()
-                        .join(" ");
-                    return cleaned.chars().take(100).collect::<String>();
-                }
-            }
+/// 依次下载多个业务码，最后汇总每个业务码的结果；任一失败时返回错误（其余业务码仍会下载）
+///
+/// 只有一个业务码时与 execute_download 相同
+pub async fn execute_download_all(
+    codes: Vec<String>,
+    dir: Option<PathBuf>,
+    server: Option<String>,
+    options: DownloadOptions,
+) -> Result<()> {
+    let mut unique = Vec::new();
+    for code in codes {
+        if !unique.contains(&code) {
+            unique.push(code);
         }
     }
+    match unique.len() {
+        0 => return Err(anyhow::anyhow!("No business code given / 没有指定业务码")),
+        1 => return execute_download(unique.remove(0), dir, server, options).await.map(|_| ()),
+        _ => {}
+    }
+    if options.archive_out.is_some() || options.keep.as_deref().is_some_and(|k| !k.is_empty()) {
+        return Err(anyhow::anyhow!(
+            "--archive-out and --keep-archive PATH take a single code / --archive-out 和 --keep-archive PATH 只能用于单个业务码"
+        ));
+    }
 
-    // 如果没有找到 YAML description，尝试其他格式
-    let patterns = [
-        // 匹配 ## Description / ## 描述 部分
-        "##\\s*(?:Description|描述)\\s*\\n\\s*([^\\n]+)",
-        // 匹配 [!description]: ... 格式
-        "\\[!?description\\]:\\s*([^\\n]+)",
-    ];
-
-    for pattern in patterns {
-        if let Ok(re) = Regex::new(pattern) {
-            if let Some(caps) = re.captures(content) {
-                if let Some(desc) = caps.get(1) {
-                    return desc.as_str().trim().to_string();
-                }
-            }
+    // 只选择一次服务器，避免每个业务码都重新检测地理位置
+    let server_url = resolve_server(server).await?;
+    let total = unique.len();
+    let mut results = Vec::new();
+    for (i, code) in unique.into_iter().enumerate() {
+        println!();
+        println!("📥 [{}/{}] Downloading / 正在下载: {}", i + 1, total, code);
+        let result = execute_download(code.clone(), dir.clone(), Some(server_url.clone()), options.clone()).await;
+        if let Err(e) = &result {
+            println!("❌ {}: {:#}", code, e);
         }
+        results.push((code, result));
     }
 
-    // 如果没有找到特定的描述字段，尝试提取第一段非空文本
-    for line in content.lines() {
-        let trimmed = line.trim();
-        // 跳过 YAML 相关行、标题行和空行
-        if !trimmed.starts_with('#')
-            && !trimmed.starts_with("---")
-            && !trimmed.starts_with("name:")
-            && !trimmed.starts_with("description:")
-            && !trimmed.starts_with("allowed-tools:")
-            && !trimmed.starts_with("metadata:")
-            && !trimmed.is_empty()
-        {
-            return trimmed.chars().take(80).collect::<String>();
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Code / 业务码", "Result / 结果", "Files / 文件"]);
+    for (code, result) in &results {
+        match result {
+            Ok(files) => table.add_row(vec![code.clone(), "✅".to_string(), files.to_string()]),
+            Err(e) => table.add_row(vec![code.clone(), format!("❌ {:#}", e), "-".to_string()]),
+        };
+    }
+    println!();
+    println!("{}", table);
+
+    let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+    if failed > 0 {
+        return Err(anyhow::anyhow!(
+            "{} of {} downloads failed / {} 个业务码中有 {} 个下载失败",
+            failed,
+            total,
+            total,
+            failed
+        ));
+    }
+    println!("✅ Downloaded {} codes / 已下载 {} 个业务码", total, total);
+    Ok(())
+}
+
+/// manifest 中各 skill 解压到的 skills 根目录（SKILL.md 所在目录的上一级）
+pub(crate) fn extraction_roots(manifest: &Manifest) -> Result<Vec<PathBuf>> {
+    let home_dir = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
+    let mut roots: Vec<PathBuf> = manifest
+        .files
+        .iter()
+        .filter(|e| {
+            e.original_path
+                .rsplit('/')
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case("SKILL.md"))
+        })
+        .map(|e| local_path(&home_dir, &e.original_path))
+        .filter_map(|path| match path {
+            Ok(path) => strip_long_prefix(&path).parent()?.parent().map(|p| Ok(p.to_path_buf())),
+            Err(e) => Some(Err(e)),
+        })
+        .collect::<Result<_>>()?;
+    roots.sort();
+    roots.dedup();
+    Ok(roots)
+}
+
+/// 根据 --include 模式筛选 manifest 中要解压的原始路径，模式与 skill 名称或原始路径匹配；
+/// 没有指定模式时返回 None（解压全部）
+fn select_included(manifest: &Manifest, include: &[glob::Pattern]) -> Option<HashSet<String>> {
+    if include.is_empty() {
+        return None;
+    }
+
+    let selected: HashSet<String> = manifest
+        .files
+        .iter()
+        .filter(|e| {
+            include.iter().any(|p| {
+                e.skill_name.as_deref().is_some_and(|name| p.matches(name)) || p.matches(&e.original_path)
+            })
+        })
+        .map(|e| e.original_path.clone())
+        .collect();
+
+    let skills: HashSet<&str> = manifest
+        .files
+        .iter()
+        .filter(|e| selected.contains(&e.original_path))
+        .filter_map(|e| e.skill_name.as_deref())
+        .collect();
+    println!(
+        "🔍 {} files from {} skills match --include / {} 个文件（{} 个 skills）匹配 --include",
+        selected.len(),
+        skills.len(),
+        selected.len(),
+        skills.len()
+    );
+    Some(selected)
+}
+
+/// 下载增量归档的基础链，按从新到旧的顺序返回基础归档路径
+pub(crate) async fn fetch_base_chain(
+    manifest: &Manifest,
+    zip_path: &Path,
+    server_url: &str,
+    jobs: usize,
+    no_cache: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut base_archives = Vec::new();
+    let mut next_base = manifest.base_code.clone();
+    while let Some(base_code) = next_base {
+        if base_archives.len() >= MAX_BASE_CHAIN {
+            return Err(anyhow::anyhow!(
+                "Delta chain too long (> {}) / 增量链过长",
+                MAX_BASE_CHAIN
+            ));
         }
+        println!("🔗 Fetching base archive / 获取基础归档: {}", base_code);
+        let base_path = PathBuf::from(format!("{}.base{}", zip_path.display(), base_archives.len() + 1));
+        fetch_archive(&base_code, server_url, &base_path, jobs, no_cache)
+            .await
+            .with_context(|| format!("Failed to fetch base archive {} / 获取基础归档失败", base_code))?;
+        next_base = Manifest::read_from_archive(&base_path)?.and_then(|m| m.base_code);
+        base_archives.push(base_path);
     }
+    Ok(base_archives)
+}
 
-    "No description".to_string()
+/// 服务器上业务码（或别名）的当前信息
+#[derive(Debug, Default)]
+pub(crate) struct CodeInfo {
+    /// 实际业务码（传入的是别名时与之不同）
+    pub(crate) code: Option<String>,
+    /// 当前归档的 SHA256
+    pub(crate) sha256: Option<String>,
 }
 
-/// 执行列表命令
-pub fn execute_list(dir: Option<String>) -> Result<()> {
-    let base_dirs = if let Some(d) = dir {
-        vec![PathBuf::from(d)]
+/// 查询业务码或别名对应的实际业务码和当前归档 SHA256，服务器不支持或请求失败时返回空信息；
+/// 无法创建客户端或服务器证书与记录不一致时报错
+pub(crate) async fn fetch_code_info(server_url: &str, code: &str) -> Result<CodeInfo> {
+    if grpc::is_grpc_url(server_url) {
+        return Ok(grpc::info(server_url, code).await.unwrap_or_default());
+    }
+    let url = format!("{}/sync/info/{}", server_url, code);
+    let client = http::client()?;
+    let Some(response) = http::try_send(client.get(&url)).await? else {
+        return Ok(CodeInfo::default());
+    };
+    if !response.status().is_success() {
+        return Ok(CodeInfo::default());
+    }
+    let Ok(result) = response.json::<serde_json::Value>().await else {
+        return Ok(CodeInfo::default());
+    };
+    let body = api::payload(server_url, &result);
+    Ok(CodeInfo {
+        code: body["code"].as_str().map(str::to_string),
+        sha256: body["sha256"].as_str().map(|s| s.to_lowercase()),
+    })
+}
+
+/// 将别名解析为实际业务码，同时返回服务器当前的归档 SHA256
+pub(crate) async fn resolve_code(server_url: &str, code: &str) -> Result<(String, Option<String>)> {
+    let info = fetch_code_info(server_url, code).await?;
+    Ok(match info.code {
+        Some(resolved) if resolved != code => {
+            println!("🏷️  Alias {} -> code / 别名 {} -> 业务码: {}", code, code, resolved);
+            (resolved, info.sha256)
+        }
+        _ => (code.to_string(), info.sha256),
+    })
+}
+
+/// 获取业务码对应的归档：优先使用本地缓存，避免重复下载（如上次解压失败后重试）
+///
+/// 别名先解析为实际业务码；业务码已更新为新版本时不使用旧缓存
+pub(crate) async fn fetch_archive(code: &str, server_url: &str, zip_path: &Path, jobs: usize, no_cache: bool) -> Result<String> {
+    let (code, remote) = resolve_code(server_url, code).await?;
+    let code = code.as_str();
+    let cached = if no_cache { None } else { cache::lookup(server_url, code, remote.as_deref()) };
+    if let Some((cached_path, sha256)) = cached {
+        println!("💾 Using cached archive / 使用缓存归档: {}", cached_path.display());
+        fs::copy(&cached_path, zip_path).context("Failed to read download cache / 读取下载缓存失败")?;
+        println!("Zip file SHA256 / Zip 文件 SHA256: {}", sha256);
+        Ok(sha256)
     } else {
-        get_default_skills_dirs()?
+        download_to(code, server_url, zip_path, jobs, no_cache).await
+    }
+}
+
+/// 从服务器下载业务码对应的完整归档（自动合并分卷），校验通过后写入缓存；返回归档的 SHA256
+async fn download_to(code: &str, server_url: &str, zip_path: &Path, jobs: usize, no_cache: bool) -> Result<String> {
+    let remote = download_zip(code, server_url, zip_path).await?;
+    let sha256 = remote.clone();
+
+    // 业务码指向分卷索引时，下载全部分卷并合并为完整归档
+    let sha256 = if let Some(index) = PartsIndex::read(zip_path)? {
+        println!(
+            "🧩 Archive has {} parts / 归档包含 {} 个分卷",
+            index.parts.len(),
+            index.parts.len()
+        );
+        let parts = download_parts(&index, server_url, zip_path, jobs).await;
+        let joined = parts.and_then(|parts| {
+            let result = join_files(&parts, zip_path, &index.sha256);
+            for part in &parts {
+                let _ = fs::remove_file(part);
+            }
+            result
+        });
+        joined?;
+        println!("Archive SHA256 / 归档 SHA256: {}", index.sha256);
+        index.sha256
+    } else {
+        println!("Zip file SHA256 / Zip 文件 SHA256: {}", sha256);
+        sha256
     };
 
+    if !no_cache {
+        if let Err(e) = cache::store(server_url, code, zip_path, &sha256, &remote) {
+            summary::warn(&format!("Failed to cache archive / 缓存归档失败: {}", e));
+        }
+    }
+
+    Ok(sha256)
+}
+
+/// 执行缓存清理命令
+pub fn execute_cache_clear() -> Result<()> {
+    let freed = cache::clear()?;
+    println!("🗑️  Download cache cleared, freed {} / 已清空下载缓存，释放 {}", HumanBytes(freed), HumanBytes(freed));
+    Ok(())
+}
+
+/// Skill 信息结构体
+pub(crate) struct SkillInfo {
+    pub(crate) name: String,
+    pub(crate) description: String,
+    pub(crate) path: String,
+    /// SKILL.md 的实际路径（path 仅用于显示）
+    pub(crate) file: PathBuf,
+    pub(crate) tags: Vec<String>,
+    /// front matter 中的 version
+    pub(crate) version: Option<String>,
+    pub(crate) size: u64,
+    pub(crate) modified: Option<chrono::DateTime<chrono::Utc>>,
+    /// front matter 中标记为私有（sync: false / private: true）
+    pub(crate) private: bool,
+    /// 最近一次上传和下载的时间
+    pub(crate) synced: SkillSyncTimes,
+}
+
+/// 按来源目录分组收集 skills（不输出任何内容）
+pub(crate) fn collect_skills(dirs: Vec<PathBuf>) -> Result<Vec<(String, Vec<SkillInfo>)>> {
+    let base_dirs = resolve_skill_dirs(dirs)?;
+    let state = SyncState::load()?;
+
     // 按来源目录分组存储 skills
     let mut skills_by_source: Vec<(String, Vec<SkillInfo>)> = Vec::new();
 
@@ -615,18 +2856,14 @@ pub fn execute_list(dir: Option<String>) -> Result<()> {
             continue;
         }
 
-        // 确定来源名称
-        let source_name = if let Some(parent) = base_dir.parent() {
-            parent
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("Unknown")
-                .to_string()
-        } else {
-            "Unknown".to_string()
-        };
+        // 确定来源名称（非 UTF-8 名称以替换字符显示）
+        let source_name = base_dir
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Unknown".to_string());
 
-        for entry in WalkDir::new(base_dir)
+        for entry in WalkDir::new(long_path(base_dir))
             .min_depth(1)
             .max_depth(3)
             .into_iter()
@@ -636,23 +2873,29 @@ pub fn execute_list(dir: Option<String>) -> Result<()> {
             if path.file_name() == Some(std::ffi::OsStr::new("SKILL.md"))
                 || path.file_name() == Some(std::ffi::OsStr::new("skill.md"))
             {
-                // 获取 skill 名称（目录名）
+                // 获取 skill 名称（目录名，非 UTF-8 名称以替换字符显示，与打包时一致）
                 let name = path
                     .parent()
                     .and_then(|p| p.file_name())
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
+                    .map(|n| normalize_nfc(&n.to_string_lossy()))
+                    .unwrap_or_else(|| "unknown".to_string());
 
                 // 读取文件内容
                 let content = fs::read_to_string(path).unwrap_or_default();
-                let description = extract_description(&content);
+                let description = skill::description(&content);
+                let metadata = skill::parse(&content).unwrap_or_default();
+                let file_meta = entry.metadata().ok();
+                let size = file_meta.as_ref().map(|m| m.len()).unwrap_or(0);
+                let modified = file_meta
+                    .and_then(|m| m.modified().ok())
+                    .map(chrono::DateTime::<chrono::Utc>::from);
 
                 // 获取相对路径
                 let home_dir = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
-                let relative_path = path
+                let display = strip_long_prefix(path);
+                let relative_path = display
                     .strip_prefix(&home_dir)
-                    .unwrap_or(path)
+                    .unwrap_or(&display)
                     .to_string_lossy()
                     .replace('\\', "/");
 
@@ -660,6 +2903,17 @@ pub fn execute_list(dir: Option<String>) -> Result<()> {
                     name,
                     description,
                     path: format!("~/{}", relative_path),
+                    file: path.to_path_buf(),
+                    tags: metadata.tags,
+                    version: metadata.version,
+                    size,
+                    modified,
+                    private: metadata.private,
+                    synced: path
+                        .parent()
+                        .and_then(|dir| state.last_synced.get(&original_path(dir)))
+                        .copied()
+                        .unwrap_or_default(),
                 });
             }
         }
@@ -669,12 +2923,64 @@ pub fn execute_list(dir: Option<String>) -> Result<()> {
         }
     }
 
+    Ok(skills_by_source)
+}
+
+/// 超过 stale 未上传或下载（包括从未同步）
+fn is_stale(synced: &SkillSyncTimes, stale: std::time::Duration, now: chrono::DateTime<chrono::Utc>) -> bool {
+    match synced.last() {
+        Some(last) => (now - last).to_std().is_ok_and(|age| age > stale),
+        None => true,
+    }
+}
+
+/// 以 CSV / TSV 格式输出 skills 清单
+fn write_inventory(
+    skills_by_source: &[(String, Vec<SkillInfo>)],
+    columns: &[ListColumn],
+    cells: CellFormat,
+    delimiter: u8,
+) -> Result<()> {
+    let cells = CellFormat { truncate: None, ..cells };
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(std::io::stdout());
+    writer.write_record(columns.iter().map(|c| c.key()))?;
+    for (source, skills) in skills_by_source {
+        for skill in skills {
+            writer.write_record(columns.iter().map(|&c| table::cell(c, source, skill, cells)))?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// 执行列表命令
+pub fn execute_list(dirs: Vec<PathBuf>, options: ListOptions) -> Result<()> {
+    let mut skills_by_source = collect_skills(dirs)?;
+    if let Some(stale) = options.stale {
+        let now = chrono::Utc::now();
+        for (_, skills) in &mut skills_by_source {
+            skills.retain(|skill| is_stale(&skill.synced, stale, now));
+        }
+        skills_by_source.retain(|(_, skills)| !skills.is_empty());
+    }
+
+    // 清单默认包含全部列，只有命令行指定的列会改变清单格式，配置中的列只影响表格
+    let inventory_columns = options.columns.clone().unwrap_or_else(|| ListColumn::value_variants().to_vec());
+    match options.format {
+        ListFormat::Table => {}
+        ListFormat::Csv => return write_inventory(&skills_by_source, &inventory_columns, options.cells, b','),
+        ListFormat::Tsv => return write_inventory(&skills_by_source, &inventory_columns, options.cells, b'\t'),
+    }
+
     if skills_by_source.is_empty() {
         println!("❌ No skills found / 未找到任何 skills");
         return Ok(());
     }
 
     let total_count: usize = skills_by_source.iter().map(|(_, v)| v.len()).sum();
+    let columns = options.columns.unwrap_or_else(|| options.style.columns.clone());
 
     // 按来源分组显示
     for (source, skills) in &skills_by_source {
@@ -685,22 +2991,9 @@ pub fn execute_list(dir: Option<String>) -> Result<()> {
         println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
         // 创建表格
-        let mut table = Table::new();
-        table
-            .load_preset(UTF8_FULL)
-            .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
-                "Name / 名称",
-                "Description / 描述",
-                "Path / 路径",
-            ]);
-
+        let mut table = options.style.table(&columns);
         for skill in skills {
-            table.add_row(vec![
-                skill.name.as_str(),
-                skill.description.as_str(),
-                skill.path.as_str(),
-            ]);
+            table.add_row(columns.iter().map(|&c| table::cell(c, source, skill, options.cells)));
         }
 
         println!("{table}");
@@ -714,3 +3007,57 @@ pub fn execute_list(dir: Option<String>) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_downloads_into_contiguous_segments() {
+        assert_eq!(segment_ranges(10, 4), vec![(0, 9)]);
+        let ranges = segment_ranges(5 * MIN_SEGMENT_SIZE + 1, 4);
+        assert_eq!(ranges.len(), 4);
+        assert_eq!(ranges[0].0, 0);
+        assert_eq!(ranges[3].1, 5 * MIN_SEGMENT_SIZE);
+        assert!(ranges.windows(2).all(|w| w[0].1 + 1 == w[1].0));
+    }
+
+    #[test]
+    fn reads_files_in_order_and_detects_duplicates() {
+        let dir = std::env::temp_dir().join(format!("skills-sync-read-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let files: Vec<PathBuf> = (0..7).map(|i| dir.join(format!("{}.md", i))).collect();
+        for (i, file) in files.iter().enumerate() {
+            fs::write(file, i.to_string()).unwrap();
+        }
+        let contents: Vec<_> = read_files_ordered(&files, 3).map(Result::unwrap).collect();
+        assert_eq!(contents, (0..7).map(|i| i.to_string().into_bytes()).collect::<Vec<_>>());
+        assert!(!has_duplicate_content(&files, 3));
+
+        fs::write(&files[6], "2").unwrap();
+        assert!(has_duplicate_content(&files, 3));
+        assert!(read_files_ordered(&[dir.join("missing.md")], 2).next().unwrap().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn finds_stale_skills() {
+        let now = chrono::Utc::now();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        assert!(is_stale(&SkillSyncTimes::default(), day, now));
+        let synced = SkillSyncTimes {
+            uploaded: Some(now - chrono::Duration::days(3)),
+            downloaded: Some(now - chrono::Duration::hours(1)),
+        };
+        assert!(!is_stale(&synced, day, now));
+        assert!(is_stale(&SkillSyncTimes { downloaded: None, ..synced }, day, now));
+    }
+
+    #[test]
+    fn requires_matching_upload_checksum() {
+        assert!(check_upload_sha256("abc", Some("ABC"), false).is_ok());
+        assert!(check_upload_sha256("abc", Some("def"), true).is_err());
+        assert!(check_upload_sha256("abc", None, true).is_ok());
+        assert!(check_upload_sha256("abc", None, false).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml_edit::DocumentMut;
+
+use crate::sync::encoding::UploadEncoding;
+use crate::sync::filter::AssetFilter;
+use crate::sync::hooks::HooksConfig;
+use crate::sync::locations;
+use crate::sync::paths::expand_path;
+use crate::sync::policy::Policy;
+use crate::sync::prune::Retention;
+use crate::sync::redact::RedactRule;
+use crate::sync::table::ListStyle;
+
+/// 指定配置文件位置的环境变量（也会传给插件）
+pub const CONFIG_ENV: &str = "SKILLS_SYNC_CONFIG";
+
+/// 覆盖配置项的环境变量：优先级高于配置文件，低于命令行参数
+///
+/// 值的写法与 config set 相同；SKILLS_SYNC_DIRS 按系统的 PATH 分隔符（Unix 为 :，Windows 为 ;）拆分为多个目录。
+/// API 令牌的环境变量 SKILLS_SYNC_TOKEN 由 http::init_api_token 处理（优先于系统密钥环）
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("SKILLS_SYNC_SERVER", "server"),
+    ("SKILLS_SYNC_DIRS", "dirs"),
+    ("SKILLS_SYNC_COMPRESSION_LEVEL", "compression_level"),
+    ("SKILLS_SYNC_NOTIFY", "notify"),
+    ("SKILLS_SYNC_WEBSOCKET", "websocket"),
+    ("SKILLS_SYNC_HTTP3", "http3"),
+    ("SKILLS_SYNC_UPLOAD_ENCODING", "upload_encoding"),
+    ("SKILLS_SYNC_WHOLE_DIR", "whole_dir"),
+    ("SKILLS_SYNC_MAX_FILE_SIZE", "max_file_size"),
+    ("SKILLS_SYNC_GIT_HISTORY", "git_history"),
+    ("SKILLS_SYNC_TELEMETRY", "telemetry"),
+    ("SKILLS_SYNC_REDACT", "redact"),
+    ("SKILLS_SYNC_HOOKS_PRE_UPLOAD", "hooks.pre-upload"),
+    ("SKILLS_SYNC_HOOKS_POST_UPLOAD", "hooks.post-upload"),
+    ("SKILLS_SYNC_HOOKS_PRE_DOWNLOAD", "hooks.pre-download"),
+    ("SKILLS_SYNC_HOOKS_POST_DOWNLOAD", "hooks.post-download"),
+    ("SKILLS_SYNC_HOOKS_PRE_EXTRACT", "hooks.pre-extract"),
+    ("SKILLS_SYNC_HOOKS_POST_EXTRACT", "hooks.post-extract"),
+    ("SKILLS_SYNC_FILTER_INCLUDE_EXTENSIONS", "filter.include-extensions"),
+    ("SKILLS_SYNC_FILTER_EXCLUDE_EXTENSIONS", "filter.exclude-extensions"),
+    ("SKILLS_SYNC_FILTER_EXCLUDE_MIME", "filter.exclude-mime"),
+    ("SKILLS_SYNC_FILTER_SKIP_BINARY", "filter.skip-binary"),
+    ("SKILLS_SYNC_LIST_COLUMNS", "list.columns"),
+    ("SKILLS_SYNC_LIST_PRESET", "list.preset"),
+    ("SKILLS_SYNC_LIST_HEADER_COLOR", "list.header-color"),
+    ("SKILLS_SYNC_POLICY_REQUIRED_FIELDS", "policy.required-fields"),
+    ("SKILLS_SYNC_POLICY_MAX_DESCRIPTION_LENGTH", "policy.max-description-length"),
+    ("SKILLS_SYNC_POLICY_MIN_TAGS", "policy.min-tags"),
+    ("SKILLS_SYNC_RETENTION_KEEP_LAST", "retention.keep-last"),
+    ("SKILLS_SYNC_RETENTION_MAX_AGE", "retention.max-age"),
+    ("SKILLS_SYNC_RETENTION_MAX_SIZE", "retention.max-size"),
+    ("SKILLS_SYNC_RETENTION_AUTO", "retention.auto"),
+];
+
+/// 用户配置（~/.config/skills-sync/config.toml）
+///
+/// 所有字段均为可选；优先级从低到高为配置文件、SKILLS_SYNC_* 环境变量、命令行参数
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// 默认服务器地址，未指定 --server 时使用（管理命令只连接 --server 或该地址）
+    pub server: Option<String>,
+    /// 未指定 --dir 时 upload、list、catalog、export 扫描的目录（支持 ~ 和环境变量）
+    pub dirs: Vec<String>,
+    /// 默认压缩级别
+    pub compression_level: Option<i64>,
+    /// 访问同步服务器的 API 令牌
+    pub token: Option<String>,
+    /// 上传、下载完成或失败时发送桌面通知
+    pub notify: bool,
+    /// 通过 WebSocket 传输归档
+    pub websocket: bool,
+    /// 优先通过 HTTP/3 访问 https 服务器
+    pub http3: bool,
+    /// 上传请求体的压缩方式（none、auto、zstd、gzip），服务器公布支持时生效
+    pub upload_encoding: UploadEncoding,
+    /// 上传时打包每个 skill 目录下的全部文件
+    pub whole_dir: bool,
+    /// 单个文件最大大小（如 "256K"），超出时中止上传
+    pub max_file_size: Option<String>,
+    /// 在状态目录中用 git 记录 skills 目录的历史，每次同步操作前后自动提交
+    pub git_history: bool,
+    /// 发送匿名使用统计（命令使用次数和错误类别），默认关闭；内容可用 report 查看
+    pub telemetry: bool,
+    /// 上传、下载、解压前后执行的钩子命令
+    pub hooks: HooksConfig,
+    /// 打包时的文件过滤规则
+    pub filter: AssetFilter,
+    /// 打包时改写归档中文本内容的脱敏规则
+    pub redact: Vec<RedactRule>,
+    /// list 表格的列和样式
+    pub list: ListStyle,
+    /// upload --strict 检查的元数据规范
+    pub policy: Policy,
+    /// 下载缓存、同步基准和变更记录的保留策略
+    pub retention: Retention,
+}
+
+impl Config {
+    /// 配置文件路径：SKILLS_SYNC_CONFIG 指定时使用该路径，否则为配置目录下的 config.toml
+    pub fn path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|p| !p.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+        Ok(locations::config_dir()?.join("config.toml"))
+    }
+
+    /// 读取配置文件并应用环境变量覆盖，文件不存在时以默认配置为基础
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        let content = with_env_overrides(&read_config_file(&path)?)?;
+        toml::from_str(&content)
+            .with_context(|| format!("Invalid config file / 配置文件格式错误: {}", path.display()))
+    }
+
+    /// 配置的扫描目录，展开 ~ 和环境变量
+    pub fn scan_dirs(&self) -> Result<Vec<PathBuf>> {
+        self.dirs
+            .iter()
+            .map(|dir| expand_path(dir).with_context(|| format!("Invalid directory in config / 配置中的目录无效: {}", dir)))
+            .collect()
+    }
+}
+
+/// 设置了的覆盖环境变量及其对应的配置项
+///
+/// 空值视为未设置：钩子会把 SKILLS_SYNC_SERVER 等变量传给子进程（没有服务器时为空），
+/// 在钩子中再调用 skills-sync 时不应被空值覆盖配置
+fn env_overrides() -> Vec<(&'static str, &'static str, String)> {
+    ENV_OVERRIDES
+        .iter()
+        .filter_map(|(var, key)| Some((*var, *key, std::env::var(var).ok().filter(|v| !v.is_empty())?)))
+        .collect()
+}
+
+/// 将环境变量覆盖写入配置文件内容，返回新的内容；文件本身的格式错误留给调用方报告
+fn with_env_overrides(content: &str) -> Result<String> {
+    let overrides = env_overrides();
+    if overrides.is_empty() {
+        return Ok(content.to_string());
+    }
+    let mut document: DocumentMut = content.parse().context("Invalid config file / 配置文件格式错误")?;
+    for (var, key, raw) in overrides {
+        let value = if key == "dirs" {
+            std::env::split_paths(&raw)
+                .filter(|p| !p.as_os_str().is_empty())
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect::<toml_edit::Array>()
+                .into()
+        } else {
+            parse_value(&raw)
+        };
+        set_key(&mut document, key, value).with_context(|| format!("Invalid {} / {} 无效", var, var))?;
+        // 逐个校验，出错时指明是哪个环境变量
+        if let Err(e) = toml::from_str::<Config>(&document.to_string()) {
+            if toml::from_str::<Config>(content).is_ok() {
+                return Err(anyhow::anyhow!("Invalid {} / {} 无效: {}", var, var, e.message()));
+            }
+        }
+    }
+    Ok(document.to_string())
+}
+
+/// 按 TOML 解析值（9、true、["md"]），不是合法的 TOML 值时作为字符串
+fn parse_value(raw: &str) -> toml_edit::Value {
+    raw.parse::<toml_edit::Value>().unwrap_or_else(|_| raw.into())
+}
+
+/// 按点分隔的键（如 hooks.post-upload）设置值，缺少的表自动创建
+fn set_key(document: &mut DocumentMut, key: &str, value: toml_edit::Value) -> Result<()> {
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(anyhow::anyhow!("Invalid config key / 无效的配置项: {}", key));
+    }
+    let (last, parents) = segments.split_last().expect("split always yields a segment");
+    let mut table = document.as_table_mut();
+    for segment in parents {
+        table = table
+            .entry(segment)
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .with_context(|| format!("Config key is not a table / 配置项不是表: {}", segment))?;
+    }
+    table.insert(last, toml_edit::value(value));
+    Ok(())
+}
+
+/// 读取配置文件内容，文件不存在时为空
+fn read_config_file(path: &Path) -> Result<String> {
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    fs::read_to_string(path).context("Failed to read config file / 读取配置文件失败")
+}
+
+/// 将配置文件内容解析为生效的配置，再转换为 TOML 值（包含默认值，未设置的可选项不出现）
+///
+/// 未知的键在解析时被忽略，不会出现在结果中
+fn effective_values(content: &str, path: &Path) -> Result<toml::Table> {
+    let config: Config = toml::from_str(content)
+        .with_context(|| format!("Invalid config file / 配置文件格式错误: {}", path.display()))?;
+    toml::Table::try_from(config).context("Failed to serialize config / 序列化配置失败")
+}
+
+/// 按点分隔的键（如 hooks.post-upload）查找值
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let mut segments = key.split('.');
+    let mut value = table.get(segments.next()?)?;
+    for segment in segments {
+        value = value.as_table()?.get(segment)?;
+    }
+    Some(value)
+}
+
+/// 字符串不带引号输出，其他值按 TOML 语法输出
+fn display_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 将嵌套的表展开为 (a.b, value) 形式的键值对
+fn flatten(prefix: &str, table: &toml::Table, entries: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::Table(inner) => flatten(&key, inner, entries),
+            value => entries.push((key, value.to_string())),
+        }
+    }
+}
+
+/// 输出一个配置项的生效值（包括环境变量覆盖）
+pub fn execute_config_get(key: &str) -> Result<()> {
+    let path = Config::path()?;
+    let values = effective_values(&with_env_overrides(&read_config_file(&path)?)?, &path)?;
+    let value = lookup(&values, key).with_context(|| format!("Config key not set / 配置项未设置: {}", key))?;
+    println!("{}", display_value(value));
+    Ok(())
+}
+
+/// 修改配置文件中的一项，保留其余内容和注释
+///
+/// 值按 TOML 解析（9、true、["md"]），不是合法的 TOML 值时作为字符串保存；写入前校验键名和类型。
+/// 只校验和输出文件中的值，不受环境变量覆盖的影响
+pub fn execute_config_set(key: &str, raw: &str) -> Result<()> {
+    let path = Config::path()?;
+    let mut document: DocumentMut = read_config_file(&path)?
+        .parse()
+        .with_context(|| format!("Invalid config file / 配置文件格式错误: {}", path.display()))?;
+
+    set_key(&mut document, key, parse_value(raw))?;
+
+    let content = document.to_string();
+    let values = effective_values(&content, &path)
+        .map_err(|e| anyhow::anyhow!("Invalid value for / 配置值无效: {}: {:#}", key, e))?;
+    let value = lookup(&values, key).with_context(|| format!("Unknown config key / 未知的配置项: {}", key))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("Failed to create config directory / 创建配置目录失败")?;
+    }
+    fs::write(&path, content).context("Failed to write config file / 写入配置文件失败")?;
+    println!("✅ {} = {}", key, value);
+    Ok(())
+}
+
+/// 列出所有生效的配置项（包括默认值），标出被环境变量覆盖的项
+pub fn execute_config_list() -> Result<()> {
+    let path = Config::path()?;
+    let values = effective_values(&with_env_overrides(&read_config_file(&path)?)?, &path)?;
+    let overrides = env_overrides();
+    println!("# {}", path.display());
+    let mut entries = Vec::new();
+    flatten("", &values, &mut entries);
+    for (key, value) in entries {
+        match overrides.iter().find(|(_, k, _)| *k == key) {
+            Some((var, _, _)) => println!("{} = {}  # {}", key, value, var),
+            None => println!("{} = {}", key, value),
+        }
+    }
+    Ok(())
+}
+
+/// 用 $VISUAL / $EDITOR 打开文件（默认 vi，Windows 上为 notepad），等待编辑器退出
+pub(crate) fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    // 编辑器命令可以带参数（如 code --wait），交给 shell 执行，文件路径作为单独的参数传入
+    let mut cmd = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(format!("{} \"{}\"", editor, path.display()));
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c").arg(format!("{} \"$1\"", editor)).arg("sh").arg(path);
+        c
+    };
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run editor / 启动编辑器失败: {}", editor))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("Editor exited with {} / 编辑器异常退出", status));
+    }
+    Ok(())
+}
+
+/// 用 $VISUAL / $EDITOR 打开配置文件，保存后校验格式
+pub fn execute_config_edit() -> Result<()> {
+    let path = Config::path()?;
+    if !path.exists() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create config directory / 创建配置目录失败")?;
+        }
+        fs::write(&path, "").context("Failed to write config file / 写入配置文件失败")?;
+    }
+
+    open_in_editor(&path)?;
+
+    effective_values(&read_config_file(&path)?, &path).map_err(|e| anyhow::anyhow!("{:#}", e))?;
+    println!("✅ Config saved / 配置已保存: {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_reads_nested_effective_values() {
+        let values = effective_values(
+            "server = \"https://a\"\nunknown = 1\n[hooks]\npost-upload = \"echo hi\"\n",
+            Path::new("config.toml"),
+        )
+        .unwrap();
+        assert_eq!(lookup(&values, "server").map(display_value), Some("https://a".to_string()));
+        assert_eq!(lookup(&values, "hooks.post-upload").map(display_value), Some("echo hi".to_string()));
+        assert_eq!(lookup(&values, "notify").map(display_value), Some("false".to_string()));
+        assert!(lookup(&values, "unknown").is_none());
+        assert!(lookup(&values, "token").is_none());
+    }
+
+    #[test]
+    fn rejects_values_of_the_wrong_type() {
+        assert!(effective_values("notify = \"yes\"", Path::new("config.toml")).is_err());
+    }
+}
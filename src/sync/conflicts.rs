@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use comfy_table::{presets::UTF8_FULL, ContentArrangement, Table};
+use sha2::{Digest, Sha256};
+use std::fs;
+
+use crate::sync::client::{fetch_archive, fetch_base_chain, read_remote_files, resolve_server};
+use crate::sync::manifest::Manifest;
+use crate::sync::paths::local_path;
+use crate::sync::state::SyncState;
+use crate::sync::temp::TempArchive;
+
+/// 本地与远端的差异类型
+enum Divergence {
+    /// 本地不存在
+    MissingLocally,
+    /// 只有远端在上次同步后变化
+    RemoteNewer,
+    /// 只有本地在上次同步后变化
+    LocalNewer,
+    /// 双方都在上次同步后变化
+    BothChanged,
+    /// 没有同步记录，无法判断哪一方更新
+    Unknown,
+}
+
+impl Divergence {
+    fn label(&self) -> &'static str {
+        match self {
+            Divergence::MissingLocally => "missing locally / 本地缺失",
+            Divergence::RemoteNewer => "remote newer / 远端较新",
+            Divergence::LocalNewer => "local newer / 本地较新",
+            Divergence::BothChanged => "both changed / 双方均有修改",
+            Divergence::Unknown => "differs / 不一致",
+        }
+    }
+}
+
+/// 统计两份文本之间新增和删除的行数
+fn diff_stats(local: &[u8], remote: &[u8]) -> String {
+    match (std::str::from_utf8(local), std::str::from_utf8(remote)) {
+        (Ok(local), Ok(remote)) => {
+            let patch = diffy::create_patch(local, remote);
+            let (mut added, mut removed) = (0, 0);
+            for hunk in patch.hunks() {
+                for line in hunk.lines() {
+                    match line {
+                        diffy::Line::Insert(_) => added += 1,
+                        diffy::Line::Delete(_) => removed += 1,
+                        diffy::Line::Context(_) => {}
+                    }
+                }
+            }
+            format!("+{} -{} lines (remote vs local) / 行（远端相对本地）", added, removed)
+        }
+        _ => "binary / 二进制".to_string(),
+    }
+}
+
+/// 执行冲突报告命令：列出本地与远端不一致的 skills，不修改任何文件
+pub async fn execute_conflicts(code: String, server: Option<String>, jobs: usize, no_cache: bool) -> Result<()> {
+    let server_url = resolve_server(server).await?;
+
+    // 只读命令，下载的归档（含基础链）用完由守卫删除
+    let archive = TempArchive::new("skills_conflicts", "download");
+    let zip_path = archive.path();
+
+    fetch_archive(&code, &server_url, zip_path, jobs, no_cache).await?;
+    let manifest = Manifest::read_from_archive(zip_path)?.unwrap_or_default();
+    let base_archives = fetch_base_chain(&manifest, zip_path, &server_url, jobs, no_cache).await?;
+    let remote_files = read_remote_files(zip_path, &manifest, &base_archives, None)?;
+    drop(archive);
+
+    let home_dir = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
+    let state = SyncState::load()?;
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["Path / 路径", "Status / 状态", "Changes / 差异"]);
+
+    let mut differing = 0;
+    for (path, remote) in &remote_files {
+        let local = fs::read(local_path(&home_dir, path)?).ok();
+        if local.as_deref() == Some(remote.as_slice()) {
+            continue;
+        }
+        differing += 1;
+
+        let (status, changes) = match &local {
+            None => (Divergence::MissingLocally, format!("+{} bytes / 字节", remote.len())),
+            Some(local) => {
+                let local_sha256 = format!("{:x}", Sha256::digest(local));
+                let remote_sha256 = format!("{:x}", Sha256::digest(remote));
+                let status = match state.synced.get(path) {
+                    Some(base) if *base == local_sha256 => Divergence::RemoteNewer,
+                    Some(base) if *base == remote_sha256 => Divergence::LocalNewer,
+                    Some(_) => Divergence::BothChanged,
+                    None => Divergence::Unknown,
+                };
+                (status, diff_stats(local, remote))
+            }
+        };
+
+        table.add_row(vec![format!("~/{}", path), status.label().to_string(), changes]);
+    }
+
+    if differing == 0 {
+        println!(
+            "✅ All {} remote files match local copies / 全部 {} 个远端文件与本地一致",
+            remote_files.len(),
+            remote_files.len()
+        );
+        return Ok(());
+    }
+
+    println!("{table}");
+    println!(
+        "⚠️  {} of {} files differ / {} 个文件中有 {} 个不一致",
+        differing,
+        remote_files.len(),
+        remote_files.len(),
+        differing
+    );
+
+    Ok(())
+}
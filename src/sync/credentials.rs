@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use std::io::{BufRead, IsTerminal};
+
+/// 密钥环中的服务名
+const SERVICE: &str = "skills-sync";
+
+/// 未指定配置名时使用的默认配置名，服务器没有单独保存令牌时也使用它
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 配置名：服务器地址（去掉末尾的 /）或自定义名称，未指定时为 default
+fn profile_name(profile: Option<String>) -> String {
+    profile
+        .map(|p| p.trim_end_matches('/').to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+fn entry(profile: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, profile)
+        .map_err(|e| anyhow::anyhow!("Failed to open the OS keyring / 无法打开系统密钥环: {}", e))
+}
+
+/// 在独立线程中访问密钥环：Linux 的 Secret Service 后端会自行创建 tokio 运行时，不能在异步上下文中直接调用
+fn off_runtime<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|scope| scope.spawn(f).join().expect("keyring thread panicked"))
+}
+
+/// 读取服务器对应的令牌，没有时读取 default 配置的令牌；密钥环不可用时返回 None
+pub fn stored_token(server: Option<&str>) -> Option<String> {
+    let mut profiles = server.map(|s| profile_name(Some(s.to_string()))).into_iter().collect::<Vec<_>>();
+    profiles.push(DEFAULT_PROFILE.to_string());
+    off_runtime(|| {
+        profiles
+            .iter()
+            .find_map(|profile| entry(profile).ok()?.get_password().ok())
+            .filter(|t| !t.is_empty())
+    })
+}
+
+/// 读取要保存的令牌：终端中不回显输入，否则从标准输入读取一行（便于脚本传入）
+fn read_token() -> Result<String> {
+    let token = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password("🔑 API token / API 令牌: ").context("Failed to read token / 读取令牌失败")?
+    } else {
+        let mut line = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .context("Failed to read token / 读取令牌失败")?;
+        line
+    };
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err(anyhow::anyhow!("Token must not be empty / 令牌不能为空"));
+    }
+    Ok(token)
+}
+
+/// 将令牌保存到系统密钥环
+pub fn execute_credentials_set(profile: Option<String>) -> Result<()> {
+    let profile = profile_name(profile);
+    let token = read_token()?;
+    off_runtime(|| entry(&profile)?.set_password(&token).map_err(anyhow::Error::from))
+        .map_err(|e| anyhow::anyhow!("Failed to save token to the OS keyring / 无法将令牌保存到系统密钥环: {}", e))?;
+    println!("✅ Token saved to the OS keyring / 令牌已保存到系统密钥环: {}", profile);
+    Ok(())
+}
+
+/// 查看密钥环中是否保存了令牌，--show 时输出完整令牌
+pub fn execute_credentials_get(profile: Option<String>, show: bool) -> Result<()> {
+    let profile = profile_name(profile);
+    match off_runtime(|| entry(&profile).map(|e| e.get_password()))? {
+        Ok(token) if show => println!("{}", token),
+        Ok(token) => {
+            let prefix: String = token.chars().take(4).collect();
+            println!("🔑 {}: {}… ({} chars / 字符)", profile, prefix, token.chars().count());
+        }
+        Err(keyring::Error::NoEntry) => {
+            return Err(anyhow::anyhow!("No token stored for / 未保存令牌: {}", profile));
+        }
+        Err(e) => return Err(anyhow::anyhow!("Failed to read the OS keyring / 无法读取系统密钥环: {}", e)),
+    }
+    Ok(())
+}
+
+/// 从系统密钥环删除令牌
+pub fn execute_credentials_delete(profile: Option<String>) -> Result<()> {
+    let profile = profile_name(profile);
+    match off_runtime(|| entry(&profile).map(|e| e.delete_credential()))? {
+        Ok(()) => println!("🗑️  Token removed from the OS keyring / 已从系统密钥环删除令牌: {}", profile),
+        Err(keyring::Error::NoEntry) => println!("ℹ️  No token stored for / 未保存令牌: {}", profile),
+        Err(e) => return Err(anyhow::anyhow!("Failed to update the OS keyring / 无法修改系统密钥环: {}", e)),
+    }
+    Ok(())
+}
@@ -0,0 +1,170 @@
+//! 上传请求体的传输压缩（--upload-encoding）：整个 multipart 请求体用 zstd 或 gzip 压缩后发送，
+//! 并带上 Content-Encoding 头；只在服务器通过 /sync/version 公布支持时使用
+//!
+//! 对未压缩的 tar 归档、以仅存储方式打包的 zip 和压缩率低的资源文件效果明显；
+//! 压缩后没有变小时按原样发送
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::sync::OnceLock;
+
+use crate::sync::api;
+use crate::sync::summary;
+
+static UPLOAD_ENCODING: OnceLock<UploadEncoding> = OnceLock::new();
+
+/// 上传请求体的压缩方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadEncoding {
+    /// 不压缩 / No compression
+    #[default]
+    None,
+    /// 使用服务器支持的压缩方式（优先 zstd）/ Whatever the server supports, preferring zstd
+    Auto,
+    /// zstd
+    Zstd,
+    /// gzip
+    Gzip,
+}
+
+impl UploadEncoding {
+    /// Content-Encoding 的值
+    pub fn header_value(self) -> &'static str {
+        match self {
+            UploadEncoding::Zstd => "zstd",
+            UploadEncoding::Gzip => "gzip",
+            UploadEncoding::None | UploadEncoding::Auto => "identity",
+        }
+    }
+
+    /// 服务器公布支持该压缩方式时使用的功能名
+    fn capability(self) -> String {
+        format!("upload-{}", self.header_value())
+    }
+}
+
+/// 设置上传请求体的压缩方式
+pub fn init(encoding: UploadEncoding) {
+    let _ = UPLOAD_ENCODING.set(encoding);
+}
+
+/// 对该服务器实际使用的压缩方式：服务器没有公布支持时不压缩
+pub fn negotiate(server_url: &str) -> Option<UploadEncoding> {
+    let requested = UPLOAD_ENCODING.get().copied().unwrap_or_default();
+    let candidates: &[UploadEncoding] = match requested {
+        UploadEncoding::None => return None,
+        UploadEncoding::Auto => &[UploadEncoding::Zstd, UploadEncoding::Gzip],
+        UploadEncoding::Zstd => &[UploadEncoding::Zstd],
+        UploadEncoding::Gzip => &[UploadEncoding::Gzip],
+    };
+    let chosen = candidates
+        .iter()
+        .copied()
+        .find(|encoding| api::advertises(server_url, &encoding.capability()));
+    if chosen.is_none() && requested != UploadEncoding::Auto {
+        summary::warn(&format!(
+            "Server does not accept {} request bodies, uploading uncompressed / 服务器不接受 {} 压缩的请求体，按原样上传",
+            requested.header_value(),
+            requested.header_value()
+        ));
+    }
+    chosen
+}
+
+/// 手工构造的 multipart/form-data 请求体（reqwest 的 Form 无法取得字节以便整体压缩）：
+/// 文件内容前后的部分，文件字段名为 file，其后是各文本字段
+pub struct Multipart {
+    pub content_type: String,
+    head: Vec<u8>,
+    tail: Vec<u8>,
+}
+
+impl Multipart {
+    pub fn new(fields: &[(&str, &str)], file_name: &str, mime: &str) -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let boundary = format!("skills-sync-{:032x}", nanos);
+        let head = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary, file_name, mime
+        );
+        let mut tail = String::from("\r\n");
+        for (name, value) in fields {
+            tail.push_str(&format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, name, value));
+        }
+        tail.push_str(&format!("--{}--\r\n", boundary));
+        Multipart {
+            content_type: format!("multipart/form-data; boundary={}", boundary),
+            head: head.into_bytes(),
+            tail: tail.into_bytes(),
+        }
+    }
+
+    /// 将完整的请求体（文件内容从 content 读取）压缩写入 out，逐块处理不整体读入内存；返回压缩前的长度
+    pub fn compress(&self, encoding: UploadEncoding, content: &mut impl Read, out: impl Write) -> Result<u64> {
+        let length = match encoding {
+            UploadEncoding::Zstd => {
+                let mut encoder = zstd::Encoder::new(out, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+                let length = self.write(content, &mut encoder)?;
+                encoder.finish()?.flush()?;
+                length
+            }
+            UploadEncoding::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(out, flate2::Compression::default());
+                let length = self.write(content, &mut encoder)?;
+                encoder.finish()?.flush()?;
+                length
+            }
+            UploadEncoding::None | UploadEncoding::Auto => {
+                let mut out = out;
+                let length = self.write(content, &mut out)?;
+                out.flush()?;
+                length
+            }
+        };
+        Ok(length)
+    }
+
+    fn write(&self, content: &mut impl Read, writer: &mut impl Write) -> Result<u64> {
+        writer.write_all(&self.head)?;
+        let copied = std::io::copy(content, writer)?;
+        writer.write_all(&self.tail)?;
+        Ok(self.head.len() as u64 + copied + self.tail.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compresses_multipart_bodies() {
+        let content = vec![b'a'; 4096];
+        let multipart = Multipart::new(&[("code", "ABC123")], "skills.tar", "application/x-tar");
+        let boundary = multipart.content_type.strip_prefix("multipart/form-data; boundary=").unwrap();
+
+        let mut body = Vec::new();
+        let length = multipart.compress(UploadEncoding::None, &mut content.as_slice(), &mut body).unwrap();
+        assert_eq!(length, body.len() as u64);
+        let text = String::from_utf8_lossy(&body);
+        assert!(text.starts_with(&format!("--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"skills.tar\"", boundary)));
+        assert!(text.contains("name=\"code\"\r\n\r\nABC123\r\n"));
+        assert!(text.ends_with(&format!("--{}--\r\n", boundary)));
+
+        let mut zstd = Vec::new();
+        assert_eq!(multipart.compress(UploadEncoding::Zstd, &mut content.as_slice(), &mut zstd).unwrap(), length);
+        assert!(zstd.len() < body.len());
+        assert_eq!(zstd::decode_all(zstd.as_slice()).unwrap(), body);
+
+        let mut gzip = Vec::new();
+        multipart.compress(UploadEncoding::Gzip, &mut content.as_slice(), &mut gzip).unwrap();
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(gzip.as_slice()).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, body);
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::sync::client::collect_skills;
+
+/// 插件导出参数
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// 输出目录
+    pub out: PathBuf,
+    /// 插件名称
+    pub name: String,
+    /// 插件版本
+    pub version: String,
+    /// 插件描述
+    pub description: Option<String>,
+    /// 作者
+    pub author: Option<String>,
+    /// 只导出这些 skills（为空时导出全部）
+    pub skills: Vec<String>,
+    /// 同时生成 marketplace 清单
+    pub marketplace: bool,
+    /// 同时导出标记为私有（sync: false / private: true）的 skill
+    pub include_private: bool,
+}
+
+/// 复制整个 skill 目录（SKILL.md 及其附带的文件）
+fn copy_skill_dir(src: &Path, dest: &Path) -> Result<()> {
+    for entry in WalkDir::new(src).into_iter().filter_map(|e| e.ok()) {
+        let relative = entry.path().strip_prefix(src)?;
+        let target = dest.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy / 复制失败: {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// 执行导出命令：将 skills 打包为 Claude Code 插件结构
+///
+/// 插件结构：
+///   - .claude-plugin/plugin.json
+///   - skills/<name>/SKILL.md
+///
+/// 生成 marketplace 时插件放在 plugins/<name>/ 下，并在输出目录写入 .claude-plugin/marketplace.json
+pub fn execute_export(dirs: Vec<PathBuf>, options: ExportOptions) -> Result<()> {
+    let wanted: HashSet<&str> = options.skills.iter().map(String::as_str).collect();
+
+    let plugin_dir = if options.marketplace {
+        options.out.join("plugins").join(&options.name)
+    } else {
+        options.out.clone()
+    };
+    let skills_dir = plugin_dir.join("skills");
+
+    let mut exported = Vec::new();
+    for (source, skills) in collect_skills(dirs)? {
+        for skill in skills {
+            if !wanted.is_empty() && !wanted.contains(skill.name.as_str()) {
+                continue;
+            }
+            if skill.private && !options.include_private {
+                println!("🔒 Private skill skipped (use --include-private) / 跳过私有 skill: {}", skill.name);
+                continue;
+            }
+            // 不同来源目录中的同名 skill 只导出第一个
+            if exported.contains(&skill.name) {
+                println!(
+                    "⚠️  Duplicate skill skipped / 跳过重复的 skill: {} ({})",
+                    skill.name, source
+                );
+                continue;
+            }
+
+            let src = skill.file.parent().context("Invalid skill path / 无效的 skill 路径")?;
+            copy_skill_dir(src, &skills_dir.join(&skill.name))?;
+
+            println!("  📄 {} -> skills/{}", skill.path, skill.name);
+            exported.push(skill.name);
+        }
+    }
+
+    let missing: Vec<_> = options
+        .skills
+        .iter()
+        .filter(|name| !exported.contains(name))
+        .collect();
+    if !missing.is_empty() {
+        println!("⚠️  Skills not found / 未找到的 skills: {:?}", missing);
+    }
+    if exported.is_empty() {
+        println!("❌ No skills to export / 没有可导出的 skills");
+        return Ok(());
+    }
+
+    let description = options
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("{} skills exported by skills-sync", exported.len()));
+
+    let mut plugin = json!({
+        "name": options.name,
+        "version": options.version,
+        "description": description,
+    });
+    if let Some(author) = &options.author {
+        plugin["author"] = json!({ "name": author });
+    }
+    let manifest_dir = plugin_dir.join(".claude-plugin");
+    fs::create_dir_all(&manifest_dir)?;
+    fs::write(manifest_dir.join("plugin.json"), serde_json::to_string_pretty(&plugin)?)
+        .context("Failed to write plugin.json / 写入 plugin.json 失败")?;
+
+    if options.marketplace {
+        let marketplace = json!({
+            "name": options.name,
+            "owner": { "name": options.author.clone().unwrap_or_else(|| options.name.clone()) },
+            "plugins": [{
+                "name": options.name,
+                "source": format!("./plugins/{}", options.name),
+                "description": description,
+                "version": options.version,
+            }]
+        });
+        let marketplace_dir = options.out.join(".claude-plugin");
+        fs::create_dir_all(&marketplace_dir)?;
+        fs::write(
+            marketplace_dir.join("marketplace.json"),
+            serde_json::to_string_pretty(&marketplace)?,
+        )
+        .context("Failed to write marketplace.json / 写入 marketplace.json 失败")?;
+    }
+
+    println!(
+        "✅ Exported {} skills as plugin '{}' / 已导出 {} 个 skills 为插件 '{}': {}",
+        exported.len(),
+        options.name,
+        exported.len(),
+        options.name,
+        options.out.display()
+    );
+
+    Ok(())
+}
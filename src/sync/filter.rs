@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 二进制检测读取的字节数
+const SNIFF_LEN: usize = 8192;
+
+/// 打包时按扩展名、MIME 类型或二进制内容过滤文件（config.toml 中的 [filter] 段）
+///
+/// 作用于整目录打包时 skill 目录中的文件，SKILL.md 本身不受过滤规则影响
+///
+/// 示例：
+/// ```toml
+/// [filter]
+/// exclude-extensions = ["mp4", "sqlite"]
+/// exclude-mime = ["video/*", "application/zip"]
+/// skip-binary = true
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct AssetFilter {
+    /// 只打包这些扩展名的文件（为空时不限制，不区分大小写，不带点）
+    pub include_extensions: Vec<String>,
+    /// 不打包这些扩展名的文件
+    pub exclude_extensions: Vec<String>,
+    /// 不打包这些 MIME 类型的文件（根据文件头识别，支持 video/* 这样的通配）
+    pub exclude_mime: Vec<String>,
+    /// 不打包看起来是二进制的文件
+    pub skip_binary: bool,
+}
+
+impl AssetFilter {
+    /// 文件应被跳过时返回原因
+    pub fn skip_reason(&self, path: &Path, content: &[u8]) -> Option<String> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let listed = |list: &[String]| list.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&extension));
+
+        if !self.include_extensions.is_empty() && !listed(&self.include_extensions) {
+            return Some(format!("extension .{} not included / 扩展名未包含", extension));
+        }
+        if listed(&self.exclude_extensions) {
+            return Some(format!("extension .{} excluded / 扩展名已排除", extension));
+        }
+        if let Some(mime) = sniff_mime(content) {
+            if self
+                .exclude_mime
+                .iter()
+                .any(|m| glob::Pattern::new(m).is_ok_and(|p| p.matches(mime)))
+            {
+                return Some(format!("{} excluded / MIME 类型已排除", mime));
+            }
+        }
+        if self.skip_binary && is_binary(content) {
+            return Some("binary content / 二进制内容".to_string());
+        }
+        None
+    }
+}
+
+/// 根据文件头识别常见的 MIME 类型
+fn sniff_mime(content: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x28\xb5\x2f\xfd", "application/zstd"),
+        (b"SQLite format 3\0", "application/vnd.sqlite3"),
+        (b"\x7fELF", "application/x-executable"),
+        (b"ID3", "audio/mpeg"),
+        (b"OggS", "audio/ogg"),
+        (b"\x1a\x45\xdf\xa3", "video/webm"),
+    ];
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(magic, _)| content.starts_with(magic)) {
+        return Some(mime);
+    }
+    // MP4 / MOV：第 4 字节起为 ftyp box
+    if content.len() >= 12 && &content[4..8] == b"ftyp" {
+        return Some(if &content[8..10] == b"qt" { "video/quicktime" } else { "video/mp4" });
+    }
+    if content.len() >= 12 && content.starts_with(b"RIFF") && &content[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    None
+}
+
+/// 二进制启发式判断：开头部分包含 NUL 字节或不是合法的 UTF-8 文本
+fn is_binary(content: &[u8]) -> bool {
+    let head = &content[..content.len().min(SNIFF_LEN)];
+    if head.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
+        // 截断处可能正好落在多字节字符中间
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_common_signatures() {
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff_mime(b"SQLite format 3\0rest"), Some("application/vnd.sqlite3"));
+        assert_eq!(sniff_mime(b"\0\0\0\x18ftypmp42\0\0"), Some("video/mp4"));
+        assert_eq!(sniff_mime(b"\0\0\0\x14ftypqt  \0\0"), Some("video/quicktime"));
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime(b"# Title"), None);
+        assert_eq!(sniff_mime(b""), None);
+    }
+
+    #[test]
+    fn detects_binary_content() {
+        assert!(!is_binary("中文 text\n".as_bytes()));
+        assert!(is_binary(b"abc\0def"));
+        assert!(is_binary(b"\xff\xfe text"));
+        // 截断在多字节字符中间不算二进制
+        let mut text = "a".repeat(SNIFF_LEN - 1).into_bytes();
+        text.extend_from_slice("中".as_bytes());
+        assert!(!is_binary(&text));
+    }
+
+    #[test]
+    fn skip_reason_applies_rules_in_order() {
+        let filter = AssetFilter {
+            include_extensions: vec!["md".into(), "sh".into(), "png".into()],
+            exclude_extensions: vec![".SH".into()],
+            exclude_mime: vec!["image/*".into()],
+            skip_binary: true,
+        };
+        assert_eq!(filter.skip_reason(Path::new("a/README.md"), b"text"), None);
+        assert!(filter.skip_reason(Path::new("a/data.json"), b"{}").unwrap().contains("not included"));
+        assert!(filter.skip_reason(Path::new("a/run.sh"), b"echo").unwrap().contains("excluded"));
+        assert!(filter
+            .skip_reason(Path::new("a/logo.png"), b"\x89PNG\r\n\x1a\n")
+            .unwrap()
+            .contains("image/png"));
+        assert!(filter.skip_reason(Path::new("a/notes.md"), b"\0\x01").unwrap().contains("binary"));
+        assert_eq!(AssetFilter::default().skip_reason(Path::new("a/x.bin"), b"\0"), None);
+    }
+}
@@ -0,0 +1,161 @@
+//! 可选的 git 历史（配置 git_history = true）：每次锁定 skills 根目录（上传、下载、重命名、卸载等）
+//! 前后各提交一次，用户不必自己管理 git 就能查看历史、blame 和恢复文件
+//!
+//! 仓库放在状态目录下的 git/ 中，通过 --git-dir / --work-tree 使用，不在 skills 目录中创建 .git，
+//! 因此也不会被打包上传。需要系统中安装了 git
+
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use crate::sync::client::resolve_skill_dirs;
+use crate::sync::lock::root_key;
+use crate::sync::state::SyncState;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// 没有历史仓库时的错误信息
+const NO_HISTORY: &str = "No git history yet, set git_history = true in the config / 还没有 git 历史，请在配置中设置 git_history = true";
+
+/// 设置是否记录 git 历史
+pub fn init(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+/// 当前是否记录 git 历史
+pub fn enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
+
+/// 某个 skills 根目录的 git 仓库
+fn repo_dir(root: &Path) -> Result<PathBuf> {
+    let state_path = SyncState::path()?;
+    let dir = state_path.parent().context("Invalid state path / 无效的状态路径")?;
+    Ok(dir.join("git").join(root_key(root)))
+}
+
+/// 以 root 为工作区、repo 为仓库的 git 命令；提交者固定为 skills-sync，不依赖用户的 git 配置
+fn git(repo: &Path, root: &Path) -> Command {
+    let mut command = Command::new("git");
+    command
+        .arg("--git-dir")
+        .arg(repo)
+        .arg("--work-tree")
+        .arg(root)
+        .args(["-c", "user.name=skills-sync", "-c", "user.email=skills-sync@localhost", "-c", "core.autocrlf=false"]);
+    command
+}
+
+/// 运行 git 命令：退出码 0 返回 true，1 返回 false（如 diff --quiet 发现差异），其他退出码以 stderr 作为错误
+fn run(mut command: Command) -> Result<bool> {
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run git / 无法运行 git")?;
+    if !output.status.success() && output.status.code() != Some(1) {
+        return Err(anyhow::anyhow!("{}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.status.success())
+}
+
+/// 提交 skills 根目录的当前内容（没有变化时不提交），首次使用时创建仓库
+fn commit(root: &Path, message: &str) -> Result<()> {
+    let repo = repo_dir(root)?;
+    if !has_history(root)? {
+        fs::create_dir_all(&repo)?;
+        let mut init = git(&repo, root);
+        init.args(["init", "--quiet"]);
+        run(init)?;
+    }
+    let mut add = git(&repo, root);
+    add.args(["add", "--all"]);
+    run(add)?;
+    // diff --cached --quiet 有暂存的变化时退出码为 1
+    let mut diff = git(&repo, root);
+    diff.args(["diff", "--cached", "--quiet"]);
+    if run(diff)? {
+        return Ok(());
+    }
+    let mut commit = git(&repo, root);
+    commit.args(["commit", "--quiet", "--no-verify", "-m", message]);
+    run(commit)?;
+    Ok(())
+}
+
+/// 提交 skills 根目录的当前内容；目录不存在时跳过，失败只打印警告，不影响正在进行的操作
+pub fn snapshot(root: &Path, message: &str) {
+    if !root.is_dir() {
+        return;
+    }
+    if let Err(e) = commit(root, message) {
+        println!("⚠️  Failed to record git history / 记录 git 历史失败 ({}): {:#}", root.display(), e);
+    }
+}
+
+/// 仓库是否已存在（开启 git_history 后至少执行过一次操作）
+fn has_history(root: &Path) -> Result<bool> {
+    Ok(repo_dir(root)?.join("HEAD").exists())
+}
+
+/// 列出涉及 skills 根目录下 path 的提交（快照），从新到旧
+pub(crate) fn list_snapshots(root: &Path, path: &str) -> Result<()> {
+    if !has_history(root)? {
+        return Err(anyhow::anyhow!(NO_HISTORY));
+    }
+    let mut log = git(&repo_dir(root)?, root);
+    log.current_dir(root)
+        .args(["log", "--format=%h  %ad  %s", "--date=format-local:%Y-%m-%d %H:%M", "--", path]);
+    let status = log.status().context("Failed to run git / 无法运行 git")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("git log failed / git log 失败"));
+    }
+    Ok(())
+}
+
+/// 把 skills 根目录下的 path 恢复为提交 snapshot 中的内容（该提交之后新增并已提交的文件会被删除）
+pub(crate) fn restore(root: &Path, path: &str, snapshot: &str) -> Result<()> {
+    if !has_history(root)? {
+        return Err(anyhow::anyhow!(NO_HISTORY));
+    }
+    let mut restore = git(&repo_dir(root)?, root);
+    restore
+        .current_dir(root)
+        .args(["restore", "--worktree", "--source", snapshot, "--", path]);
+    if !run(restore)? {
+        return Err(anyhow::anyhow!("git restore failed / git restore 失败"));
+    }
+    Ok(())
+}
+
+/// 执行 git 命令：在某个 skills 根目录的历史仓库中运行任意 git 子命令（log、show、blame、checkout 等）
+///
+/// 只有一个根目录有历史时可以省略 --dir；返回 git 的退出码
+pub fn execute_git(dirs: Vec<PathBuf>, args: Vec<OsString>) -> Result<i32> {
+    let mut roots = Vec::new();
+    for root in resolve_skill_dirs(dirs)? {
+        if has_history(&root)? {
+            roots.push(root);
+        }
+    }
+    let root = match roots.len() {
+        0 => return Err(anyhow::anyhow!(NO_HISTORY)),
+        1 => roots.remove(0),
+        _ => {
+            let found: Vec<String> = roots.iter().map(|r| format!("  {}", r.display())).collect();
+            return Err(anyhow::anyhow!(
+                "Several directories have git history, choose one with --dir / 多个目录都有 git 历史，请用 --dir 指定:\n{}",
+                found.join("\n")
+            ));
+        }
+    };
+
+    let mut command = git(&repo_dir(&root)?, &root);
+    // 在工作区中运行，使 checkout -- <path> 等命令中的相对路径以 skills 根目录为基准
+    command.current_dir(&root).args(args);
+    let status = command.status().context("Failed to run git / 无法运行 git")?;
+    // 被信号终止时没有退出码
+    Ok(status.code().unwrap_or(1))
+}
@@ -0,0 +1,302 @@
+//! 可选的 gRPC 传输：服务器地址为 grpc://（明文）或 grpcs://（TLS）时使用，接口定义见 proto/skills_sync.proto
+//!
+//! 需要以 `--features grpc` 编译，未启用时使用 gRPC 地址会报错
+
+/// 服务器地址是否使用 gRPC 传输
+pub fn is_grpc_url(server_url: &str) -> bool {
+    server_url.starts_with("grpc://") || server_url.starts_with("grpcs://")
+}
+
+#[cfg(feature = "grpc")]
+pub use transport::{download, info, limits, upload};
+
+#[cfg(not(feature = "grpc"))]
+pub use unavailable::{download, info, limits, upload};
+
+/// 未启用 grpc 特性时的占位实现，调用时报错
+#[cfg(not(feature = "grpc"))]
+mod unavailable {
+    use anyhow::Result;
+    use std::path::Path;
+
+    use crate::sync::client::{CodeInfo, UploadReply, UploadTarget};
+
+    fn error() -> anyhow::Error {
+        anyhow::anyhow!(
+            "gRPC transport is not available in this build (rebuild with --features grpc) / 当前构建不支持 gRPC 传输（请使用 --features grpc 重新编译）"
+        )
+    }
+
+    pub async fn upload(_: &str, _: &Path, _: &str, _: &str, _: UploadTarget<'_>) -> Result<UploadReply> {
+        Err(error())
+    }
+
+    pub async fn download(_: &str, _: &str, _: &Path) -> Result<(String, Option<String>)> {
+        Err(error())
+    }
+
+    pub async fn limits(_: &str) -> Result<Option<u64>> {
+        Err(error())
+    }
+
+    pub async fn info(_: &str, _: &str) -> Result<CodeInfo> {
+        Err(error())
+    }
+}
+
+#[cfg(feature = "grpc")]
+mod transport {
+    use anyhow::{Context, Result};
+    use reqwest::StatusCode;
+    use std::path::Path;
+    use std::time::Duration;
+    use tonic::client::Grpc;
+    use tonic::codegen::http::uri::PathAndQuery;
+    use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+    use tokio::io::AsyncReadExt;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::ReceiverStream;
+    use tonic::{Code, Request, Status};
+    use tonic_prost::ProstCodec;
+
+    use crate::sync::client::{CodeInfo, DownloadWriter, UploadReply, UploadTarget};
+    use crate::sync::http;
+    use crate::sync::pin;
+
+    /// 上传时每个消息携带的数据大小
+    const CHUNK_SIZE: usize = 1024 * 1024;
+
+    /// 单次调用的截止时间
+    const DEADLINE: Duration = Duration::from_secs(30 * 60);
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct UploadChunk {
+        #[prost(string, tag = "1")]
+        file_name: String,
+        #[prost(string, tag = "2")]
+        code: String,
+        #[prost(string, tag = "3")]
+        alias: String,
+        #[prost(string, tag = "4")]
+        owner_token: String,
+        #[prost(bytes = "vec", tag = "5")]
+        data: Vec<u8>,
+        #[prost(string, tag = "6")]
+        sha256: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct UploadReplyMessage {
+        #[prost(string, tag = "1")]
+        code: String,
+        #[prost(string, tag = "2")]
+        owner_token: String,
+        #[prost(string, tag = "3")]
+        alias: String,
+        #[prost(string, tag = "4")]
+        sha256: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct CodeRequest {
+        #[prost(string, tag = "1")]
+        code: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct DownloadChunk {
+        #[prost(string, tag = "1")]
+        sha256: String,
+        #[prost(bytes = "vec", tag = "2")]
+        data: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct InfoReply {
+        #[prost(string, tag = "1")]
+        code: String,
+        #[prost(string, tag = "2")]
+        sha256: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct LimitsRequest {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct LimitsReply {
+        #[prost(uint64, tag = "1")]
+        max_upload_size: u64,
+    }
+
+    fn non_empty(s: String) -> Option<String> {
+        Some(s).filter(|s| !s.is_empty())
+    }
+
+    /// 连接服务器：grpc:// 使用明文 HTTP/2，grpcs:// 使用 TLS
+    async fn connect(server_url: &str) -> Result<Grpc<Channel>> {
+        let (uri, tls) = match server_url.strip_prefix("grpcs://") {
+            Some(rest) => (format!("https://{}", rest), true),
+            None => (server_url.replacen("grpc://", "http://", 1), false),
+        };
+        let mut endpoint = Endpoint::from_shared(uri).context("Invalid gRPC server address / 无效的 gRPC 服务器地址")?;
+        if tls {
+            // tonic 不公开对端证书，无法核对；该主机已记录证书时拒绝连接
+            pin::refuse_if_pinned(endpoint.uri().host().unwrap_or_default(), "grpcs://")?;
+            endpoint = endpoint.tls_config(ClientTlsConfig::new().with_webpki_roots())?;
+        }
+        let channel = endpoint
+            .connect()
+            .await
+            .with_context(|| format!("Failed to connect / 连接失败: {}", server_url))?;
+        let mut grpc = Grpc::new(channel);
+        grpc.ready().await.context("gRPC channel not ready / gRPC 通道未就绪")?;
+        Ok(grpc)
+    }
+
+    /// 为请求设置截止时间和 API 令牌
+    fn request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.set_timeout(DEADLINE);
+        if let Some(value) = http::api_token().and_then(|t| format!("Bearer {}", t).parse().ok()) {
+            request.metadata_mut().insert("authorization", value);
+        }
+        request
+    }
+
+    /// 将 gRPC 状态转换为与 HTTP 传输一致的错误
+    fn status_error(status: Status) -> anyhow::Error {
+        let http_status = match status.code() {
+            Code::Unauthenticated => Some(StatusCode::UNAUTHORIZED),
+            Code::PermissionDenied => Some(StatusCode::FORBIDDEN),
+            Code::ResourceExhausted => Some(StatusCode::TOO_MANY_REQUESTS),
+            _ => None,
+        };
+        if let Some(Err(e)) = http_status.map(http::check_access) {
+            return e;
+        }
+        anyhow::anyhow!("gRPC request failed / gRPC 请求失败: {:?} - {}", status.code(), status.message())
+    }
+
+    /// 从磁盘逐块读取文件并发送，first 为携带元数据的第一个消息
+    async fn send_chunks(path: &Path, first: UploadChunk, tx: &mpsc::Sender<UploadChunk>) -> std::io::Result<()> {
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut first = Some(first);
+        loop {
+            let mut data = Vec::with_capacity(CHUNK_SIZE);
+            let read = (&mut file).take(CHUNK_SIZE as u64).read_to_end(&mut data).await?;
+            if read == 0 && first.is_none() {
+                return Ok(());
+            }
+            let mut chunk = first.take().unwrap_or_default();
+            chunk.data = data;
+            // 接收端关闭说明调用已结束
+            if tx.send(chunk).await.is_err() || read < CHUNK_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
+    /// 以客户端流上传文件，第一个消息携带元数据；文件边读边发，不整体读入内存
+    pub async fn upload(
+        server_url: &str,
+        path: &Path,
+        file_name: &str,
+        sha256: &str,
+        target: UploadTarget<'_>,
+    ) -> Result<UploadReply> {
+        let first = UploadChunk {
+            file_name: file_name.to_string(),
+            sha256: sha256.to_string(),
+            code: target.code.unwrap_or_default().to_string(),
+            alias: target.alias.unwrap_or_default().to_string(),
+            owner_token: target.owner_token.unwrap_or_default().to_string(),
+            data: Vec::new(),
+        };
+
+        let mut grpc = connect(server_url).await?;
+        let (tx, rx) = mpsc::channel(4);
+        let (error_tx, error_rx) = tokio::sync::oneshot::channel();
+        let path = path.to_path_buf();
+        tokio::spawn(async move {
+            if let Err(e) = send_chunks(&path, first, &tx).await {
+                let _ = error_tx.send(e);
+                // 读取失败时不结束流，调用被取消，服务器不会把截断的内容当作完整上传
+                tx.closed().await;
+            }
+        });
+
+        let call = grpc.client_streaming(
+            request(ReceiverStream::new(rx)),
+            PathAndQuery::from_static("/skills_sync.v1.SkillsSync/Upload"),
+            ProstCodec::default(),
+        );
+        let reply: UploadReplyMessage = tokio::select! {
+            reply = call => reply.map_err(status_error)?.into_inner(),
+            Ok(e) = error_rx => return Err(anyhow::Error::new(e).context("Failed to read archive / 读取归档失败")),
+        };
+
+        Ok(UploadReply {
+            code: reply.code,
+            owner_token: non_empty(reply.owner_token),
+            alias: non_empty(reply.alias),
+            sha256: non_empty(reply.sha256.to_lowercase()),
+        })
+    }
+
+    /// 以服务端流下载归档并逐块写入 path，返回内容的 SHA256 和服务器提供的 SHA256
+    pub async fn download(server_url: &str, code: &str, path: &Path) -> Result<(String, Option<String>)> {
+        let mut grpc = connect(server_url).await?;
+        let mut stream = grpc
+            .server_streaming(
+                request(CodeRequest { code: code.to_string() }),
+                PathAndQuery::from_static("/skills_sync.v1.SkillsSync/Download"),
+                ProstCodec::<CodeRequest, DownloadChunk>::default(),
+            )
+            .await
+            .map_err(status_error)?
+            .into_inner();
+
+        let mut writer = DownloadWriter::create(path)?;
+        let mut sha256 = None;
+        while let Some(chunk) = stream.message().await.map_err(status_error)? {
+            if sha256.is_none() {
+                sha256 = non_empty(chunk.sha256.trim().to_lowercase());
+            }
+            writer.write(&chunk.data)?;
+        }
+        Ok((writer.finish()?, sha256))
+    }
+
+    /// 查询服务器的单次上传大小上限，0 表示不限制
+    pub async fn limits(server_url: &str) -> Result<Option<u64>> {
+        let mut grpc = connect(server_url).await?;
+        let reply: LimitsReply = grpc
+            .unary(
+                request(LimitsRequest {}),
+                PathAndQuery::from_static("/skills_sync.v1.SkillsSync/Limits"),
+                ProstCodec::default(),
+            )
+            .await
+            .map_err(status_error)?
+            .into_inner();
+        Ok(Some(reply.max_upload_size).filter(|&size| size > 0))
+    }
+
+    /// 查询业务码（或别名）的实际业务码和当前归档 SHA256
+    pub async fn info(server_url: &str, code: &str) -> Result<CodeInfo> {
+        let mut grpc = connect(server_url).await?;
+        let reply: InfoReply = grpc
+            .unary(
+                request(CodeRequest { code: code.to_string() }),
+                PathAndQuery::from_static("/skills_sync.v1.SkillsSync/Info"),
+                ProstCodec::default(),
+            )
+            .await
+            .map_err(status_error)?
+            .into_inner();
+        Ok(CodeInfo {
+            code: non_empty(reply.code),
+            sha256: non_empty(reply.sha256.to_lowercase()),
+        })
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::sync::merge::load_blob;
+use crate::sync::state::SyncState;
+use crate::sync::summary;
+
+/// 同步事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryEvent {
+    Upload,
+    Download,
+}
+
+/// skill 变更记录中的一条（每个 skill 一个 JSON Lines 文件）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub event: HistoryEvent,
+    /// 上传得到或下载使用的业务码
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// skill 全部文件的内容摘要（按路径排序的 "相对路径 SHA256" 行的 SHA256）
+    pub sha256: String,
+    pub added: usize,
+    pub modified: usize,
+    pub removed: usize,
+    /// 文本文件新增的行数
+    pub insertions: usize,
+    /// 文本文件删除的行数
+    pub deletions: usize,
+}
+
+/// 变更记录目录（与同步状态文件同目录）
+fn history_dir() -> Result<PathBuf> {
+    let state_path = SyncState::path()?;
+    let dir = state_path.parent().context("Invalid state path / 无效的状态路径")?;
+    Ok(dir.join("history"))
+}
+
+/// skill 根目录（原始路径）对应的变更记录文件，/ 编码为 %2F 使其成为单个文件名
+fn history_file(skill_root: &str) -> Result<PathBuf> {
+    Ok(history_dir()?.join(format!("{}.jsonl", skill_root.replace('/', "%2F"))))
+}
+
+/// 路径所属的 skill 根目录：最近的、在 paths 中有 SKILL.md 的上级目录
+fn skill_root<'a>(path: &'a str, paths: &BTreeSet<&str>) -> Option<&'a str> {
+    path.rmatch_indices('/').map(|(i, _)| &path[..i]).find(|dir| {
+        paths.contains(format!("{}/SKILL.md", dir).as_str()) || paths.contains(format!("{}/skill.md", dir).as_str())
+    })
+}
+
+/// 文本内容之间新增和删除的行数，任一方不是 UTF-8 时返回 (0, 0)
+fn line_stats(old: &[u8], new: &[u8]) -> (usize, usize) {
+    let (Ok(old), Ok(new)) = (std::str::from_utf8(old), std::str::from_utf8(new)) else {
+        return (0, 0);
+    };
+    let patch = diffy::create_patch(old, new);
+    let mut stats = (0, 0);
+    for line in patch.hunks().iter().flat_map(|h| h.lines()) {
+        match line {
+            diffy::Line::Insert(_) => stats.0 += 1,
+            diffy::Line::Delete(_) => stats.1 += 1,
+            diffy::Line::Context(_) => {}
+        }
+    }
+    stats
+}
+
+/// 比较两次同步的文件集合（原始路径 -> SHA256），为内容变化的每个 skill 生成一条记录
+///
+/// blob 读取同步基准内容，用于统计行数；读取不到时只统计文件数
+fn changes(
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+    event: HistoryEvent,
+    code: Option<&str>,
+    blob: impl Fn(&str) -> Option<Vec<u8>>,
+) -> Vec<(String, HistoryEntry)> {
+    let paths: BTreeSet<&str> = before.keys().chain(after.keys()).map(String::as_str).collect();
+    let mut entries: BTreeMap<&str, HistoryEntry> = BTreeMap::new();
+    for &path in &paths {
+        let (old, new) = (before.get(path), after.get(path));
+        if old == new {
+            continue;
+        }
+        let Some(root) = skill_root(path, &paths) else {
+            continue;
+        };
+        let entry = entries.entry(root).or_insert_with(|| HistoryEntry {
+            at: chrono::Utc::now(),
+            event,
+            code: code.map(str::to_string),
+            sha256: String::new(),
+            added: 0,
+            modified: 0,
+            removed: 0,
+            insertions: 0,
+            deletions: 0,
+        });
+        let (insertions, deletions) = match (old, new) {
+            (None, Some(_)) => {
+                entry.added += 1;
+                new.and_then(|s| blob(s)).map_or((0, 0), |n| line_stats(b"", &n))
+            }
+            (Some(_), None) => {
+                entry.removed += 1;
+                old.and_then(|s| blob(s)).map_or((0, 0), |o| line_stats(&o, b""))
+            }
+            _ => {
+                entry.modified += 1;
+                match (old.and_then(|s| blob(s)), new.and_then(|s| blob(s))) {
+                    (Some(o), Some(n)) => line_stats(&o, &n),
+                    _ => (0, 0),
+                }
+            }
+        };
+        entry.insertions += insertions;
+        entry.deletions += deletions;
+    }
+
+    entries
+        .into_iter()
+        .map(|(root, mut entry)| {
+            let prefix = format!("{}/", root);
+            let mut hasher = Sha256::new();
+            for (path, sha256) in after.range(prefix.clone()..).take_while(|(p, _)| p.starts_with(&prefix)) {
+                hasher.update(format!("{} {}\n", &path[prefix.len()..], sha256));
+            }
+            entry.sha256 = format!("{:x}", hasher.finalize());
+            (root.to_string(), entry)
+        })
+        .collect()
+}
+
+/// 记录一次同步中内容变化的 skills，追加到各自的变更记录文件；失败时只打印警告，不影响同步
+pub fn record(before: &BTreeMap<String, String>, after: &BTreeMap<String, String>, event: HistoryEvent, code: Option<&str>) {
+    let result = (|| -> Result<()> {
+        let changed = changes(before, after, event, code, load_blob);
+        if changed.is_empty() {
+            return Ok(());
+        }
+        fs::create_dir_all(history_dir()?)?;
+        for (root, entry) in changed {
+            let mut file = OpenOptions::new().create(true).append(true).open(history_file(&root)?)?;
+            writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = result {
+        summary::warn(&format!("Failed to record skill history / 记录 skill 变更失败: {}", e));
+    }
+}
+
+/// 读取 skill 的变更记录（从旧到新），没有记录时返回空列表；无法解析的行被忽略
+pub fn load(skill_root: &str) -> Result<Vec<HistoryEntry>> {
+    let path = history_file(skill_root)?;
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read / 读取失败: {}", path.display())),
+    };
+    Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+/// 只保留最近 keep_last 条、且不早于 cutoff 的记录
+fn retain(entries: &mut Vec<HistoryEntry>, keep_last: Option<usize>, cutoff: Option<chrono::DateTime<chrono::Utc>>) {
+    if let Some(cutoff) = cutoff {
+        entries.retain(|e| e.at >= cutoff);
+    }
+    if let Some(keep) = keep_last {
+        let excess = entries.len().saturating_sub(keep);
+        entries.drain(..excess);
+    }
+}
+
+/// 按保留策略裁剪所有 skill 的变更记录，返回删除的记录条数；dry_run 时只统计不写入
+pub fn trim(keep_last: Option<usize>, cutoff: Option<chrono::DateTime<chrono::Utc>>, dry_run: bool) -> Result<usize> {
+    let dir = history_dir()?;
+    let Ok(files) = fs::read_dir(&dir) else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for file in files.filter_map(|f| f.ok()) {
+        let path = file.path();
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let mut entries: Vec<HistoryEntry> = content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect();
+        let before = entries.len();
+        retain(&mut entries, keep_last, cutoff);
+        if entries.len() == before {
+            continue;
+        }
+        removed += before - entries.len();
+        if dry_run {
+            continue;
+        }
+        if entries.is_empty() {
+            fs::remove_file(&path)?;
+        } else {
+            let lines: Vec<String> = entries.iter().map(serde_json::to_string).collect::<Result<_, _>>()?;
+            fs::write(&path, lines.join("\n") + "\n")?;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_changes_per_skill() {
+        let map = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs.iter().map(|(p, s)| (p.to_string(), s.to_string())).collect()
+        };
+        let before = map(&[
+            (".claude/skills/a/SKILL.md", "a1"),
+            (".claude/skills/a/old.sh", "x"),
+            (".claude/skills/b/SKILL.md", "b1"),
+        ]);
+        let after = map(&[
+            (".claude/skills/a/SKILL.md", "a2"),
+            (".claude/skills/a/scripts/new.sh", "y"),
+            (".claude/skills/b/SKILL.md", "b1"),
+        ]);
+        let blobs = map(&[("a1", "one\ntwo\n"), ("a2", "one\nthree\nfour\n"), ("x", "gone\n"), ("y", "new\n")]);
+        let changed = changes(&before, &after, HistoryEvent::Upload, Some("ABC"), |s| {
+            blobs.get(s).map(|c| c.as_bytes().to_vec())
+        });
+
+        assert_eq!(changed.len(), 1);
+        let (root, entry) = &changed[0];
+        assert_eq!(root, ".claude/skills/a");
+        assert_eq!((entry.added, entry.modified, entry.removed), (1, 1, 1));
+        assert_eq!((entry.insertions, entry.deletions), (3, 2));
+        assert_eq!(entry.code.as_deref(), Some("ABC"));
+        assert_eq!(entry.sha256.len(), 64);
+
+        let now = chrono::Utc::now();
+        let mut entries: Vec<HistoryEntry> = (0..5i64)
+            .map(|days| HistoryEntry {
+                at: now - chrono::Duration::days(days),
+                ..entry.clone()
+            })
+            .rev()
+            .collect();
+        retain(&mut entries, Some(3), Some(now - chrono::Duration::days(3) - chrono::Duration::hours(1)));
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries.last().map(|e| e.at), Some(now));
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::sync::summary;
+
+/// 钩子配置（config.toml 中的 [hooks] 表），值为交给系统 shell 执行的命令
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HooksConfig {
+    pub pre_upload: Option<String>,
+    pub post_upload: Option<String>,
+    pub pre_download: Option<String>,
+    pub post_download: Option<String>,
+    pub pre_extract: Option<String>,
+    pub post_extract: Option<String>,
+}
+
+/// 钩子触发时机
+#[derive(Debug, Clone, Copy)]
+pub enum HookEvent {
+    PreUpload,
+    PostUpload,
+    PreDownload,
+    PostDownload,
+    PreExtract,
+    PostExtract,
+}
+
+impl HookEvent {
+    /// 钩子名称，与配置键一致
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::PreUpload => "pre-upload",
+            HookEvent::PostUpload => "post-upload",
+            HookEvent::PreDownload => "pre-download",
+            HookEvent::PostDownload => "post-download",
+            HookEvent::PreExtract => "pre-extract",
+            HookEvent::PostExtract => "post-extract",
+        }
+    }
+
+    /// pre 钩子失败会中止操作，post 钩子失败只给出警告
+    fn is_pre(&self) -> bool {
+        matches!(
+            self,
+            HookEvent::PreUpload | HookEvent::PreDownload | HookEvent::PreExtract
+        )
+    }
+}
+
+/// 传递给钩子的操作信息，以 SKILLS_SYNC_* 环境变量的形式提供
+#[derive(Debug, Default)]
+pub struct HookContext {
+    /// 业务码（SKILLS_SYNC_CODE）
+    pub code: Option<String>,
+    /// 服务器地址（SKILLS_SYNC_SERVER）
+    pub server: Option<String>,
+    /// 归档路径（SKILLS_SYNC_ARCHIVE）
+    pub archive: Option<PathBuf>,
+    /// 目标目录（SKILLS_SYNC_TARGET）
+    pub target: Option<PathBuf>,
+    /// 涉及的文件列表，每行一个（SKILLS_SYNC_FILES）
+    pub files: Vec<String>,
+}
+
+impl HooksConfig {
+    fn command_for(&self, event: HookEvent) -> Option<&str> {
+        let command = match event {
+            HookEvent::PreUpload => &self.pre_upload,
+            HookEvent::PostUpload => &self.post_upload,
+            HookEvent::PreDownload => &self.pre_download,
+            HookEvent::PostDownload => &self.post_download,
+            HookEvent::PreExtract => &self.pre_extract,
+            HookEvent::PostExtract => &self.post_extract,
+        };
+        command.as_deref().filter(|c| !c.trim().is_empty())
+    }
+
+    /// 执行指定时机的钩子（未配置时不做任何事）
+    pub fn run(&self, event: HookEvent, ctx: &HookContext) -> Result<()> {
+        let Some(command) = self.command_for(event) else {
+            return Ok(());
+        };
+
+        println!("🪝 Running {} hook / 执行 {} 钩子: {}", event.name(), event.name(), command);
+
+        let mut cmd = if cfg!(windows) {
+            let mut c = Command::new("cmd");
+            c.arg("/C").arg(command);
+            c
+        } else {
+            let mut c = Command::new("sh");
+            c.arg("-c").arg(command);
+            c
+        };
+
+        cmd.env("SKILLS_SYNC_HOOK", event.name())
+            .env("SKILLS_SYNC_CODE", ctx.code.as_deref().unwrap_or_default())
+            .env("SKILLS_SYNC_SERVER", ctx.server.as_deref().unwrap_or_default())
+            .env("SKILLS_SYNC_FILES", ctx.files.join("\n"))
+            .env("SKILLS_SYNC_FILE_COUNT", ctx.files.len().to_string());
+        if let Some(archive) = &ctx.archive {
+            cmd.env("SKILLS_SYNC_ARCHIVE", archive);
+        }
+        if let Some(target) = &ctx.target {
+            cmd.env("SKILLS_SYNC_TARGET", target);
+        }
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("Failed to run {} hook / 执行 {} 钩子失败", event.name(), event.name()))?;
+
+        if status.success() {
+            return Ok(());
+        }
+
+        if event.is_pre() {
+            Err(anyhow::anyhow!(
+                "{} hook failed ({}), aborting / {} 钩子失败，操作已中止",
+                event.name(),
+                status,
+                event.name()
+            ))
+        } else {
+            summary::warn(&format!("{} hook failed ({}) / {} 钩子执行失败", event.name(), status, event.name()));
+            Ok(())
+        }
+    }
+}
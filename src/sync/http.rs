@@ -0,0 +1,332 @@
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, RETRY_AFTER};
+use reqwest::{Client, ClientBuilder, RequestBuilder, Response, StatusCode};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::sync::api;
+use crate::sync::pin;
+
+/// 未通过 --token 指定时读取的环境变量
+pub const API_TOKEN_ENV: &str = "SKILLS_SYNC_TOKEN";
+
+/// 被服务器限流（429）时的最大重试次数
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// 单次限流等待的上限，Retry-After 超过该值时直接报错
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(120);
+
+/// 单次下载（一个归档或分卷）的最大大小，HTTP 与 gRPC 传输共用，超出时中止下载
+pub const MAX_DOWNLOAD_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Unix 域套接字服务器地址的前缀，例如 unix:///run/skills-sync.sock
+const UNIX_SCHEME: &str = "unix://";
+
+/// 通过 Unix 域套接字访问服务器时，请求 URL 使用的主机名（只用于生成 Host 头）
+const UNIX_BASE_URL: &str = "http://localhost";
+
+/// 访问同步服务器使用的 API 令牌，启动时设置一次
+static API_TOKEN: OnceLock<String> = OnceLock::new();
+
+/// 服务器地址为 unix:// 时使用的套接字路径，启动时设置一次
+static UNIX_SOCKET: OnceLock<PathBuf> = OnceLock::new();
+
+/// 连接服务器时使用的 IP 协议族和 DNS 覆盖，启动时设置一次
+static NETWORK: OnceLock<NetworkOptions> = OnceLock::new();
+
+/// 是否请求使用 HTTP/3（--http3 或配置 http3 = true）
+static HTTP3_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 探测成功后置为 true，之后访问同步服务器的请求都使用 HTTP/3
+static HTTP3_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// HTTP 下载归档时使用的并发连接数（--connections），服务器支持 Range 时分段并行下载
+static CONNECTIONS: AtomicUsize = AtomicUsize::new(1);
+
+/// 并发连接数上限
+const MAX_CONNECTIONS: usize = 16;
+
+/// 探测 HTTP/3 是否可用的超时时间
+#[cfg(feature = "http3")]
+const HTTP3_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 设置 API 令牌：命令行参数优先，其次为环境变量、系统密钥环，最后为配置文件
+///
+/// 只有前面都未设置时才读取密钥环
+pub fn init_api_token(flag: Option<String>, keyring: impl FnOnce() -> Option<String>, config: Option<String>) {
+    let token = flag
+        .or_else(|| std::env::var(API_TOKEN_ENV).ok())
+        .filter(|t| !t.is_empty())
+        .or_else(keyring)
+        .or(config)
+        .filter(|t| !t.is_empty());
+    if let Some(token) = token {
+        let _ = API_TOKEN.set(token);
+    }
+}
+
+/// 当前使用的 API 令牌，传给插件和子进程
+pub fn api_token() -> Option<&'static str> {
+    API_TOKEN.get().map(String::as_str)
+}
+
+/// 服务器地址为 unix://PATH 时记录套接字路径，并返回发送 HTTP 请求使用的地址；其他地址原样返回
+///
+/// 之后所有访问同步服务器的请求都经由该套接字发送，不需要开放 TCP 端口
+pub fn init_unix_socket(server: Option<String>) -> Result<Option<String>> {
+    let Some(path) = server.as_deref().and_then(|s| s.strip_prefix(UNIX_SCHEME)) else {
+        return Ok(server);
+    };
+    if !cfg!(unix) {
+        return Err(anyhow::anyhow!(
+            "Unix socket servers are only supported on Unix / 仅 Unix 系统支持 Unix 域套接字服务器"
+        ));
+    }
+    if path.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Missing socket path, expected unix:///path/to.sock / 缺少套接字路径，应为 unix:///path/to.sock"
+        ));
+    }
+    let _ = UNIX_SOCKET.set(PathBuf::from(path));
+    Ok(Some(UNIX_BASE_URL.to_string()))
+}
+
+/// 当前使用的 Unix 域套接字路径
+pub fn unix_socket() -> Option<&'static std::path::Path> {
+    UNIX_SOCKET.get().map(PathBuf::as_path)
+}
+
+/// 连接服务器时限定的 IP 协议族
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFamily {
+    V4,
+    V6,
+}
+
+/// 网络选项（--ipv4 / --ipv6 / --resolve）
+#[derive(Debug, Clone, Default)]
+pub struct NetworkOptions {
+    /// 只连接该协议族的地址
+    pub family: Option<IpFamily>,
+    /// 主机名到地址的覆盖，不再经过 DNS 解析
+    pub resolve: Vec<(String, SocketAddr)>,
+}
+
+/// 设置网络选项，之后创建的客户端都会应用
+pub fn init_network(options: NetworkOptions) {
+    let _ = NETWORK.set(options);
+}
+
+/// 应用网络选项：绑定到对应协议族的未指定地址（只会连接同一协议族的地址），并加入 DNS 覆盖
+///
+/// reqwest 的 DNS 覆盖按主机名生效，请求 URL 中的端口优先于覆盖地址中的端口
+pub fn apply_network(builder: ClientBuilder) -> ClientBuilder {
+    let Some(options) = NETWORK.get() else {
+        return builder;
+    };
+    let builder = match options.family {
+        Some(IpFamily::V4) => builder.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        Some(IpFamily::V6) => builder.local_address(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        None => builder,
+    };
+    options
+        .resolve
+        .iter()
+        .fold(builder, |builder, (host, addr)| builder.resolve(host, *addr))
+}
+
+/// 访问同步服务器的 HTTP 客户端构建器：默认附带客户端的 API 版本，设置了 API 令牌时附带 Authorization: Bearer 头，
+/// 服务器地址为 unix:// 时经由该套接字连接
+///
+/// 只用于同步服务器，第三方服务（如 IP 地理位置接口）使用普通客户端，避免泄露令牌
+pub fn client_builder() -> ClientBuilder {
+    let mut headers = HeaderMap::new();
+    headers.insert(api::API_VERSION_HEADER, HeaderValue::from(api::API_VERSION));
+    if let Some(token) = API_TOKEN.get() {
+        if let Ok(mut value) = HeaderValue::from_str(&format!("Bearer {}", token)) {
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+    }
+    // 记录对端证书，供首次信任（TOFU）核对
+    let builder = apply_network(Client::builder().default_headers(headers).tls_info(true));
+    #[cfg(unix)]
+    let builder = match UNIX_SOCKET.get() {
+        Some(path) => builder.unix_socket(path.as_path()),
+        None => builder,
+    };
+    #[cfg(feature = "http3")]
+    let builder = if HTTP3_ACTIVE.load(Ordering::Relaxed) {
+        builder.http3_prior_knowledge()
+    } else {
+        builder
+    };
+    builder
+}
+
+/// 设置下载时的并发连接数（限制在 1 到 MAX_CONNECTIONS 之间）
+pub fn init_connections(connections: usize) {
+    CONNECTIONS.store(connections.clamp(1, MAX_CONNECTIONS), Ordering::Relaxed);
+}
+
+/// 下载时的并发连接数
+pub fn connections() -> usize {
+    CONNECTIONS.load(Ordering::Relaxed)
+}
+
+/// 设置是否请求使用 HTTP/3：命令行参数或配置文件任一开启即可
+pub fn init_http3(requested: bool) {
+    if requested && !cfg!(feature = "http3") {
+        println!(
+            "⚠️  HTTP/3 is not available in this build (rebuild with --features http3), using HTTP/1.1 / 当前构建不支持 HTTP/3（请使用 --features http3 重新编译），使用 HTTP/1.1"
+        );
+        return;
+    }
+    HTTP3_REQUESTED.store(requested, Ordering::Relaxed);
+}
+
+/// 请求了 HTTP/3 时向 https 服务器发送一次探测请求，成功则之后的请求都使用 HTTP/3，否则回退到 HTTP/1.1
+///
+/// 每个进程只探测一次（定时同步不会每轮重复探测）；unix:// 和 grpc 服务器不使用 HTTP/3
+#[cfg(feature = "http3")]
+pub async fn negotiate_http3(server_url: &str) {
+    if !HTTP3_REQUESTED.swap(false, Ordering::Relaxed) || !server_url.starts_with("https://") || UNIX_SOCKET.get().is_some() {
+        return;
+    }
+    match probe_http3(server_url).await {
+        Ok(()) => {
+            HTTP3_ACTIVE.store(true, Ordering::Relaxed);
+            println!("⚡ Using HTTP/3 / 使用 HTTP/3");
+        }
+        Err(e) => println!(
+            "⚠️  HTTP/3 unavailable, falling back to HTTP/1.1 / HTTP/3 不可用，回退到 HTTP/1.1: {:#}",
+            e
+        ),
+    }
+}
+
+/// 未启用 http3 特性时始终使用 HTTP/1.1
+#[cfg(not(feature = "http3"))]
+pub async fn negotiate_http3(_: &str) {}
+
+/// 通过 HTTP/3 请求 /sync/limits，服务器有任何响应即视为可用
+#[cfg(feature = "http3")]
+async fn probe_http3(server_url: &str) -> Result<()> {
+    let client = client_builder()
+        .http3_prior_knowledge()
+        .timeout(HTTP3_PROBE_TIMEOUT)
+        .build()?;
+    client
+        .get(format!("{}/sync/limits", server_url))
+        .version(reqwest::Version::HTTP_3)
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// 协商出 HTTP/3 后，请求需要显式指定版本才会经由 QUIC 发送
+pub fn with_version(request: RequestBuilder) -> RequestBuilder {
+    if HTTP3_ACTIVE.load(Ordering::Relaxed) {
+        request.version(reqwest::Version::HTTP_3)
+    } else {
+        request
+    }
+}
+
+/// 访问同步服务器的 HTTP 客户端；构造失败时报错，而不是退回到不带令牌、套接字和证书记录等设置的默认客户端
+pub fn client() -> Result<Client> {
+    client_builder()
+        .build()
+        .context("Failed to create HTTP client / 创建 HTTP 客户端失败")
+}
+
+/// 将鉴权、配额和限流相关的状态码转换为可读的错误
+pub fn check_access(status: StatusCode) -> Result<()> {
+    match status {
+        StatusCode::UNAUTHORIZED if API_TOKEN.get().is_none() => Err(anyhow::anyhow!(
+            "Server requires an API token: pass --token or set {} / 服务器需要 API 令牌：使用 --token 或设置 {}",
+            API_TOKEN_ENV,
+            API_TOKEN_ENV
+        )),
+        StatusCode::UNAUTHORIZED => Err(anyhow::anyhow!(
+            "API token rejected (revoked or expired?) / API 令牌无效（已吊销或过期？）"
+        )),
+        StatusCode::FORBIDDEN => Err(anyhow::anyhow!(
+            "API token lacks permission or quota exceeded / API 令牌无权限或已超出配额"
+        )),
+        StatusCode::INSUFFICIENT_STORAGE => Err(anyhow::anyhow!(
+            "Storage quota exceeded on the server / 服务器存储配额已用尽"
+        )),
+        StatusCode::TOO_MANY_REQUESTS => Err(anyhow::anyhow!(
+            "Rate limited by the server, try again later / 请求过于频繁，已被服务器限流，请稍后重试"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// 下载大小超过 MAX_DOWNLOAD_SIZE 时报错
+pub fn check_download_size(size: u64) -> Result<()> {
+    if size > MAX_DOWNLOAD_SIZE {
+        return Err(anyhow::anyhow!(
+            "Download larger than {} / 下载内容超过 {}",
+            indicatif::HumanBytes(MAX_DOWNLOAD_SIZE),
+            indicatif::HumanBytes(MAX_DOWNLOAD_SIZE)
+        ));
+    }
+    Ok(())
+}
+
+/// 发送单个请求（不重试）并核对服务器证书，用于 info、limits、version 等查询接口：
+/// 网络错误返回 None（调用方按服务器不支持处理），证书与记录不一致时报错
+pub async fn try_send(request: RequestBuilder) -> Result<Option<Response>> {
+    let Ok(response) = with_version(request).send().await else {
+        return Ok(None);
+    };
+    pin::check(&response)?;
+    Ok(Some(response))
+}
+
+/// 发送请求，被服务器限流（429）时按 Retry-After 等待后重试
+///
+/// build 每次重试都会被调用以重新构造请求（multipart 请求体无法复制），构造失败（如重新打开请求体文件出错）时直接报错；
+/// 服务器证书与首次连接时记录的不一致时报错
+pub async fn send_with_retry<F>(mut build: F) -> Result<Response>
+where
+    F: FnMut() -> Result<RequestBuilder>,
+{
+    let mut attempt = 0;
+    loop {
+        let response = with_version(build()?).send().await?;
+        pin::check(&response)?;
+        if response.status() != StatusCode::TOO_MANY_REQUESTS || attempt >= MAX_RATE_LIMIT_RETRIES {
+            return Ok(response);
+        }
+
+        let wait = retry_after(&response).unwrap_or(Duration::from_secs(2u64.pow(attempt + 1)));
+        if wait > MAX_RETRY_WAIT {
+            return Ok(response);
+        }
+        attempt += 1;
+        println!(
+            "⏳ Rate limited by server, retrying in {}s ({}/{}) / 已被服务器限流，{} 秒后重试",
+            wait.as_secs(),
+            attempt,
+            MAX_RATE_LIMIT_RETRIES,
+            wait.as_secs()
+        );
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// 解析 Retry-After 头（秒数或 HTTP 日期）
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+}
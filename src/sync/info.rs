@@ -0,0 +1,128 @@
+use anyhow::Result;
+use indicatif::HumanBytes;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::sync::client::SkillInfo;
+use crate::sync::history::{self, HistoryEntry, HistoryEvent};
+use crate::sync::parts::file_sha256;
+use crate::sync::paths::original_path;
+use crate::sync::rename::find_skill;
+use crate::sync::skill;
+use crate::sync::state::SyncState;
+
+/// skill 目录的原始路径，作为变更记录的键
+fn skill_root(skill: &SkillInfo) -> Option<String> {
+    skill.file.parent().map(original_path)
+}
+
+/// 显示 skill 的详情，以及 SKILL.md 与上次同步内容是否一致
+pub(crate) fn print_info(source: &str, skill: &SkillInfo) -> Result<()> {
+    let synced = SyncState::load()?.synced.get(&original_path(&skill.file)).cloned();
+    let status = match (synced, file_sha256(&skill.file).ok()) {
+        (None, _) => "never synced / 从未同步",
+        (Some(expected), Some(actual)) if expected == actual => "in sync / 与上次同步一致",
+        _ => "modified since last sync / 上次同步后已修改",
+    };
+
+    println!();
+    println!("  Name / 名称:        {}", skill.name);
+    println!("  Source / 来源:      {}", source);
+    println!("  Path / 路径:        {}", skill.path);
+    println!("  Description / 描述: {}", skill.description);
+    if !skill.tags.is_empty() {
+        println!("  Tags / 标签:        {}", skill.tags.join(", "));
+    }
+    if let Some(version) = &skill.version {
+        println!("  Version / 版本:     {}", version);
+    }
+    let metadata = fs::read_to_string(&skill.file).ok().and_then(|c| skill::parse(&c)).unwrap_or_default();
+    if !metadata.allowed_tools.is_empty() {
+        println!("  Allowed tools / 允许的工具: {}", metadata.allowed_tools.join(", "));
+    }
+    println!("  Size / 大小:        {}", HumanBytes(skill.size));
+    if let Some(modified) = skill.modified {
+        println!("  Modified / 修改时间: {}", modified.format("%Y-%m-%d %H:%M"));
+    }
+    println!("  Sync / 同步状态:    {}", status);
+    let changes = skill_root(skill).map(|root| history::load(&root)).transpose()?.unwrap_or_default();
+    if let Some(last) = changes.last() {
+        println!(
+            "  History / 变更记录: {} change(s), last {} / {} 条，最近一次 {}",
+            changes.len(),
+            last.at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+            changes.len(),
+            last.at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
+/// 一条变更记录的单行摘要
+fn history_line(entry: &HistoryEntry) -> String {
+    let event = match entry.event {
+        HistoryEvent::Upload => "⬆️  upload  ",
+        HistoryEvent::Download => "⬇️  download",
+    };
+    let mut files = Vec::new();
+    for (count, label) in [(entry.added, "added"), (entry.modified, "modified"), (entry.removed, "removed")] {
+        if count > 0 {
+            files.push(format!("{} {}", count, label));
+        }
+    }
+    format!(
+        "{}  {}  {:<10}  {:<8}  {}  +{} -{}",
+        entry.at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+        event,
+        entry.code.as_deref().unwrap_or("-"),
+        &entry.sha256[..entry.sha256.len().min(8)],
+        files.join(", "),
+        entry.insertions,
+        entry.deletions
+    )
+}
+
+/// 执行详情命令：显示一个本地 skill 的详情；history 为 true 时列出每次同步时记录的变更（从新到旧）
+pub fn execute_info(dirs: Vec<PathBuf>, name: String, history: bool) -> Result<()> {
+    let (_, source, skill) = find_skill(dirs, &name)?;
+    print_info(&source, &skill)?;
+    if !history {
+        return Ok(());
+    }
+
+    let changes = skill_root(&skill).map(|root| history::load(&root)).transpose()?.unwrap_or_default();
+    println!();
+    if changes.is_empty() {
+        println!("📜 No changes recorded yet / 尚无变更记录");
+        return Ok(());
+    }
+    println!("📜 History / 变更记录:");
+    for entry in changes.iter().rev() {
+        println!("  {}", history_line(entry));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_history_entries() {
+        let entry = HistoryEntry {
+            at: chrono::Utc::now(),
+            event: HistoryEvent::Download,
+            code: Some("ABC123".to_string()),
+            sha256: "0123456789abcdef".to_string(),
+            added: 1,
+            modified: 2,
+            removed: 0,
+            insertions: 10,
+            deletions: 3,
+        };
+        let line = history_line(&entry);
+        assert!(line.contains("ABC123"));
+        assert!(line.contains("01234567 "));
+        assert!(line.ends_with("1 added, 2 modified  +10 -3"));
+    }
+}
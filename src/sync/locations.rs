@@ -0,0 +1,65 @@
+use anyhow::{Context, Result};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 应用目录名
+const APP_DIR: &str = "skills-sync";
+
+/// 读取 XDG 目录环境变量：按规范只接受绝对路径，设置后在所有平台上生效
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var).map(PathBuf::from).filter(|p| p.is_absolute())
+}
+
+/// 配置目录：$XDG_CONFIG_HOME/skills-sync，未设置时为平台默认位置（Linux ~/.config，macOS ~/Library/Application Support，Windows %APPDATA%）
+pub fn config_dir() -> Result<PathBuf> {
+    let dir = xdg_dir("XDG_CONFIG_HOME")
+        .or_else(dirs::config_dir)
+        .context("Failed to get config directory / 无法获取配置目录")?;
+    Ok(dir.join(APP_DIR))
+}
+
+/// 缓存目录：$XDG_CACHE_HOME/skills-sync，未设置时为平台默认位置（Linux ~/.cache，macOS ~/Library/Caches，Windows %LOCALAPPDATA%）
+pub fn cache_dir() -> Result<PathBuf> {
+    let dir = xdg_dir("XDG_CACHE_HOME")
+        .or_else(dirs::cache_dir)
+        .context("Failed to get cache directory / 无法获取缓存目录")?;
+    Ok(dir.join(APP_DIR))
+}
+
+/// 状态目录（同步状态、锁文件）：$XDG_STATE_HOME/skills-sync，未设置时为平台默认位置
+/// （Linux ~/.local/state，其他平台没有单独的状态目录，使用本地数据目录）
+pub fn state_dir() -> Result<PathBuf> {
+    let dir = xdg_dir("XDG_STATE_HOME")
+        .or_else(dirs::state_dir)
+        .or_else(dirs::data_local_dir)
+        .context("Failed to get state directory / 无法获取状态目录")?;
+    Ok(dir.join(APP_DIR))
+}
+
+/// 临时归档目录：缓存目录下的 tmp，只有当前用户可以访问（不使用共享的系统临时目录）
+pub fn temp_dir() -> Result<PathBuf> {
+    let dir = cache_dir()?.join("tmp");
+    fs::create_dir_all(&dir).context("Failed to create temp directory / 创建临时目录失败")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+            .context("Failed to secure temp directory / 无法设置临时目录权限")?;
+    }
+    Ok(dir)
+}
+
+/// 不可预测的 16 位十六进制字符串，用于临时文件名
+pub fn random_suffix() -> String {
+    // RandomState 的种子来自系统随机数，每个实例都不同
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    hasher.write_u128(nanos);
+    format!("{:016x}", hasher.finish())
+}
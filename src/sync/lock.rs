@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::sync::git;
+use crate::sync::state::SyncState;
+
+/// 持有的 skills 根目录锁，离开作用域时自动释放
+#[derive(Default)]
+pub struct RootLock {
+    /// 已持有的锁文件路径及其句柄
+    held: Vec<(PathBuf, File)>,
+    /// 开启 git 历史时，释放锁前需要再提交一次的根目录及操作
+    tracked: Vec<(PathBuf, String)>,
+}
+
+/// skills 根目录在状态目录中的文件名键（锁文件、git 历史仓库共用）
+pub(crate) fn root_key(root: &Path) -> String {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    root.to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect()
+}
+
+/// 某个 skills 根目录对应的锁文件（放在状态目录下，不污染 skills 目录）
+fn lock_path(root: &Path) -> Result<PathBuf> {
+    let state_path = SyncState::path()?;
+    let dir = state_path.parent().context("Invalid state path / 无效的状态路径")?;
+    Ok(dir.join("locks").join(format!("{}.lock", root_key(root))))
+}
+
+/// 为每个 skills 根目录加锁，防止两个进程同时打包或解压同一目录
+///
+/// 已被其他进程锁定时立即失败，并提示对方的进程号和操作
+pub fn lock_roots(roots: &[PathBuf], operation: &str) -> Result<RootLock> {
+    let mut lock = RootLock::default();
+    lock.extend(roots, operation)?;
+    Ok(lock)
+}
+
+impl RootLock {
+    /// 追加锁定更多根目录（如下载后才从 manifest 得知的解压位置），已持有的跳过
+    pub fn extend(&mut self, roots: &[PathBuf], operation: &str) -> Result<()> {
+        let mut paths = roots
+            .iter()
+            .map(|r| Ok((lock_path(r)?, r)))
+            .collect::<Result<Vec<_>>>()?;
+        // 排序去重：同一进程对同一文件重复加锁也会失败
+        paths.sort();
+        paths.dedup_by(|a, b| a.0 == b.0);
+        paths.retain(|(p, _)| !self.held.iter().any(|(held, _)| held == p));
+
+        for (path, root) in paths {
+            let file = lock_file(&path, operation)?;
+            self.held.push((path, file));
+            if git::enabled() {
+                git::snapshot(root, &format!("before {}", operation));
+                self.tracked.push((root.clone(), operation.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RootLock {
+    /// 释放锁之前提交操作后的状态（锁文件句柄在此之后才关闭）
+    fn drop(&mut self) {
+        for (root, operation) in &self.tracked {
+            git::snapshot(root, &format!("after {}", operation));
+        }
+    }
+}
+
+/// 打开并锁定单个锁文件，写入持有者信息
+fn lock_file(path: &Path, operation: &str) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // 不截断打开，避免在拿到锁之前清掉持有者写入的信息
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .with_context(|| format!("Failed to open lock file / 打开锁文件失败: {}", path.display()))?;
+
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let holder = fs::read_to_string(path).unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "Another skills-sync process is running ({}) / 另一个 skills-sync 进程正在运行 ({})\n   lock file / 锁文件: {}",
+                holder.trim(),
+                holder.trim(),
+                path.display()
+            ));
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(e).with_context(|| format!("Failed to lock / 加锁失败: {}", path.display()));
+        }
+    }
+
+    file.set_len(0)?;
+    write!(file, "pid {}, {}", std::process::id(), operation)?;
+    Ok(file)
+}
@@ -0,0 +1,255 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::sync::archive;
+use crate::sync::paths::{decode_path_utf8, normalize_nfc, PATH_ENCODING_PERCENT};
+
+/// 当前支持的最高 manifest 格式版本
+///
+/// - 2：每个路径对应归档中的一个独立条目
+/// - 3：增量归档（base_code / from_base），或多个路径共享同一个归档条目；旧版客户端无法完整解压，必须拒绝
+pub const MANIFEST_VERSION: u32 = 3;
+
+/// 不使用增量和共享条目时写入的版本，旧版客户端仍可读取
+const MANIFEST_VERSION_PLAIN: u32 = 2;
+
+/// 需要 v3 时写在归档最前面的说明文件；v1 manifest.txt 将其映射到无效路径，使旧版客户端在写入任何文件前报错
+pub const UPGRADE_NOTICE: &str = "UPGRADE-SKILLS-SYNC.txt";
+
+/// v2 manifest 在 zip 中的文件名
+pub const MANIFEST_JSON: &str = "manifest.json";
+
+/// v1 manifest 在 zip 中的文件名（文件名=原始路径，每行一条）
+pub const MANIFEST_TXT: &str = "manifest.txt";
+
+/// 归档清单（manifest.json）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    /// 格式版本，v1 清单读取后为 1
+    pub version: u32,
+    /// 生成归档的工具版本
+    pub tool_version: Option<String>,
+    /// 归档创建时间（设置了 SOURCE_DATE_EPOCH 时为该时间，旧版清单可能没有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 增量归档所基于的上一次上传的业务码，未变化的文件需从该归档（及其基础链）中获取
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_code: Option<String>,
+    /// original_path 的编码方式，旧版清单没有该字段（读取后统一转换为百分号编码）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_encoding: Option<String>,
+    /// 归档中的文件列表（增量归档中包含完整文件集合）
+    pub files: Vec<ManifestEntry>,
+}
+
+/// 归档中单个文件的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// 归档内的文件名，内容相同的文件共享同一个归档条目
+    pub archive_name: String,
+    /// 相对用户目录的原始路径（统一使用正斜杠，非 UTF-8 字节和 "%" 使用百分号编码）
+    pub original_path: String,
+    /// skill 名称（目录名）
+    pub skill_name: Option<String>,
+    /// skill 描述
+    pub description: Option<String>,
+    /// front matter 中的 name（可能与目录名不同，仅 SKILL.md 条目有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// front matter 中的 version（仅 SKILL.md 条目有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// front matter 中的标签（仅 SKILL.md 条目有）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// 来源 agent（claude、codex 等）
+    pub source: Option<String>,
+    /// 文件内容 SHA256
+    pub sha256: Option<String>,
+    /// 文件大小（字节）
+    pub size: Option<u64>,
+    /// 文件修改时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<i64>,
+    /// 文件权限位（仅 Unix）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+    /// 文件未变化、未存入本归档，需从 base_code 对应的归档中获取
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub from_base: bool,
+}
+
+impl Manifest {
+    /// 创建当前版本的空 manifest
+    pub fn new() -> Self {
+        Manifest {
+            version: MANIFEST_VERSION,
+            tool_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            created_at: Some(chrono::Utc::now()),
+            base_code: None,
+            path_encoding: Some(PATH_ENCODING_PERCENT.to_string()),
+            files: Vec::new(),
+        }
+    }
+
+    /// 按归档内文件名查找所有引用该文件的记录（内容去重后一个文件可对应多个原始路径）
+    pub fn entries_for<'a>(&'a self, archive_name: &'a str) -> impl Iterator<Item = &'a ManifestEntry> + 'a {
+        self.files.iter().filter(move |e| e.archive_name == archive_name)
+    }
+
+    /// 写入该清单所需的最低格式版本
+    pub fn required_version(&self) -> u32 {
+        let mut stored = HashSet::new();
+        let shared = self
+            .files
+            .iter()
+            .filter(|e| !e.from_base)
+            .any(|e| !stored.insert(e.archive_name.as_str()));
+        if self.base_code.is_some() || self.files.iter().any(|e| e.from_base) || shared {
+            MANIFEST_VERSION
+        } else {
+            MANIFEST_VERSION_PLAIN
+        }
+    }
+
+    /// 序列化为 manifest.json 内容
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize manifest / 序列化 manifest 失败")
+    }
+
+    /// 生成 v1 manifest.txt 内容，使只读取 manifest.txt 的旧版客户端也能解压归档中存储的文件
+    ///
+    /// 旧版客户端按原样拼接路径，因此写入解码后的路径；无法表示为 UTF-8 的路径不写入。
+    /// v3 清单无法用 v1 格式表示，只写入指向无效路径的 UPGRADE_NOTICE，旧版客户端会报错而不是只解压部分文件
+    pub fn to_v1(&self) -> String {
+        if self.required_version() > MANIFEST_VERSION_PLAIN {
+            return format!("{}=\0requires a newer skills-sync\n", UPGRADE_NOTICE);
+        }
+        let mut out = String::new();
+        for entry in self.files.iter().filter(|e| !e.from_base && !e.archive_name.is_empty()) {
+            if let Some(path) = decode_path_utf8(&entry.original_path) {
+                out.push_str(&format!("{}={}\n", entry.archive_name, path));
+            }
+        }
+        out
+    }
+
+    /// 解析 v1 manifest.txt 内容
+    pub fn from_v1(content: &str) -> Self {
+        let files = content
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(filename, original_path)| ManifestEntry {
+                archive_name: filename.to_string(),
+                original_path: original_path.to_string(),
+                skill_name: None,
+                description: None,
+                name: None,
+                version: None,
+                tags: Vec::new(),
+                source: source_from_path(original_path),
+                sha256: None,
+                size: None,
+                modified: None,
+                mode: None,
+                from_base: false,
+            })
+            .collect();
+
+        let mut manifest = Manifest {
+            version: 1,
+            tool_version: None,
+            created_at: None,
+            base_code: None,
+            path_encoding: None,
+            files,
+        };
+        manifest.normalize_paths();
+        manifest
+    }
+
+    /// 解析 manifest.json 内容，拒绝未知版本的清单
+    pub fn from_json(content: &str) -> Result<Self> {
+        let mut manifest: Manifest =
+            serde_json::from_str(content).context("Invalid manifest.json / manifest.json 格式错误")?;
+        if !(MANIFEST_VERSION_PLAIN..=MANIFEST_VERSION).contains(&manifest.version) {
+            return Err(anyhow::anyhow!(
+                "Unsupported manifest version {} (max {}), please upgrade skills-sync / 不支持的 manifest 版本 {}（最高 {}），请升级 skills-sync",
+                manifest.version,
+                MANIFEST_VERSION,
+                manifest.version,
+                MANIFEST_VERSION
+            ));
+        }
+        manifest.normalize_paths();
+        Ok(manifest)
+    }
+
+    /// 统一原始路径格式：旧版清单中的原始路径未编码，将其中的 "%" 转义，使所有路径都能按百分号编码解码；
+    /// 所有路径规范化为 NFC
+    fn normalize_paths(&mut self) {
+        let escape = self.path_encoding.is_none();
+        for entry in &mut self.files {
+            if escape {
+                entry.original_path = entry.original_path.replace('%', "%25");
+            }
+            // 旧版本打包时未做 Unicode 规范化
+            entry.original_path = normalize_nfc(&entry.original_path);
+        }
+        self.path_encoding = Some(PATH_ENCODING_PERCENT.to_string());
+    }
+
+    /// 从归档中读取 manifest，优先 manifest.json，其次兼容 v1 的 manifest.txt
+    pub fn read_from_archive(path: &Path) -> Result<Option<Self>> {
+        let mut json = None;
+        let mut txt = None;
+        archive::for_each_entry(path, |name, reader| {
+            match name {
+                MANIFEST_JSON => {
+                    let mut content = String::new();
+                    reader.read_to_string(&mut content)?;
+                    json = Some(content);
+                }
+                MANIFEST_TXT => {
+                    let mut content = String::new();
+                    reader.read_to_string(&mut content)?;
+                    txt = Some(content);
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+
+        if let Some(content) = json {
+            return Ok(Some(Manifest::from_json(&content)?));
+        }
+        Ok(txt.map(|content| Manifest::from_v1(&content)))
+    }
+}
+
+impl Default for Manifest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// UPGRADE_NOTICE 的内容
+pub fn upgrade_notice() -> String {
+    format!(
+        "This archive needs skills-sync {} or newer: run `skills-sync download` with an up-to-date client.\n\
+         该归档需要 skills-sync {} 或更新版本：请升级客户端后使用 `skills-sync download` 下载。\n",
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// 从原始路径推断来源 agent，例如 .claude/skills/x/SKILL.md -> claude
+pub fn source_from_path(original_path: &str) -> Option<String> {
+    original_path
+        .split('/')
+        .find(|c| !c.is_empty())
+        .map(|c| c.trim_start_matches('.').to_string())
+        .filter(|c| !c.is_empty())
+}
@@ -0,0 +1,248 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::sync::client::collect_skills;
+use crate::sync::http;
+use crate::sync::paths::expand_path;
+
+/// 默认的 MCP 协议版本（客户端指定版本时沿用客户端的版本）
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC 错误码
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// MCP 服务需要的全局参数，调用上传/下载工具时原样传给子进程
+pub struct McpOptions {
+    pub server: Option<String>,
+    pub jobs: usize,
+}
+
+/// 对外暴露的工具定义
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "list_skills",
+            "description": "List locally installed skills with their name, description, version, source and path",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir": { "type": "string", "description": "Skills directory to scan instead of the defaults" }
+                }
+            }
+        },
+        {
+            "name": "get_skill",
+            "description": "Read the SKILL.md content of a locally installed skill",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Skill name (directory name)" },
+                    "dir": { "type": "string", "description": "Skills directory to scan instead of the defaults" }
+                },
+                "required": ["name"]
+            }
+        },
+        {
+            "name": "upload_skills",
+            "description": "Upload local skills to the sync server and return the business code",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "dir": { "type": "string", "description": "Skills directory to upload instead of the defaults" },
+                    "server": { "type": "string", "description": "Server URL" }
+                }
+            }
+        },
+        {
+            "name": "install_skill",
+            "description": "Download and install skills shared under a business code",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "code": { "type": "string", "description": "Business code" },
+                    "dir": { "type": "string", "description": "Extract target directory" },
+                    "server": { "type": "string", "description": "Server URL" }
+                },
+                "required": ["code"]
+            }
+        }
+    ])
+}
+
+/// 工具调用结果：文本内容 + 是否出错
+fn tool_result(text: String, is_error: bool) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error
+    })
+}
+
+fn string_arg(args: &Value, key: &str) -> Option<String> {
+    args.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// 目录参数与命令行一致：展开 ~ 和环境变量，支持 glob
+fn dirs_arg(args: &Value) -> Result<Vec<PathBuf>> {
+    string_arg(args, "dir").map(|p| expand_path(&p)).into_iter().collect()
+}
+
+/// list_skills：返回 JSON 格式的本地 skills 列表
+fn list_skills(args: &Value) -> Result<Value> {
+    let skills: Vec<Value> = collect_skills(dirs_arg(args)?)?
+        .into_iter()
+        .flat_map(|(source, skills)| {
+            skills.into_iter().map(move |skill| {
+                json!({
+                    "name": skill.name,
+                    "description": skill.description,
+                    "version": skill.version,
+                    "source": source,
+                    "path": skill.path,
+                })
+            })
+        })
+        .collect();
+
+    Ok(tool_result(serde_json::to_string_pretty(&skills)?, false))
+}
+
+/// get_skill：按名称读取 SKILL.md 内容
+fn get_skill(args: &Value) -> Result<Value> {
+    let name = string_arg(args, "name").context("Missing argument: name / 缺少参数: name")?;
+
+    let skill = collect_skills(dirs_arg(args)?)?
+        .into_iter()
+        .flat_map(|(_, skills)| skills)
+        .find(|skill| skill.name == name);
+
+    let Some(skill) = skill else {
+        return Ok(tool_result(format!("Skill not found: {}", name), true));
+    };
+
+    let path = &skill.file;
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read skill / 读取 skill 失败: {}", path.display()))?;
+    Ok(tool_result(content, false))
+}
+
+/// 上传/下载会输出进度信息，为了不污染协议通道（stdout），以子进程方式执行并收集其输出
+///
+/// 工具参数中的 server 优先，未指定时使用启动 MCP 服务时的 --server（只传一次 --server）
+async fn run_self(args: Vec<String>, server: Option<String>, options: &McpOptions) -> Result<Value> {
+    let exe = std::env::current_exe().context("Failed to locate executable / 无法定位可执行文件")?;
+    let mut cmd = tokio::process::Command::new(exe);
+    cmd.arg("--jobs").arg(options.jobs.to_string());
+    if let Some(server) = server.or_else(|| options.server.clone()) {
+        cmd.arg("--server").arg(server);
+    }
+    if let Some(token) = http::api_token() {
+        cmd.env(http::API_TOKEN_ENV, token);
+    }
+    cmd.args(args).stdin(Stdio::null());
+
+    let output = cmd.output().await.context("Failed to run command / 执行命令失败")?;
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok(tool_result(text, !output.status.success()))
+}
+
+async fn upload_skills(args: &Value, options: &McpOptions) -> Result<Value> {
+    let mut cmd_args = vec!["upload".to_string()];
+    if let Some(dir) = string_arg(args, "dir") {
+        cmd_args.extend(["--dir".to_string(), dir]);
+    }
+    run_self(cmd_args, string_arg(args, "server"), options).await
+}
+
+async fn install_skill(args: &Value, options: &McpOptions) -> Result<Value> {
+    let code = string_arg(args, "code").context("Missing argument: code / 缺少参数: code")?;
+    let mut cmd_args = vec!["download".to_string(), "--code".to_string(), code];
+    if let Some(dir) = string_arg(args, "dir") {
+        cmd_args.extend(["--dir".to_string(), dir]);
+    }
+    run_self(cmd_args, string_arg(args, "server"), options).await
+}
+
+/// 处理 tools/call 请求，工具执行失败作为 isError 结果返回而不是协议错误
+async fn call_tool(params: &Value, options: &McpOptions) -> Result<Value, (i64, String)> {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+    let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+
+    let result = match name {
+        "list_skills" => list_skills(&args),
+        "get_skill" => get_skill(&args),
+        "upload_skills" => upload_skills(&args, options).await,
+        "install_skill" => install_skill(&args, options).await,
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+    };
+
+    Ok(result.unwrap_or_else(|e| tool_result(format!("{:#}", e), true)))
+}
+
+/// 处理单个 JSON-RPC 请求，通知（没有 id）不需要响应
+async fn handle_message(message: Value, options: &McpOptions) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": params
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "skills-sync", "version": env!("CARGO_PKG_VERSION") }
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(&params, options).await,
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message }
+        }),
+    })
+}
+
+/// 执行 MCP 命令：通过 stdio 运行 Model Context Protocol 服务（每行一个 JSON-RPC 消息）
+pub async fn execute_mcp(options: McpOptions) -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    eprintln!("🔌 MCP server running on stdio / MCP 服务已在 stdio 上运行");
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(message, &options).await,
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": Value::Null,
+                "error": { "code": PARSE_ERROR, "message": format!("Parse error: {}", e) }
+            })),
+        };
+
+        if let Some(response) = response {
+            let mut out = serde_json::to_vec(&response)?;
+            out.push(b'\n');
+            stdout.write_all(&out).await?;
+            stdout.flush().await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::sync::state::SyncState;
+
+/// 同步基准内容的存储目录（按 SHA256 寻址）
+pub(crate) fn blobs_dir() -> Result<PathBuf> {
+    let state_path = SyncState::path()?;
+    let dir = state_path.parent().context("Invalid state path / 无效的状态路径")?;
+    Ok(dir.join("blobs"))
+}
+
+/// 保存一份同步基准内容，返回其 SHA256
+pub fn store_blob(content: &[u8]) -> Result<String> {
+    let sha256 = format!("{:x}", Sha256::digest(content));
+    let dir = blobs_dir()?;
+    let path = dir.join(&sha256);
+    if !path.exists() {
+        fs::create_dir_all(&dir)?;
+        fs::write(&path, content).context("Failed to save sync base / 保存同步基准失败")?;
+    }
+    Ok(sha256)
+}
+
+/// 读取同步基准内容
+pub fn load_blob(sha256: &str) -> Option<Vec<u8>> {
+    fs::read(blobs_dir().ok()?.join(sha256)).ok()
+}
+
+/// 单个文件的合并结果
+pub enum MergeOutcome {
+    /// 本地不存在或与远端一致，直接写入远端内容
+    TakeRemote,
+    /// 只有本地修改过，保留本地内容
+    KeepLocal,
+    /// 双方都修改过且自动合并成功
+    Merged(Vec<u8>),
+    /// 双方都修改过且存在冲突，内容中带有冲突标记
+    Conflict(Vec<u8>),
+}
+
+/// 以上次同步时的内容为基准，对本地与远端内容做三方合并
+///
+/// 没有记录基准、基准内容丢失或内容不是 UTF-8 文本时退化为直接覆盖
+pub fn three_way_merge(local: Option<&[u8]>, remote: &[u8], base_sha256: Option<&str>) -> MergeOutcome {
+    let Some(local) = local else {
+        return MergeOutcome::TakeRemote;
+    };
+    if local == remote {
+        return MergeOutcome::TakeRemote;
+    }
+
+    let Some(base_sha256) = base_sha256 else {
+        return MergeOutcome::TakeRemote;
+    };
+    let local_sha256 = format!("{:x}", Sha256::digest(local));
+    let remote_sha256 = format!("{:x}", Sha256::digest(remote));
+
+    // 只有远端变化
+    if local_sha256 == base_sha256 {
+        return MergeOutcome::TakeRemote;
+    }
+    // 只有本地变化
+    if remote_sha256 == base_sha256 {
+        return MergeOutcome::KeepLocal;
+    }
+
+    // 双方都变化，需要基准内容进行合并
+    match load_blob(base_sha256) {
+        Some(base) => merge_text(&base, local, remote),
+        None => MergeOutcome::TakeRemote,
+    }
+}
+
+/// 双方都修改过时按行合并，内容不是 UTF-8 文本时采用远端内容
+fn merge_text(base: &[u8], local: &[u8], remote: &[u8]) -> MergeOutcome {
+    let (Ok(base), Ok(ours), Ok(theirs)) = (
+        std::str::from_utf8(base),
+        std::str::from_utf8(local),
+        std::str::from_utf8(remote),
+    ) else {
+        return MergeOutcome::TakeRemote;
+    };
+
+    match diffy::merge(base, ours, theirs) {
+        Ok(merged) => MergeOutcome::Merged(merged.into_bytes()),
+        Err(conflicted) => MergeOutcome::Conflict(conflicted.into_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256(content: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(content))
+    }
+
+    #[test]
+    fn takes_remote_without_local_or_base() {
+        assert!(matches!(three_way_merge(None, b"remote", None), MergeOutcome::TakeRemote));
+        assert!(matches!(three_way_merge(Some(b"local"), b"remote", None), MergeOutcome::TakeRemote));
+        assert!(matches!(three_way_merge(Some(b"same"), b"same", Some("x")), MergeOutcome::TakeRemote));
+    }
+
+    #[test]
+    fn keeps_the_only_changed_side() {
+        let base = sha256(b"base");
+        assert!(matches!(
+            three_way_merge(Some(b"base"), b"remote", Some(&base)),
+            MergeOutcome::TakeRemote
+        ));
+        assert!(matches!(
+            three_way_merge(Some(b"local"), b"base", Some(&base)),
+            MergeOutcome::KeepLocal
+        ));
+    }
+
+    #[test]
+    fn merges_non_overlapping_edits() {
+        let base = b"one\ntwo\nthree\n";
+        let local = b"ONE\ntwo\nthree\n";
+        let remote = b"one\ntwo\nTHREE\n";
+        match merge_text(base, local, remote) {
+            MergeOutcome::Merged(merged) => assert_eq!(merged, b"ONE\ntwo\nTHREE\n"),
+            _ => panic!("expected a clean merge"),
+        }
+    }
+
+    #[test]
+    fn marks_overlapping_edits_as_conflict() {
+        match merge_text(b"one\n", b"local\n", b"remote\n") {
+            MergeOutcome::Conflict(conflicted) => {
+                let text = String::from_utf8(conflicted).unwrap();
+                assert!(text.contains("<<<<<<<") && text.contains("local") && text.contains("remote"));
+            }
+            _ => panic!("expected a conflict"),
+        }
+    }
+
+    #[test]
+    fn binary_content_takes_remote() {
+        assert!(matches!(merge_text(b"base", &[0xff, 0xfe], b"remote"), MergeOutcome::TakeRemote));
+    }
+}
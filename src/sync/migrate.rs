@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sync::archive::{self, ArchiveFormat, ArchiveWriter};
+use crate::sync::config::{Config, CONFIG_ENV};
+use crate::sync::locations;
+use crate::sync::manifest::{Manifest, MANIFEST_JSON, MANIFEST_TXT};
+use crate::sync::state::SyncState;
+
+/// 应用目录名（与 locations 一致）
+const APP_DIR: &str = "skills-sync";
+
+/// 一项迁移：旧位置 -> 新位置
+struct Relocation {
+    what: &'static str,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+/// 旧版本（不读取 XDG 环境变量）使用的位置；与当前位置相同的不需要迁移
+fn legacy_relocations() -> Result<Vec<Relocation>> {
+    let mut relocations = Vec::new();
+    if std::env::var_os(CONFIG_ENV).is_none() {
+        if let Some(dir) = dirs::config_dir() {
+            relocations.push(Relocation {
+                what: "config / 配置",
+                from: dir.join(APP_DIR).join("config.toml"),
+                to: Config::path()?,
+            });
+        }
+    }
+    if let Some(dir) = dirs::state_dir().or_else(dirs::data_local_dir) {
+        let state_dir = locations::state_dir()?;
+        relocations.push(Relocation {
+            what: "sync state / 同步状态",
+            from: dir.join(APP_DIR).join("state.json"),
+            to: state_dir.join("state.json"),
+        });
+        relocations.push(Relocation {
+            what: "sync bases / 同步基准",
+            from: dir.join(APP_DIR).join("blobs"),
+            to: state_dir.join("blobs"),
+        });
+    }
+    if let Some(dir) = dirs::cache_dir() {
+        relocations.push(Relocation {
+            what: "download cache / 下载缓存",
+            from: dir.join(APP_DIR).join("downloads"),
+            to: locations::cache_dir()?.join("downloads"),
+        });
+    }
+    relocations.retain(|r| r.from != r.to && r.from.exists() && !r.to.exists());
+    Ok(relocations)
+}
+
+/// 升级状态文件，返回所做修改的说明
+fn migrate_state(dry_run: bool) -> Result<Vec<String>> {
+    let path = SyncState::path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut state = SyncState::read(&path)?;
+    let changes = state.upgrade();
+    if !changes.is_empty() && !dry_run {
+        state.save()?;
+    }
+    Ok(changes)
+}
+
+/// 将归档中的清单升级为当前格式：v1 manifest.txt 补充 manifest.json，旧版 manifest.json 补充路径编码，
+/// 并补全缺少的 SHA256 和大小；其余条目按原顺序原样保留
+///
+/// 返回升级前后的清单版本，已是当前格式时返回 None
+fn migrate_archive(path: &Path, dry_run: bool) -> Result<Option<(u32, u32)>> {
+    let format = ArchiveFormat::detect(path)?;
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut raw_json = None;
+    archive::for_each_entry(path, |name, reader| {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content)?;
+        if name == MANIFEST_JSON {
+            raw_json = Some(String::from_utf8_lossy(&content).into_owned());
+        }
+        entries.push((name.to_string(), content));
+        Ok(())
+    })?;
+
+    // 旧版 manifest.json 没有 path_encoding 字段，读取后会被补上
+    let legacy_json = raw_json
+        .map(|json| serde_json::from_str::<serde_json::Value>(&json).map(|v| v.get("path_encoding").is_none()))
+        .transpose()
+        .context("Invalid manifest.json / manifest.json 格式错误")?;
+    let Some(mut manifest) = Manifest::read_from_archive(path)? else {
+        return Err(anyhow::anyhow!("No manifest found in archive / 归档中没有 manifest: {}", path.display()));
+    };
+    let from = manifest.version;
+
+    let contents: HashMap<&str, &[u8]> = entries.iter().map(|(n, c)| (n.as_str(), c.as_slice())).collect();
+    let mut filled = false;
+    for entry in manifest.files.iter_mut().filter(|e| !e.from_base) {
+        let Some(content) = contents.get(entry.archive_name.as_str()) else {
+            continue;
+        };
+        if entry.sha256.is_none() {
+            entry.sha256 = Some(format!("{:x}", Sha256::digest(content)));
+            filled = true;
+        }
+        if entry.size.is_none() {
+            entry.size = Some(content.len() as u64);
+            filled = true;
+        }
+    }
+    if legacy_json != Some(true) && from > 1 && !filled {
+        return Ok(None);
+    }
+    manifest.version = manifest.required_version();
+    let to = manifest.version;
+    if dry_run {
+        return Ok(Some((from, to)));
+    }
+
+    manifest.tool_version = Some(env!("CARGO_PKG_VERSION").to_string());
+    let json = manifest.to_json()?;
+    let txt = manifest.to_v1();
+
+    // 先写同目录下的临时文件再替换，中断时原归档不受影响
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("archive");
+    let tmp = path.with_file_name(format!(".{}.{}.migrate", file_name, locations::random_suffix()));
+    let result = (|| {
+        let mut writer = ArchiveWriter::create(&tmp, format, None)?;
+        for (name, content) in entries.iter().filter(|(n, _)| n != MANIFEST_JSON && n != MANIFEST_TXT) {
+            writer.add_file(name, content)?;
+        }
+        writer.add_file(MANIFEST_JSON, json.as_bytes())?;
+        writer.add_file(MANIFEST_TXT, txt.as_bytes())?;
+        writer.finish()?;
+        fs::rename(&tmp, path).context("Failed to replace archive / 替换归档失败")
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+    Ok(Some((from, to)))
+}
+
+/// 执行迁移命令：把旧位置的配置、状态和缓存移到当前位置，升级状态文件和指定归档（保留的归档、备份）的格式
+///
+/// dry_run 时只报告需要迁移的内容
+pub fn execute_migrate(archives: Vec<PathBuf>, dry_run: bool) -> Result<()> {
+    let verb = if dry_run { "Would migrate / 将迁移" } else { "Migrated / 已迁移" };
+    let mut count = 0;
+
+    for relocation in legacy_relocations()? {
+        if !dry_run {
+            if let Some(parent) = relocation.to.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&relocation.from, &relocation.to).with_context(|| {
+                format!(
+                    "Failed to move / 移动失败: {} -> {}",
+                    relocation.from.display(),
+                    relocation.to.display()
+                )
+            })?;
+        }
+        println!(
+            "🔧 {} {}: {} -> {}",
+            verb,
+            relocation.what,
+            relocation.from.display(),
+            relocation.to.display()
+        );
+        count += 1;
+    }
+
+    let changes = migrate_state(dry_run)?;
+    for change in &changes {
+        println!("🔧 {} sync state / 同步状态: {}", verb, change);
+    }
+    if !changes.is_empty() {
+        count += 1;
+    }
+
+    for path in &archives {
+        if let Some((from, to)) = migrate_archive(path, dry_run)? {
+            println!("🔧 {} archive / 归档: {} (manifest v{} -> v{})", verb, path.display(), from, to);
+            count += 1;
+        } else {
+            println!("✔️  Up to date / 已是最新格式: {}", path.display());
+        }
+    }
+
+    if count == 0 {
+        println!("✅ Nothing to migrate / 无需迁移");
+    } else if dry_run {
+        println!("ℹ️  {} item(s) to migrate, run without --dry-run to apply / {} 项需要迁移，去掉 --dry-run 执行", count, count);
+    } else {
+        println!("✅ Migrated {} item(s) / 已迁移 {} 项", count, count);
+    }
+    Ok(())
+}
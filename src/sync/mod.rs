@@ -1,2 +1,56 @@
+pub mod admin;
+pub mod api;
+pub mod archive;
+pub mod bump;
+pub mod bundle;
+pub mod cache;
+pub mod catalog;
+pub mod ci;
 pub mod client;
 pub mod cli;
+pub mod config;
+pub mod conflicts;
+pub mod credentials;
+pub mod encoding;
+pub mod export;
+pub mod filter;
+pub mod git;
+pub mod grpc;
+pub mod history;
+pub mod hooks;
+pub mod http;
+pub mod info;
+pub mod locations;
+pub mod lock;
+pub mod manifest;
+pub mod mcp;
+pub mod merge;
+pub mod migrate;
+pub mod notify;
+pub mod parts;
+pub mod paths;
+pub mod pick;
+pub mod pin;
+pub mod plugin;
+pub mod policy;
+pub mod progress;
+pub mod prune;
+pub mod redact;
+pub mod rename;
+pub mod renew;
+pub mod resolve;
+pub mod restore;
+pub mod schedule;
+pub mod server_error;
+pub mod show;
+pub mod skill;
+pub mod summary;
+pub mod state;
+pub mod table;
+pub mod telemetry;
+pub mod temp;
+pub mod ui;
+pub mod update;
+pub mod validate;
+pub mod verify;
+pub mod ws;
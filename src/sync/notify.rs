@@ -0,0 +1,25 @@
+use anyhow::Result;
+use notify_rust::Notification;
+
+/// 操作完成后发送桌面通知（需通过 --notify 或配置 notify = true 开启）
+///
+/// 通知只是提示，发送失败（如无桌面环境）时静默忽略
+pub fn notify_result<T>(enabled: bool, operation: &str, result: &Result<T>) {
+    if !enabled {
+        return;
+    }
+
+    let (summary, body) = match result {
+        Ok(_) => (
+            format!("skills-sync: {} finished", operation),
+            format!("{} completed successfully / {} 已完成", operation, operation),
+        ),
+        Err(e) => (format!("skills-sync: {} failed", operation), format!("{:#}", e)),
+    };
+
+    let _ = Notification::new()
+        .appname("skills-sync")
+        .summary(&summary)
+        .body(&body)
+        .show();
+}
@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::sync::archive::ArchiveFormat;
+
+/// 分卷索引文件的类型标识
+const PARTS_INDEX_KIND: &str = "skills-sync-parts";
+
+/// 分卷索引：归档被拆分为多个分卷分别上传，索引本身作为一个普通文件上传，
+/// 其业务码即为整个归档的逻辑业务码
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartsIndex {
+    /// 固定为 "skills-sync-parts"
+    pub kind: String,
+    /// 索引格式版本
+    pub version: u32,
+    /// 原始归档格式
+    pub format: ArchiveFormat,
+    /// 原始归档 SHA256
+    pub sha256: String,
+    /// 原始归档大小（字节）
+    pub size: u64,
+    /// 按顺序排列的分卷
+    pub parts: Vec<PartInfo>,
+}
+
+/// 单个分卷信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartInfo {
+    /// 分卷的业务码
+    pub code: String,
+    /// 分卷 SHA256
+    pub sha256: String,
+    /// 分卷大小（字节）
+    pub size: u64,
+}
+
+impl PartsIndex {
+    /// 创建空索引
+    pub fn new(format: ArchiveFormat, sha256: String, size: u64) -> Self {
+        PartsIndex {
+            kind: PARTS_INDEX_KIND.to_string(),
+            version: 1,
+            format,
+            sha256,
+            size,
+            parts: Vec::new(),
+        }
+    }
+
+    /// 尝试将下载的文件解析为分卷索引，不是索引时返回 None
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let mut head = [0u8; 1];
+        let mut file = fs::File::open(path)?;
+        if file.read(&mut head)? == 0 || head[0] != b'{' {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(path)?;
+        match serde_json::from_str::<PartsIndex>(&content) {
+            Ok(index) if index.kind == PARTS_INDEX_KIND => Ok(Some(index)),
+            _ => Ok(None),
+        }
+    }
+
+    /// 写入索引文件
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .context("Failed to write parts index / 写入分卷索引失败")
+    }
+}
+
+/// 将文件按 max_part_size 拆分为多个分卷，返回分卷路径（<原路径>.partN）
+pub fn split_file(path: &Path, max_part_size: u64) -> Result<Vec<PathBuf>> {
+    let mut input = fs::File::open(path).context("Failed to open archive / 打开归档文件失败")?;
+    let mut parts = Vec::new();
+    let mut buf = vec![0u8; 64 * 1024];
+
+    loop {
+        let part_path = PathBuf::from(format!("{}.part{}", path.display(), parts.len() + 1));
+        let mut output = fs::File::create(&part_path).context("Failed to create part file / 创建分卷文件失败")?;
+        let mut written = 0u64;
+
+        while written < max_part_size {
+            let want = (max_part_size - written).min(buf.len() as u64) as usize;
+            let n = input.read(&mut buf[..want])?;
+            if n == 0 {
+                break;
+            }
+            output.write_all(&buf[..n])?;
+            written += n as u64;
+        }
+
+        if written == 0 {
+            drop(output);
+            fs::remove_file(&part_path)?;
+            break;
+        }
+        parts.push(part_path);
+    }
+
+    Ok(parts)
+}
+
+/// 按顺序拼接分卷为完整文件，并校验整体 SHA256
+pub fn join_files(parts: &[PathBuf], output: &Path, expected_sha256: &str) -> Result<()> {
+    let mut out = fs::File::create(output).context("Failed to create archive / 创建归档文件失败")?;
+    let mut hasher = Sha256::new();
+
+    for part in parts {
+        let content = fs::read(part).context("Failed to read part file / 读取分卷文件失败")?;
+        hasher.update(&content);
+        out.write_all(&content)?;
+    }
+
+    let actual = format!("{:x}", hasher.finalize());
+    if actual != expected_sha256 {
+        return Err(anyhow::anyhow!(
+            "Integrity check failed: reassembled archive SHA256 mismatch / 完整性校验失败: 合并后的归档 SHA256 不匹配\n  expected / 期望: {}\n  actual   / 实际: {}",
+            expected_sha256,
+            actual
+        ));
+    }
+
+    Ok(())
+}
+
+/// 计算文件 SHA256
+pub fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
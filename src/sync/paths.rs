@@ -0,0 +1,339 @@
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+use unicode_normalization::UnicodeNormalization;
+
+/// Windows 保留的设备名，不能作为文件或目录名（不区分大小写，带扩展名也不行）
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// 超过该长度的路径在 Windows 上需要 \\?\ 前缀（MAX_PATH 为 260，留出余量）
+const LONG_PATH_THRESHOLD: usize = 240;
+
+/// 判断路径组件在 Windows 上是否为保留名（如 CON、nul.txt）或以点、空格结尾
+pub fn is_reserved_name(component: &str) -> bool {
+    let stem = component.split('.').next().unwrap_or(component).trim_end();
+    RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem))
+        || component.ends_with('.')
+        || component.ends_with(' ')
+}
+
+/// 为 Windows 上的保留名组件加后缀 "_"，其他平台原样返回
+fn sanitize_component(component: OsString) -> OsString {
+    match component.to_str() {
+        Some(name) if cfg!(windows) && is_reserved_name(name) => {
+            OsString::from(format!("{}_", name.trim_end_matches(['.', ' '])))
+        }
+        _ => component,
+    }
+}
+
+/// 在 Windows 上为过长的绝对路径加 \\?\ 前缀，使文件 API 不受 MAX_PATH 限制；其他平台原样返回
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) || !path.is_absolute() {
+        return path.to_path_buf();
+    }
+    let raw = path.to_string_lossy();
+    if raw.starts_with(r"\\?\") || raw.len() < LONG_PATH_THRESHOLD {
+        return path.to_path_buf();
+    }
+
+    // \\?\ 路径不做任何规范化，必须使用反斜杠
+    let raw = raw.replace('/', "\\");
+    match raw.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{}", unc)),
+        None => PathBuf::from(format!(r"\\?\{}", raw)),
+    }
+}
+
+/// 去掉 \\?\ 前缀，用于显示和计算相对路径
+pub fn strip_long_prefix(path: &Path) -> PathBuf {
+    let raw = path.to_string_lossy();
+    if let Some(unc) = raw.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", unc))
+    } else if let Some(local) = raw.strip_prefix(r"\\?\") {
+        PathBuf::from(local)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// 将 manifest 中的原始路径（正斜杠分隔、百分号编码）转换为本地路径：
+/// 逐个组件解码拼接，Windows 上保留名组件加 "_" 后缀，过长时加 \\?\ 前缀
+///
+/// 已存在的目录或文件与组件仅 Unicode 规范化形式不同（如 macOS 上创建的 NFD 名称）时沿用已有名称，避免产生重复目录
+///
+/// 解码后为 "."、".."，或含路径分隔符、根目录、盘符的组件会被拒绝，结果总是位于 home_dir 之下
+pub fn local_path(home_dir: &Path, original_path: &str) -> anyhow::Result<PathBuf> {
+    let unsafe_path = || anyhow::anyhow!("Unsafe path in archive / 归档中的路径不安全: {}", original_path);
+
+    let mut path = home_dir.to_path_buf();
+    for component in original_path.split('/').filter(|c| !c.is_empty()) {
+        let decoded = decode_component(component);
+        if !is_plain_component(&decoded) {
+            return Err(unsafe_path());
+        }
+        let name = existing_variant(&path, sanitize_component(decoded));
+        path.push(name);
+    }
+    if path == home_dir || !path.starts_with(home_dir) {
+        return Err(unsafe_path());
+    }
+    Ok(long_path(&path))
+}
+
+/// 组件是否只是一个普通的文件或目录名（拼接后不会离开父目录）
+fn is_plain_component(name: &OsStr) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(single)), None) if single == name
+    )
+}
+
+/// 将路径字符串规范化为 NFC，打包和解压时统一使用，使 macOS（NFD）与 Linux/Windows（NFC）上的同名 skill 一致
+pub fn normalize_nfc(path: &str) -> String {
+    path.nfc().collect()
+}
+
+/// 在 parent 中查找与 name 规范化后相同的已有条目，找不到时返回 name 本身
+fn existing_variant(parent: &Path, name: OsString) -> OsString {
+    let Some(wanted) = name.to_str().filter(|n| !n.is_ascii()) else {
+        return name;
+    };
+    if parent.join(&name).exists() {
+        return name;
+    }
+    let wanted = normalize_nfc(wanted);
+    let Ok(entries) = std::fs::read_dir(long_path(parent)) else {
+        return name;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name())
+        .find(|existing| existing.to_str().is_some_and(|e| normalize_nfc(e) == wanted))
+        .unwrap_or(name)
+}
+
+/// 本地路径中是否包含被重命名的 Windows 保留名组件
+pub fn was_renamed(original_path: &str) -> bool {
+    cfg!(windows) && original_path.split('/').any(is_reserved_name)
+}
+
+/// manifest 中原始路径的编码方式：百分号编码非 UTF-8 字节和 "%" 本身
+pub const PATH_ENCODING_PERCENT: &str = "percent";
+
+/// 无损编码单个路径组件：合法 UTF-8 原样保留（"%" 编码为 %25），非 UTF-8 字节编码为 %XX
+pub fn encode_component(component: &OsStr) -> String {
+    let mut out = String::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        for chunk in component.as_bytes().utf8_chunks() {
+            out.push_str(&chunk.valid().replace('%', "%25"));
+            for byte in chunk.invalid() {
+                out.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+    // Windows 文件名为 UTF-16，只有未配对的代理项无法表示为 UTF-8，按替换字符处理
+    #[cfg(not(unix))]
+    out.push_str(&component.to_string_lossy().replace('%', "%25"));
+    out
+}
+
+/// 解码 encode_component 编码的路径组件，无效的 % 序列原样保留
+pub fn decode_component(component: &str) -> OsString {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        OsString::from_vec(decoded)
+    }
+    #[cfg(not(unix))]
+    OsString::from(String::from_utf8_lossy(&decoded).into_owned())
+}
+
+/// 解码 manifest 中的原始路径为 UTF-8 字符串（仍以正斜杠分隔），含非 UTF-8 字节时返回 None
+pub fn decode_path_utf8(original_path: &str) -> Option<String> {
+    original_path
+        .split('/')
+        .map(|c| decode_component(c).into_string().ok())
+        .collect::<Option<Vec<_>>>()
+        .map(|parts| parts.join("/"))
+}
+
+/// 将路径编码为 manifest 中使用的正斜杠分隔字符串
+pub fn encode_path(path: &Path) -> String {
+    let mut out = String::new();
+    for component in path.components() {
+        let part = match component {
+            Component::Prefix(prefix) => {
+                out.push_str(&prefix.as_os_str().to_string_lossy());
+                continue;
+            }
+            Component::RootDir => {
+                out.push('/');
+                continue;
+            }
+            Component::CurDir => ".".to_string(),
+            Component::ParentDir => "..".to_string(),
+            Component::Normal(name) => encode_component(name),
+        };
+        if !out.is_empty() && !out.ends_with('/') {
+            out.push('/');
+        }
+        out.push_str(&part);
+    }
+    out
+}
+
+/// 本地路径对应的原始路径（相对用户目录、百分号编码、NFC），与打包时 manifest 中的 original_path 一致
+pub fn original_path(path: &Path) -> String {
+    let relative = dirs::home_dir()
+        .and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+    normalize_nfc(&encode_path(&relative))
+}
+
+/// 展开用户输入的路径：开头的 ~ 替换为用户目录，$VAR / ${VAR} 替换为环境变量，
+/// 相对路径基于当前目录转换为绝对路径，并去掉其中的 . 和 ..
+pub fn expand_path(input: &str) -> anyhow::Result<PathBuf> {
+    let expanded = expand_env(input)?;
+
+    let path = if expanded == "~" {
+        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Failed to get home directory / 无法获取用户目录"))?
+    } else if let Some(rest) = expanded.strip_prefix("~/").or_else(|| expanded.strip_prefix("~\\")) {
+        dirs::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get home directory / 无法获取用户目录"))?
+            .join(rest)
+    } else {
+        PathBuf::from(&expanded)
+    };
+
+    let absolute = if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    Ok(normalize_lexically(&absolute))
+}
+
+/// 替换 $VAR 和 ${VAR}，未定义的变量报错，$ 后不是变量名时原样保留
+fn expand_env(input: &str) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            out.push(c);
+            continue;
+        }
+
+        let braced = chars.peek() == Some(&'{');
+        if braced {
+            chars.next();
+        }
+        let mut name = String::new();
+        while let Some(&n) = chars.peek() {
+            if n.is_ascii_alphanumeric() || n == '_' {
+                name.push(n);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        if braced && chars.next() != Some('}') {
+            return Err(anyhow::anyhow!("Unterminated ${{ in path / 路径中的 ${{ 未闭合: {}", input));
+        }
+        if name.is_empty() {
+            out.push('$');
+            if braced {
+                out.push_str("{}");
+            }
+            continue;
+        }
+
+        let value = std::env::var(&name).map_err(|_| {
+            anyhow::anyhow!("Undefined environment variable / 环境变量未定义: {} (in {})", name, input)
+        })?;
+        out.push_str(&value);
+    }
+    Ok(out)
+}
+
+/// 不访问文件系统地去掉路径中的 . 和 ..（目录可能尚不存在）
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn component_encoding_round_trips() {
+        for name in ["SKILL.md", "100%", "%41", "中文 skill", "a%", "%zz"] {
+            let encoded = encode_component(OsStr::new(name));
+            assert_eq!(decode_component(&encoded), OsString::from(name), "{}", name);
+        }
+        assert_eq!(encode_component(OsStr::new("100%")), "100%25");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_bytes_round_trip() {
+        use std::os::unix::ffi::OsStrExt;
+        let name = OsStr::from_bytes(b"bad\xffname");
+        let encoded = encode_component(name);
+        assert_eq!(encoded, "bad%FFname");
+        assert_eq!(decode_component(&encoded), name);
+    }
+
+    #[test]
+    fn path_encoding_round_trips() {
+        let encoded = encode_path(Path::new(".claude/skills/50% off/SKILL.md"));
+        assert_eq!(encoded, ".claude/skills/50%25 off/SKILL.md");
+        assert_eq!(decode_path_utf8(&encoded).as_deref(), Some(".claude/skills/50% off/SKILL.md"));
+    }
+
+    #[test]
+    fn local_path_stays_under_home() {
+        let home = Path::new("/home/user");
+        let path = local_path(home, ".claude/skills/x/SKILL.md").unwrap();
+        assert_eq!(strip_long_prefix(&path), home.join(".claude/skills/x/SKILL.md"));
+
+        for bad in ["../etc/passwd", ".claude/../../x", "./x/..", "%2E%2E/x", "a%2Fb/../../..", "", "/"] {
+            assert!(local_path(home, bad).is_err(), "{}", bad);
+        }
+        // 编码后的分隔符不能拆出新的路径组件
+        assert!(local_path(home, "x/a%2F..%2F..%2Fy").is_err());
+    }
+}
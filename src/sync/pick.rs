@@ -0,0 +1,163 @@
+use anyhow::{Context, Result};
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, FuzzySelect, Select};
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use crate::sync::client::{collect_skills, execute_upload, resolve_skill_dirs, SkillInfo, UploadOptions};
+use crate::sync::config::open_in_editor;
+use crate::sync::info::print_info;
+use crate::sync::lock::lock_roots;
+use crate::sync::paths::original_path;
+use crate::sync::state::SyncState;
+use crate::sync::table::truncate;
+
+/// 选择列表中描述最多显示的字符数
+const MAX_DESCRIPTION_CHARS: usize = 60;
+
+/// 选中 skill 后可执行的操作
+const ACTIONS: [&str; 5] = [
+    "Info / 详情",
+    "Edit / 编辑",
+    "Upload / 上传",
+    "Uninstall / 卸载",
+    "Back / 返回",
+];
+
+/// 可选择的 skill 及其所在的 skills 根目录
+struct Candidate {
+    root: PathBuf,
+    source: String,
+    skill: SkillInfo,
+}
+
+/// 选择列表中的一行：名称、来源和截断后的描述
+fn label(source: &str, skill: &SkillInfo) -> String {
+    format!("{}  [{}]  {}", skill.name, source, truncate(&skill.description, MAX_DESCRIPTION_CHARS))
+}
+
+fn candidates(dirs: &[PathBuf]) -> Result<Vec<Candidate>> {
+    let mut candidates = Vec::new();
+    for root in resolve_skill_dirs(dirs.to_vec())? {
+        for (source, skills) in collect_skills(vec![root.clone()])? {
+            candidates.extend(skills.into_iter().map(|skill| Candidate {
+                root: root.clone(),
+                source: source.clone(),
+                skill,
+            }));
+        }
+    }
+    candidates.sort_by(|a, b| a.skill.name.cmp(&b.skill.name));
+    Ok(candidates)
+}
+
+/// 执行交互选择命令：模糊搜索全部本地 skills，选中后可查看详情、编辑、上传或卸载
+///
+/// 按 Esc 退出；每次操作后重新扫描目录，回到选择列表
+pub async fn execute_pick(dirs: Vec<PathBuf>, server: Option<String>, upload: UploadOptions) -> Result<()> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Err(anyhow::anyhow!("pick needs an interactive terminal / pick 需要交互式终端"));
+    }
+    let theme = ColorfulTheme::default();
+
+    loop {
+        let candidates = candidates(&dirs)?;
+        if candidates.is_empty() {
+            println!("❌ No skills found / 未找到任何 skills");
+            return Ok(());
+        }
+        let labels: Vec<String> = candidates.iter().map(|c| label(&c.source, &c.skill)).collect();
+        let Some(index) = FuzzySelect::with_theme(&theme)
+            .with_prompt(format!("Pick a skill ({} total, Esc to quit) / 选择 skill（共 {} 个，Esc 退出）", candidates.len(), candidates.len()))
+            .items(&labels)
+            .max_length(15)
+            .interact_opt()?
+        else {
+            return Ok(());
+        };
+        let picked = &candidates[index];
+
+        let action = Select::with_theme(&theme)
+            .with_prompt(&picked.skill.name)
+            .items(&ACTIONS)
+            .default(0)
+            .interact_opt()?;
+        let result = match action {
+            Some(0) => print_info(&picked.source, &picked.skill),
+            Some(1) => open_in_editor(&picked.skill.file),
+            Some(2) => {
+                let options = UploadOptions {
+                    skills: vec![picked.skill.name.clone()],
+                    ..upload.clone()
+                };
+                execute_upload(vec![picked.root.clone()], server.clone().into_iter().collect(), options).await
+            }
+            Some(3) => uninstall(picked, &theme),
+            _ => Ok(()),
+        };
+        // 单个操作失败不退出选择器
+        if let Err(e) = result {
+            println!("❌ {:#}", e);
+        }
+        println!();
+    }
+}
+
+/// 确认后删除 skill 目录，并移除同步状态中该目录下的记录
+fn uninstall(picked: &Candidate, theme: &ColorfulTheme) -> Result<()> {
+    let dir: &Path = picked.skill.file.parent().context("Invalid skill path / 无效的 skill 路径")?;
+    // SKILL.md 直接位于根目录时，删除父目录会删掉整个 skills 目录
+    if dir == picked.root {
+        return Err(anyhow::anyhow!(
+            "SKILL.md sits directly in the skills directory, refusing to delete it / SKILL.md 直接位于 skills 目录中，拒绝删除: {}",
+            dir.display()
+        ));
+    }
+    let confirmed = Confirm::with_theme(theme)
+        .with_prompt(format!("Delete {} and all its files? / 删除 {} 及其全部文件？", dir.display(), dir.display()))
+        .default(false)
+        .interact()?;
+    if !confirmed {
+        return Ok(());
+    }
+
+    let _lock = lock_roots(std::slice::from_ref(&picked.root), "uninstall")?;
+    fs::remove_dir_all(dir).with_context(|| format!("Failed to delete / 删除失败: {}", dir.display()))?;
+
+    let mut state = SyncState::load()?;
+    let prefix = format!("{}/", original_path(dir));
+    let before = state.synced.len();
+    state.synced.retain(|path, _| !path.starts_with(&prefix));
+    let forgotten = state.last_synced.remove(&original_path(dir)).is_some();
+    if state.synced.len() != before || forgotten {
+        state.save()?;
+    }
+    println!("🗑️  Uninstalled / 已卸载: {}", picked.skill.name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_long_descriptions_in_labels() {
+        let skill = SkillInfo {
+            name: "writer".to_string(),
+            description: "写".repeat(MAX_DESCRIPTION_CHARS + 5),
+            path: String::new(),
+            file: PathBuf::new(),
+            tags: Vec::new(),
+            version: None,
+            size: 0,
+            modified: None,
+            private: false,
+            synced: Default::default(),
+        };
+        let label = label("Claude", &skill);
+        assert!(label.starts_with("writer  [Claude]  "));
+        assert!(label.ends_with('…'));
+        assert_eq!(label.chars().filter(|&c| c == '写').count(), MAX_DESCRIPTION_CHARS);
+    }
+}
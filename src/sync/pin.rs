@@ -0,0 +1,162 @@
+use anyhow::{Context, Result};
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::sync::locations;
+
+/// 证书变化时是否接受新证书并更新记录（--trust-server-cert）
+static TRUST_NEW: AtomicBool = AtomicBool::new(false);
+
+/// 本进程中已核对过的服务器，避免每个请求都读取记录文件
+static VERIFIED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+/// 一个服务器首次连接时记录的证书
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KnownServer {
+    /// 服务器叶子证书（DER）的 SHA256
+    sha256: String,
+    /// 首次记录时间
+    first_seen: chrono::DateTime<chrono::Utc>,
+}
+
+/// 设置证书变化时是否接受新证书
+pub fn init(trust_new: bool) {
+    TRUST_NEW.store(trust_new, Ordering::Relaxed);
+}
+
+/// 记录文件路径（状态目录下的 known_servers.json，与同步状态分开，避免并发保存时相互覆盖）
+fn path() -> Result<PathBuf> {
+    Ok(locations::state_dir()?.join("known_servers.json"))
+}
+
+fn load() -> Result<BTreeMap<String, KnownServer>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(&path).context("Failed to read known servers / 读取已知服务器记录失败")?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Invalid known servers file / 已知服务器记录格式错误: {}", path.display()))
+}
+
+fn save(known: &BTreeMap<String, KnownServer>) -> Result<()> {
+    let path = path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(known)?)?;
+    fs::rename(&tmp_path, &path).context("Failed to save known servers / 保存已知服务器记录失败")
+}
+
+/// 将 server 的证书指纹与记录比较：首次出现时记录，一致时通过，变化时除非 trust_new 否则报错
+///
+/// 返回记录是否被修改（需要保存）
+fn pin(known: &mut BTreeMap<String, KnownServer>, server: &str, fingerprint: String, trust_new: bool) -> Result<bool> {
+    match known.get(server) {
+        Some(pinned) if pinned.sha256 == fingerprint => return Ok(false),
+        Some(pinned) if !trust_new => {
+            eprintln!();
+            eprintln!("@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@");
+            eprintln!("⚠️  WARNING: SERVER CERTIFICATE HAS CHANGED / 警告：服务器证书已变化");
+            eprintln!("@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@@");
+            eprintln!("   Someone may be intercepting this connection (man-in-the-middle),");
+            eprintln!("   or the server simply renewed its certificate.");
+            eprintln!("   连接可能被中间人劫持，也可能只是服务器更换了证书。");
+            eprintln!("   server / 服务器:        {}", server);
+            eprintln!("   pinned / 已记录:        {} ({})", pinned.sha256, pinned.first_seen.format("%Y-%m-%d"));
+            eprintln!("   presented / 当前证书:   {}", fingerprint);
+            eprintln!();
+            return Err(anyhow::anyhow!(
+                "Refusing to continue with a changed certificate for {}; if the change is expected, rerun with --trust-server-cert / 证书已变化，拒绝继续连接 {}；确认变化正常后可加 --trust-server-cert 重新运行",
+                server,
+                server
+            ));
+        }
+        Some(_) => eprintln!("🔐 Trusting new certificate for / 已信任新证书: {} ({})", server, fingerprint),
+        None => eprintln!("🔐 First contact, pinned certificate for / 首次连接，已记录证书: {} ({})", server, fingerprint),
+    }
+    known.insert(
+        server.to_string(),
+        KnownServer {
+            sha256: fingerprint,
+            first_seen: chrono::Utc::now(),
+        },
+    );
+    Ok(true)
+}
+
+/// 首次信任（TOFU）：核对响应所用 TLS 证书与首次连接该服务器时记录的是否一致
+///
+/// 首次连接时记录证书指纹；之后证书变化时大声警告并拒绝继续（可能是中间人攻击），
+/// 除非指定了 --trust-server-cert。明文 HTTP、Unix 域套接字等没有证书的连接不做检查
+pub fn check(response: &Response) -> Result<()> {
+    let Some(cert) = response
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+    else {
+        return Ok(());
+    };
+    let url = response.url();
+    check_peer(url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or(443), cert)
+}
+
+/// 核对 host:port 出示的叶子证书（DER），规则与 check 相同；供 WebSocket 等不经过 reqwest 的 TLS 连接使用
+#[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+pub fn check_peer(host: &str, port: u16, cert: &[u8]) -> Result<()> {
+    let server = format!("{}:{}", host, port);
+    if VERIFIED.lock().is_ok_and(|v| v.contains(&server)) {
+        return Ok(());
+    }
+
+    let fingerprint = format!("{:x}", Sha256::digest(cert));
+    let mut known = load()?;
+    if pin(&mut known, &server, fingerprint, TRUST_NEW.load(Ordering::Relaxed))? {
+        save(&known)?;
+    }
+
+    if let Ok(mut verified) = VERIFIED.lock() {
+        verified.insert(server);
+    }
+    Ok(())
+}
+
+/// 无法取得证书的 TLS 连接（grpcs://）：该主机已记录过证书时拒绝连接，避免绕过证书核对
+#[cfg_attr(not(feature = "grpc"), allow(dead_code))]
+pub fn refuse_if_pinned(host: &str, scheme: &str) -> Result<()> {
+    let prefix = format!("{}:", host);
+    if load()?.keys().any(|server| server.starts_with(&prefix)) {
+        return Err(anyhow::anyhow!(
+            "{} cannot verify the pinned certificate of {}; use the https:// address instead / {} 无法核对 {} 已记录的证书，请改用 https:// 地址",
+            scheme,
+            host,
+            scheme,
+            host
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_on_first_contact_and_rejects_changes() {
+        let mut known = BTreeMap::new();
+        assert!(pin(&mut known, "example.com:443", "aa".into(), false).unwrap());
+        assert!(!pin(&mut known, "example.com:443", "aa".into(), false).unwrap());
+        assert!(pin(&mut known, "example.com:443", "bb".into(), false).is_err());
+        assert_eq!(known["example.com:443"].sha256, "aa");
+
+        assert!(pin(&mut known, "example.com:443", "bb".into(), true).unwrap());
+        assert_eq!(known["example.com:443"].sha256, "bb");
+    }
+}
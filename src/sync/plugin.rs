@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+use std::ffi::OsString;
+use std::io::ErrorKind;
+use std::process::Command;
+
+use crate::sync::config::{Config, CONFIG_ENV};
+use crate::sync::http;
+
+/// 插件可执行文件名前缀，与 cargo / git 一样按 skills-sync-<name> 查找
+const PLUGIN_PREFIX: &str = "skills-sync-";
+
+/// 执行外部子命令插件，返回插件的退出码
+///
+/// 全局参数和配置文件位置通过环境变量传给插件：
+/// SKILLS_SYNC_SERVER、SKILLS_SYNC_JOBS、SKILLS_SYNC_CONFIG
+pub fn execute_plugin(args: Vec<OsString>, server: Option<String>, jobs: usize) -> Result<i32> {
+    let mut args = args.into_iter();
+    let name = args
+        .next()
+        .context("Missing subcommand / 缺少子命令")?
+        .into_string()
+        .map_err(|n| anyhow::anyhow!("Invalid subcommand name / 无效的子命令名: {:?}", n))?;
+    let program = format!("{}{}", PLUGIN_PREFIX, name);
+
+    let mut cmd = Command::new(&program);
+    cmd.args(args).env("SKILLS_SYNC_JOBS", jobs.to_string());
+    if let Some(server) = &server {
+        cmd.env("SKILLS_SYNC_SERVER", server);
+    }
+    if let Some(token) = http::api_token() {
+        cmd.env(http::API_TOKEN_ENV, token);
+    }
+    if let Ok(config_path) = Config::path() {
+        cmd.env(CONFIG_ENV, config_path);
+    }
+
+    let status = match cmd.status() {
+        Ok(status) => status,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Err(anyhow::anyhow!(
+                "Unknown command '{}' and no '{}' found on PATH / 未知命令 '{}'，PATH 中也没有找到 '{}'",
+                name,
+                program,
+                name,
+                program
+            ));
+        }
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to run plugin / 执行插件失败: {}", program));
+        }
+    };
+
+    // 被信号终止时没有退出码
+    Ok(status.code().unwrap_or(1))
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::sync::paths::long_path;
+use crate::sync::skill;
+
+/// 上传前检查的元数据规范（config.toml 中的 [policy] 段），upload --strict 时生效
+///
+/// 示例：
+/// ```toml
+/// [policy]
+/// required-fields = ["version", "license"]
+/// max-description-length = 200
+/// min-tags = 1
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Policy {
+    /// front matter 中必须存在且不为空的字段（顶层或 metadata 下），name 和 description 总是必需的
+    pub required_fields: Vec<String>,
+    /// 描述最多的字符数
+    pub max_description_length: Option<usize>,
+    /// 至少需要的标签数
+    pub min_tags: usize,
+}
+
+/// 字段值是否为空：null、空字符串、空列表或空表
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Sequence(items) => items.is_empty(),
+        Value::Mapping(map) => map.is_empty(),
+        _ => false,
+    }
+}
+
+impl Policy {
+    /// 检查一个 SKILL.md，返回不符合规范的各项说明（符合时为空）
+    pub fn violations(&self, content: &str) -> Vec<String> {
+        let Some(front_matter) = skill::front_matter(content) else {
+            return vec!["no front matter / 没有 front matter".to_string()];
+        };
+        let mut violations = Vec::new();
+        let required = ["name", "description"].into_iter().chain(self.required_fields.iter().map(String::as_str));
+        for key in required {
+            if skill::field(&front_matter, key).is_none_or(is_empty) {
+                violations.push(format!("missing {} / 缺少 {}", key, key));
+            }
+        }
+
+        let metadata = skill::parse(content).unwrap_or_default();
+        let length = metadata.description.as_deref().map_or(0, |d| d.chars().count());
+        if let Some(max) = self.max_description_length.filter(|&max| length > max) {
+            violations.push(format!(
+                "description has {} characters, at most {} allowed / 描述有 {} 个字符，最多 {} 个",
+                length, max, length, max
+            ));
+        }
+        if metadata.tags.len() < self.min_tags {
+            violations.push(format!(
+                "{} tag(s), at least {} required / {} 个标签，至少需要 {} 个",
+                metadata.tags.len(),
+                self.min_tags,
+                metadata.tags.len(),
+                self.min_tags
+            ));
+        }
+        violations
+    }
+
+    /// 检查全部 SKILL.md，有不符合规范的 skill 时逐个列出并报错
+    pub fn check(&self, skill_files: &[PathBuf]) -> Result<()> {
+        let mut failed = 0;
+        for file in skill_files {
+            let content = fs::read_to_string(long_path(file)).unwrap_or_default();
+            let violations = self.violations(&content);
+            if violations.is_empty() {
+                continue;
+            }
+            if failed == 0 {
+                println!("❌ Policy check failed / 元数据规范检查未通过:");
+            }
+            failed += 1;
+            println!("  {}", file.display());
+            for violation in violations {
+                println!("    - {}", violation);
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{} skill(s) do not meet the policy in the config / {} 个 skill 不符合配置中的元数据规范",
+                failed,
+                failed
+            ));
+        }
+        println!("✅ {} skill(s) meet the policy / {} 个 skill 符合元数据规范", skill_files.len(), skill_files.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_each_violation() {
+        let policy = Policy {
+            required_fields: vec!["license".to_string(), "version".to_string()],
+            max_description_length: Some(10),
+            min_tags: 1,
+        };
+        let ok = "---\nname: a\ndescription: Short\nlicense: MIT\nmetadata:\n  version: 1.0.0\n  tags: [x]\n---\n";
+        assert!(policy.violations(ok).is_empty());
+
+        let bad = "---\nname: a\ndescription: Much too long text\nlicense: ''\n---\n";
+        let violations = policy.violations(bad);
+        assert_eq!(violations.len(), 4);
+        assert!(violations[0].starts_with("missing license"));
+        assert!(violations[1].starts_with("missing version"));
+        assert!(violations[2].starts_with("description has 18 characters"));
+        assert!(violations[3].starts_with("0 tag(s)"));
+
+        assert_eq!(Policy::default().violations("# no front matter").len(), 1);
+    }
+}
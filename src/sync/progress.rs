@@ -0,0 +1,130 @@
+//! 上传、下载的进度显示：进度条和 spinner 都带当前速度，进度条另有剩余时间（ETA），
+//! 传输结束后打印大小、用时和平均速度，慢速网络下能分辨“慢”和“卡住”
+//!
+//! CI 模式下进度条和 spinner 都不绘制，只保留逐行输出
+
+use anyhow::Result;
+use futures_core::Stream;
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::sync::ci;
+
+/// 上传请求体每次读取的大小
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 进度条和 spinner 的输出位置：CI 模式下不绘制
+fn draw_target() -> ProgressDrawTarget {
+    if ci::enabled() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    }
+}
+
+/// 总量为 length 的进度条
+pub fn bar(length: u64) -> ProgressBar {
+    ProgressBar::with_draw_target(Some(length), draw_target())
+}
+
+/// 总量未知时使用的 spinner
+pub fn spinner() -> ProgressBar {
+    ProgressBar::with_draw_target(None, draw_target())
+}
+
+/// 已知总大小时的进度条样式
+pub fn bar_style() -> Result<ProgressStyle> {
+    Ok(ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] [{bar:40.green/white}] {bytes}/{total_bytes} {binary_bytes_per_sec} ETA {eta} {msg}")?
+        .progress_chars("=>-"))
+}
+
+/// 总大小未知时的 spinner 样式，显示已传输的字节数
+pub fn spinner_style() -> Result<ProgressStyle> {
+    Ok(ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {bytes} {binary_bytes_per_sec} {msg}")?)
+}
+
+/// 平均速度（字节/秒），用时为 0 时按 1 毫秒计算
+pub fn average_speed(bytes: u64, elapsed: Duration) -> u64 {
+    (bytes as f64 / elapsed.as_secs_f64().max(0.001)) as u64
+}
+
+/// 传输结束后打印大小、用时和平均速度（不依赖终端，重定向到日志时也能看到）
+pub fn print_summary(uploaded: bool, bytes: u64, elapsed: Duration) {
+    let (action, action_zh) = if uploaded { ("Uploaded", "已上传") } else { ("Downloaded", "已下载") };
+    let speed = HumanBytes(average_speed(bytes, elapsed));
+    println!(
+        "⏱️  {} {} in {:.1}s ({}/s) / {} {}，用时 {:.1}s，平均 {}/s",
+        action,
+        HumanBytes(bytes),
+        elapsed.as_secs_f64(),
+        speed,
+        action_zh,
+        HumanBytes(bytes),
+        elapsed.as_secs_f64(),
+        speed
+    );
+}
+
+/// 边读文件边发送的请求体，每发出一块就推进进度条
+pub struct ProgressStream {
+    file: tokio::fs::File,
+    progress: ProgressBar,
+    buffer: Vec<u8>,
+}
+
+impl ProgressStream {
+    pub fn new(file: tokio::fs::File, progress: ProgressBar) -> Self {
+        ProgressStream {
+            file,
+            progress,
+            buffer: vec![0u8; CHUNK_SIZE],
+        }
+    }
+}
+
+impl Stream for ProgressStream {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut buffer = ReadBuf::new(&mut this.buffer);
+        match Pin::new(&mut this.file).poll_read(cx, &mut buffer) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e))),
+            Poll::Ready(Ok(())) if buffer.filled().is_empty() => Poll::Ready(None),
+            Poll::Ready(Ok(())) => {
+                this.progress.inc(buffer.filled().len() as u64);
+                Poll::Ready(Some(Ok(buffer.filled().to_vec())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_streamed_bytes() {
+        assert_eq!(average_speed(3 * 1024 * 1024, Duration::from_secs(2)), 1536 * 1024);
+        assert_eq!(average_speed(10, Duration::ZERO), 10_000);
+
+        let dir = std::env::temp_dir().join(format!("skills-sync-progress-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("body.bin");
+        std::fs::write(&path, vec![7u8; CHUNK_SIZE + 100]).unwrap();
+        let progress = ProgressBar::hidden();
+        let mut stream = ProgressStream::new(tokio::fs::File::open(&path).await.unwrap(), progress.clone());
+        let mut total = 0;
+        while let Some(chunk) = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            total += chunk.unwrap().len();
+        }
+        assert_eq!(total, CHUNK_SIZE + 100);
+        assert_eq!(progress.position(), total as u64);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
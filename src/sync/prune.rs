@@ -0,0 +1,288 @@
+use anyhow::{Context, Result};
+use indicatif::HumanBytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::sync::cache;
+use crate::sync::cli::{parse_duration, parse_size};
+use crate::sync::history;
+use crate::sync::merge::blobs_dir;
+use crate::sync::state::SyncState;
+
+/// 不再被同步状态引用的同步基准至少存在这么久才会删除，避免删掉其他进程刚写入、尚未记入状态的内容
+const ORPHAN_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// 下载缓存和 skill 变更记录的保留策略（config.toml 中的 [retention] 段）
+///
+/// 示例：
+/// ```toml
+/// [retention]
+/// keep-last = 20
+/// max-age = "90d"
+/// max-size = "2G"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Retention {
+    /// 下载缓存保留最近使用的业务码数，每个 skill 保留的变更记录条数
+    pub keep_last: Option<usize>,
+    /// 删除超过该时间未使用的下载缓存和更早的变更记录（如 "90d"）
+    pub max_age: Option<String>,
+    /// 下载缓存的总大小上限（如 "1G"），超出时从最久未使用的开始删除
+    pub max_size: Option<String>,
+    /// 每次上传、下载后自动按策略清理
+    pub auto: bool,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            keep_last: None,
+            max_age: None,
+            max_size: Some("1G".to_string()),
+            auto: true,
+        }
+    }
+}
+
+/// 解析后的保留策略
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub keep_last: Option<usize>,
+    pub max_age: Option<Duration>,
+    pub max_size: Option<u64>,
+}
+
+impl Retention {
+    /// 解析配置中的时间和大小
+    pub fn limits(&self) -> Result<Limits> {
+        Ok(Limits {
+            keep_last: self.keep_last,
+            max_age: self
+                .max_age
+                .as_deref()
+                .map(parse_duration)
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("Invalid retention.max-age in config / 配置中的 retention.max-age 无效: {}", e))?,
+            max_size: self
+                .max_size
+                .as_deref()
+                .map(parse_size)
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("Invalid retention.max-size in config / 配置中的 retention.max-size 无效: {}", e))?,
+        })
+    }
+}
+
+/// 可清理的一项：路径、大小、最近使用时间
+struct Item {
+    path: PathBuf,
+    size: u64,
+    used: SystemTime,
+}
+
+/// 按保留策略选出要删除的条目：超出最近 keep_last 个的、超过 max_age 未使用的，
+/// 以及从最久未使用的开始删除，直到剩余总大小不超过 max_size
+fn select(mut items: Vec<Item>, limits: &Limits, now: SystemTime) -> Vec<Item> {
+    // 最近使用的在前
+    items.sort_by_key(|item| std::cmp::Reverse(item.used));
+    let mut kept_size = 0;
+    let mut removed = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let too_many = limits.keep_last.is_some_and(|keep| index >= keep);
+        let too_old = limits
+            .max_age
+            .is_some_and(|age| now.duration_since(item.used).unwrap_or_default() > age);
+        let too_big = limits.max_size.is_some_and(|max| kept_size + item.size > max);
+        if too_many || too_old || too_big {
+            removed.push(item);
+        } else {
+            kept_size += item.size;
+        }
+    }
+    removed
+}
+
+/// 目录下全部文件的大小和最近修改时间
+fn dir_usage(dir: &PathBuf) -> (u64, SystemTime) {
+    let mut size = 0;
+    let mut used = SystemTime::UNIX_EPOCH;
+    for meta in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()).filter_map(|e| e.metadata().ok()) {
+        if meta.is_file() {
+            size += meta.len();
+            used = used.max(meta.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+        }
+    }
+    (size, used)
+}
+
+/// 下载缓存中的条目（每个业务码一个目录）
+fn cache_entries() -> Result<Vec<Item>> {
+    let Ok(entries) = fs::read_dir(cache::downloads_dir()?) else {
+        return Ok(Vec::new());
+    };
+    Ok(entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_dir())
+        .map(|path| {
+            let (size, used) = dir_usage(&path);
+            Item { path, size, used }
+        })
+        .collect())
+}
+
+/// 不再被同步状态（同步基准、上传记录）引用、且超过宽限期的同步基准
+fn orphaned_blobs(now: SystemTime) -> Result<Vec<Item>> {
+    let Ok(entries) = fs::read_dir(blobs_dir()?) else {
+        return Ok(Vec::new());
+    };
+    let state = SyncState::load()?;
+    let referenced: HashSet<&String> = state
+        .synced
+        .values()
+        .chain(state.uploads.values().flat_map(|record| record.files.values()))
+        .collect();
+    Ok(entries
+        .filter_map(|e| e.ok())
+        .filter(|e| !referenced.contains(&e.file_name().to_string_lossy().into_owned()))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            let used = meta.modified().ok()?;
+            (now.duration_since(used).unwrap_or_default() > ORPHAN_GRACE).then(|| Item {
+                path: e.path(),
+                size: meta.len(),
+                used,
+            })
+        })
+        .collect())
+}
+
+/// 一次清理的结果
+#[derive(Default)]
+struct PruneReport {
+    cache_entries: usize,
+    cache_bytes: u64,
+    blobs: usize,
+    blob_bytes: u64,
+    history_entries: usize,
+}
+
+/// 按保留策略清理下载缓存、无人引用的同步基准和 skill 变更记录；dry_run 时只统计不删除
+fn prune(limits: &Limits, dry_run: bool) -> Result<PruneReport> {
+    let now = SystemTime::now();
+    let mut report = PruneReport::default();
+
+    for item in select(cache_entries()?, limits, now) {
+        if !dry_run {
+            fs::remove_dir_all(&item.path)
+                .with_context(|| format!("Failed to delete / 删除失败: {}", item.path.display()))?;
+        }
+        report.cache_entries += 1;
+        report.cache_bytes += item.size;
+    }
+
+    // 仍被引用的同步基准是三方合并、增量上传和 restore 所需的，始终保留
+    for item in orphaned_blobs(now)? {
+        if !dry_run {
+            fs::remove_file(&item.path)
+                .with_context(|| format!("Failed to delete / 删除失败: {}", item.path.display()))?;
+        }
+        report.blobs += 1;
+        report.blob_bytes += item.size;
+    }
+
+    let cutoff = limits
+        .max_age
+        .and_then(|age| chrono::Duration::from_std(age).ok())
+        .map(|age| chrono::Utc::now() - age);
+    report.history_entries = history::trim(limits.keep_last, cutoff, dry_run)?;
+    Ok(report)
+}
+
+/// 执行清理命令：按保留策略清理下载缓存、无人引用的同步基准和 skill 变更记录
+pub fn execute_prune(limits: Limits, dry_run: bool) -> Result<()> {
+    let report = prune(&limits, dry_run)?;
+    let verb = if dry_run { "Would remove / 将删除" } else { "Removed / 已删除" };
+    println!(
+        "🗑️  {} {} download cache entr(ies), {} / 下载缓存 {} 项，{}",
+        verb,
+        report.cache_entries,
+        HumanBytes(report.cache_bytes),
+        report.cache_entries,
+        HumanBytes(report.cache_bytes)
+    );
+    println!(
+        "🗑️  {} {} unreferenced sync base(s), {} / 无人引用的同步基准 {} 个，{}",
+        verb,
+        report.blobs,
+        HumanBytes(report.blob_bytes),
+        report.blobs,
+        HumanBytes(report.blob_bytes)
+    );
+    println!(
+        "🗑️  {} {} skill history entr(ies) / skill 变更记录 {} 条",
+        verb, report.history_entries, report.history_entries
+    );
+    Ok(())
+}
+
+/// 上传、下载后自动清理（retention.auto 为 false 时跳过）；失败只打印警告
+pub fn auto(retention: &Retention) {
+    if !retention.auto {
+        return;
+    }
+    let result = retention.limits().and_then(|limits| prune(&limits, false));
+    match result {
+        Ok(report) if report.cache_entries + report.blobs > 0 => println!(
+            "🧹 Pruned {} of local data / 清理了 {} 本地数据",
+            HumanBytes(report.cache_bytes + report.blob_bytes),
+            HumanBytes(report.cache_bytes + report.blob_bytes)
+        ),
+        Ok(_) => {}
+        Err(e) => println!("⚠️  Automatic prune failed / 自动清理失败: {:#}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_count_age_and_size_limits() {
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        let items = |sizes: &[u64]| -> Vec<Item> {
+            sizes
+                .iter()
+                .enumerate()
+                .map(|(i, &size)| Item {
+                    path: PathBuf::from(i.to_string()),
+                    size,
+                    used: now - day * i as u32,
+                })
+                .collect()
+        };
+        let names = |removed: Vec<Item>| -> Vec<String> {
+            let mut names: Vec<String> = removed.iter().map(|i| i.path.display().to_string()).collect();
+            names.sort();
+            names
+        };
+
+        let limits = Limits { keep_last: Some(2), ..Default::default() };
+        assert_eq!(names(select(items(&[1, 1, 1, 1]), &limits, now)), ["2", "3"]);
+
+        let limits = Limits { max_age: Some(day + day / 2), ..Default::default() };
+        assert_eq!(names(select(items(&[1, 1, 1, 1]), &limits, now)), ["2", "3"]);
+
+        // 大的条目放不下时删除，之后更小的条目仍可保留
+        let limits = Limits { max_size: Some(10), ..Default::default() };
+        assert_eq!(names(select(items(&[6, 8, 3, 5]), &limits, now)), ["1", "3"]);
+
+        assert!(select(items(&[1, 1]), &Limits::default(), now).is_empty());
+    }
+}
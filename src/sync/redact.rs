@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 打包时的脱敏规则（config.toml 中的 [[redact]] 段），只改写归档中的副本，本地文件不变
+///
+/// 示例：
+/// ```toml
+/// [[redact]]
+/// pattern = '[\w.-]+\.corp\.example\.com'
+/// replacement = "internal.example"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactRule {
+    /// 正则表达式
+    pub pattern: String,
+    /// 替换文本，可用 $1、${name} 引用捕获组；省略时删除匹配的内容
+    #[serde(default)]
+    pub replacement: String,
+}
+
+/// 编译后的脱敏规则，按配置顺序依次应用
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    rules: Vec<(Regex, String)>,
+}
+
+impl Redactor {
+    /// 编译规则，正则无效时报错并指明是哪一条
+    pub fn new(rules: &[RedactRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|regex| (regex, rule.replacement.clone()))
+                    .with_context(|| format!("Invalid redact pattern in config / 配置中的脱敏规则无效: {}", rule.pattern))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Redactor { rules })
+    }
+
+    /// 对文本内容应用全部规则，返回改写后的内容和替换次数；没有匹配或内容不是 UTF-8 时返回 None
+    pub fn apply(&self, content: &[u8]) -> Option<(Vec<u8>, usize)> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let mut text = std::str::from_utf8(content).ok()?.to_string();
+        let mut count = 0;
+        for (regex, replacement) in &self.rules {
+            let matches = regex.find_iter(&text).count();
+            if matches > 0 {
+                count += matches;
+                text = regex.replace_all(&text, replacement.as_str()).into_owned();
+            }
+        }
+        (count > 0).then(|| (text.into_bytes(), count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_text_in_rule_order() {
+        let rules: Vec<RedactRule> = toml::from_str::<toml::Table>(
+            "[[redact]]\npattern = '(\\w+)\\.corp\\.example\\.com'\nreplacement = '$1.example'\n\n[[redact]]\npattern = 'alice'\n",
+        )
+        .unwrap()["redact"]
+            .clone()
+            .try_into()
+            .unwrap();
+        let redactor = Redactor::new(&rules).unwrap();
+
+        let (text, count) = redactor.apply(b"ssh alice@db.corp.example.com and web.corp.example.com").unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "ssh @db.example and web.example");
+        assert_eq!(count, 3);
+        assert_eq!(redactor.apply(b"nothing to hide"), None);
+        assert_eq!(redactor.apply(&[0xff, 0xfe]), None);
+        assert!(Redactor::new(&[RedactRule { pattern: "(".to_string(), replacement: String::new() }]).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sync::client::{collect_skills, resolve_skill_dirs, SkillInfo};
+use crate::sync::lock::lock_roots;
+use crate::sync::paths::{is_reserved_name, normalize_nfc, original_path};
+use crate::sync::skill;
+use crate::sync::state::SyncState;
+
+/// 检查新名称可以作为目录名和 front matter 中的 name 使用
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !is_reserved_name(name)
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(anyhow::anyhow!(
+            "Invalid skill name (letters, digits, '-', '_' and '.' only) / 无效的 skill 名称（只能包含字母、数字、-、_ 和 .）: {}",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// 按名称（目录名）查找 skill，返回所在的 skills 根目录、来源和 skill 信息；多个目录中都有时报错
+pub(crate) fn find_skill(dirs: Vec<PathBuf>, name: &str) -> Result<(PathBuf, String, SkillInfo)> {
+    let mut matches = Vec::new();
+    for root in resolve_skill_dirs(dirs)? {
+        for (source, skills) in collect_skills(vec![root.clone()])? {
+            matches.extend(
+                skills
+                    .into_iter()
+                    .filter(|s| s.name == normalize_nfc(name))
+                    .map(|s| (root.clone(), source.clone(), s)),
+            );
+        }
+    }
+    match matches.len() {
+        0 => Err(anyhow::anyhow!("Skill not found / 未找到 skill: {}", name)),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let found: Vec<String> = matches.iter().map(|(_, _, s)| format!("  {}", s.file.display())).collect();
+            Err(anyhow::anyhow!(
+                "Skill {} exists in several directories, choose one with --dir / 多个目录中都有 skill {}，请用 --dir 指定:\n{}",
+                name,
+                name,
+                found.join("\n")
+            ))
+        }
+    }
+}
+
+/// 先写临时文件再重命名，避免中断时留下写了一半的文件
+pub(crate) fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let tmp = path.with_extension("md.tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        e.into()
+    })
+}
+
+/// 执行重命名命令：重命名 skill 目录，更新 SKILL.md 中的 name，并改写同步状态中该 skill 的路径
+///
+/// 任一步失败时撤销已完成的步骤；重命名期间锁定所在的 skills 根目录
+pub fn execute_rename(dirs: Vec<PathBuf>, old_name: String, new_name: String) -> Result<()> {
+    validate_name(&new_name)?;
+
+    let (root, _, skill) = find_skill(dirs, &old_name)?;
+    let skill_file = skill.file;
+
+    let _lock = lock_roots(std::slice::from_ref(&root), "rename")?;
+
+    let old_dir = skill_file.parent().context("Invalid skill path / 无效的 skill 路径")?.to_path_buf();
+    let new_dir = old_dir.with_file_name(&new_name);
+    if new_dir.exists() {
+        return Err(anyhow::anyhow!("Target already exists / 目标已存在: {}", new_dir.display()));
+    }
+    let entry_name = skill_file.file_name().context("Invalid skill path / 无效的 skill 路径")?;
+    let original = fs::read_to_string(&skill_file).context("Failed to read SKILL.md / 读取 SKILL.md 失败")?;
+    let updated = skill::set_name(&original, &new_name);
+
+    // 同步状态中以旧目录开头的路径改为新目录，使下次下载仍能与同步基准三方合并
+    let mut state = SyncState::load()?;
+    let (old_prefix, new_prefix) = (format!("{}/", original_path(&old_dir)), format!("{}/", original_path(&new_dir)));
+    let moved: Vec<String> = state.synced.keys().filter(|k| k.starts_with(&old_prefix)).cloned().collect();
+    for key in &moved {
+        if let Some(sha256) = state.synced.remove(key) {
+            state.synced.insert(format!("{}{}", new_prefix, &key[old_prefix.len()..]), sha256);
+        }
+    }
+    let times = state.last_synced.remove(&original_path(&old_dir));
+    if let Some(times) = times {
+        state.last_synced.insert(original_path(&new_dir), times);
+    }
+
+    fs::rename(&old_dir, &new_dir).with_context(|| {
+        format!("Failed to rename / 重命名失败: {} -> {}", old_dir.display(), new_dir.display())
+    })?;
+    let new_file = new_dir.join(entry_name);
+    let result = updated
+        .as_deref()
+        .map_or(Ok(()), |content| write_atomic(&new_file, content))
+        .and_then(|()| if moved.is_empty() && times.is_none() { Ok(()) } else { state.save() });
+    if let Err(e) = result {
+        if updated.is_some() {
+            let _ = write_atomic(&new_file, &original);
+        }
+        let _ = fs::rename(&new_dir, &old_dir);
+        return Err(e.context("Rename rolled back / 重命名已撤销"));
+    }
+
+    println!("✅ Renamed / 已重命名: {} -> {}", old_dir.display(), new_dir.display());
+    if updated.is_none() {
+        println!("ℹ️  No name field in front matter, SKILL.md unchanged / front matter 中没有 name 字段，SKILL.md 未修改");
+    }
+    if !moved.is_empty() {
+        println!("🔧 Updated {} sync state entries / 更新了 {} 条同步状态", moved.len(), moved.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_path_like_names() {
+        assert!(validate_name("new-skill_2").is_ok());
+        assert!(validate_name("../x").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("").is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::sync::api;
+use crate::sync::client::{resolve_code, resolve_server};
+use crate::sync::http;
+use crate::sync::server_error;
+use crate::sync::state::SyncState;
+
+/// 执行续期命令：延长已上传业务码的有效期，无需重新上传归档
+///
+/// 本机上传过该业务码时附带所有者令牌，服务器可据此只允许所有者续期
+pub async fn execute_renew(code: String, expire: Duration, server: Option<String>) -> Result<()> {
+    let server_url = resolve_server(server).await?;
+    let (code, _) = resolve_code(&server_url, &code).await?;
+
+    let state = SyncState::load()?;
+    let owner_token = state.owner_tokens.get(&server_url).and_then(|tokens| tokens.get(&code));
+
+    let url = format!("{}/sync/renew", server_url);
+    println!("⏳ Renewing / 续期: {} (+{})", code, format_duration(expire.as_secs()));
+
+    let client = http::client()?;
+    let response = http::send_with_retry(|| {
+        let mut request = client.post(&url).json(&json!({
+            "code": code,
+            "expire_seconds": expire.as_secs(),
+        }));
+        if let Some(token) = owner_token {
+            request = request.header("X-Owner-Token", token);
+        }
+        Ok(request)
+    })
+    .await
+    .context("Renew request failed / 续期请求失败")?;
+
+    let status = response.status();
+    match status {
+        StatusCode::FORBIDDEN => {
+            return Err(anyhow::anyhow!(
+                "Not allowed to renew code {} (only codes uploaded from this machine can be renewed) / 无权续期业务码 {}（只能续期本机上传的业务码）",
+                code,
+                code
+            ));
+        }
+        _ if !status.is_success() => {
+            return Err(server_error::from_response(response, "Renew failed / 续期失败", Some(&code)).await);
+        }
+        _ => {}
+    }
+
+    let result: serde_json::Value = response.json().await.context("Failed to parse response / 解析响应失败")?;
+    match api::payload(&server_url, &result)["expires_at"].as_str() {
+        Some(expires_at) => println!("✅ Code renewed, expires at / 已续期，过期时间: {}", expires_at),
+        None => println!("✅ Code renewed / 已续期"),
+    }
+    Ok(())
+}
+
+/// 以 1d2h30m 的形式显示秒数
+fn format_duration(secs: u64) -> String {
+    let (days, rest) = (secs / 86400, secs % 86400);
+    let (hours, rest) = (rest / 3600, rest % 3600);
+    let (minutes, seconds) = (rest / 60, rest % 60);
+    let mut out = String::new();
+    for (value, unit) in [(days, "d"), (hours, "h"), (minutes, "m"), (seconds, "s")] {
+        if value > 0 {
+            out.push_str(&format!("{}{}", value, unit));
+        }
+    }
+    if out.is_empty() {
+        out.push_str("0s");
+    }
+    out
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::sync::ci;
+use crate::sync::cli::OnConflict;
+
+/// 差异预览最多显示的行数
+const MAX_DIFF_LINES: usize = 20;
+
+/// 对一个本地与远端不同的 skill 的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// 按行三方合并（默认行为）
+    Merge,
+    /// 保留本地内容
+    KeepLocal,
+    /// 用远端内容覆盖本地
+    TakeRemote,
+    /// 本地不动，远端内容另存为 <skill>-remote
+    Rename,
+    /// 跳过整个 skill，不写入任何文件
+    Skip,
+}
+
+/// 一个本地与远端内容不同、且本地在上次同步后被修改过（或从未同步过）的文件
+pub struct ContestedFile<'a> {
+    pub path: &'a str,
+    pub local: &'a [u8],
+    pub remote: &'a [u8],
+}
+
+/// 按 --on-conflict 决定每个有差异的 skill 如何处理，记住"全部应用"的回答
+pub struct Resolver {
+    mode: OnConflict,
+    for_all: Option<Resolution>,
+}
+
+impl Resolver {
+    pub fn new(mode: OnConflict) -> Self {
+        Resolver { mode, for_all: None }
+    }
+
+    /// 决定 skill 的处理方式：prompt 模式下显示差异并询问
+    pub fn resolve(&mut self, skill: &str, files: &[ContestedFile]) -> Result<Resolution> {
+        if self.mode == OnConflict::Merge {
+            return Ok(Resolution::Merge);
+        }
+        if let Some(resolution) = self.for_all {
+            return Ok(resolution);
+        }
+        // --yes 时按默认回答（合并）处理，不询问
+        if ci::assume_yes() {
+            return Ok(Resolution::Merge);
+        }
+        if ci::enabled() {
+            return Err(anyhow::anyhow!(
+                "Prompts are disabled in CI mode; pass --yes to merge or use --on-conflict merge / CI 模式下不会询问，请指定 --yes 以合并或使用 --on-conflict merge"
+            ));
+        }
+        if !std::io::stdin().is_terminal() {
+            return Err(anyhow::anyhow!(
+                "--on-conflict prompt needs an interactive terminal / --on-conflict prompt 需要交互式终端"
+            ));
+        }
+
+        println!();
+        println!("⚔️  {} differs from the downloaded copy / {} 与下载的内容不同:", skill, skill);
+        for file in files {
+            println!("  ~/{}", file.path);
+        }
+        if let Some(file) = files.first() {
+            println!("{}", short_diff(file.local, file.remote));
+        }
+
+        let stdin = std::io::stdin();
+        loop {
+            print!("  [k]eep local / 保留本地  [r]emote / 采用远端  [m]erge / 合并  re[n]ame / 另存  [s]kip / 跳过  (uppercase = all / 大写应用到全部): ");
+            std::io::stdout().flush()?;
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line)? == 0 {
+                return Err(anyhow::anyhow!("No answer, aborting / 未作答，已中止"));
+            }
+            if let Some((resolution, all)) = parse_answer(line.trim()) {
+                if all {
+                    self.for_all = Some(resolution);
+                }
+                return Ok(resolution);
+            }
+        }
+    }
+}
+
+/// 解析回答：小写只作用于当前 skill，大写应用到其余全部
+fn parse_answer(answer: &str) -> Option<(Resolution, bool)> {
+    let mut chars = answer.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    let resolution = match c.to_ascii_lowercase() {
+        'k' => Resolution::KeepLocal,
+        'r' => Resolution::TakeRemote,
+        'm' => Resolution::Merge,
+        'n' => Resolution::Rename,
+        's' => Resolution::Skip,
+        _ => return None,
+    };
+    Some((resolution, c.is_ascii_uppercase()))
+}
+
+/// 本地 -> 远端的统一差异格式预览，超过 MAX_DIFF_LINES 行时截断
+fn short_diff(local: &[u8], remote: &[u8]) -> String {
+    let (Ok(local), Ok(remote)) = (std::str::from_utf8(local), std::str::from_utf8(remote)) else {
+        return "  (binary file differs / 二进制文件不同)".to_string();
+    };
+    let patch = diffy::create_patch(local, remote).to_string();
+    let lines: Vec<&str> = patch.lines().skip(2).collect();
+    let mut preview: Vec<String> = lines.iter().take(MAX_DIFF_LINES).map(|l| format!("    {}", l)).collect();
+    if lines.len() > MAX_DIFF_LINES {
+        preview.push(format!("    ... ({} more lines / 另有 {} 行)", lines.len() - MAX_DIFF_LINES, lines.len() - MAX_DIFF_LINES));
+    }
+    preview.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uppercase_answers_apply_to_all() {
+        assert_eq!(parse_answer("k"), Some((Resolution::KeepLocal, false)));
+        assert_eq!(parse_answer("R"), Some((Resolution::TakeRemote, true)));
+        assert_eq!(parse_answer("n"), Some((Resolution::Rename, false)));
+        assert_eq!(parse_answer("x"), None);
+        assert_eq!(parse_answer("kk"), None);
+        assert_eq!(parse_answer(""), None);
+    }
+}
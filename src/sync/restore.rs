@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sync::git;
+use crate::sync::lock::lock_roots;
+use crate::sync::merge::load_blob;
+use crate::sync::parts::file_sha256;
+use crate::sync::paths::{local_path, original_path, strip_long_prefix};
+use crate::sync::rename::find_skill;
+use crate::sync::state::SyncState;
+
+/// 同步状态中 skill 目录（原始路径）下的记录（原始路径, SHA256）
+pub(crate) fn synced_files(state: &SyncState, skill_dir: &str) -> Vec<(String, String)> {
+    let prefix = format!("{}/", skill_dir);
+    state
+        .synced
+        .iter()
+        .filter(|(path, _)| path.starts_with(&prefix))
+        .map(|(path, sha256)| (path.clone(), sha256.clone()))
+        .collect()
+}
+
+/// 把记录中被修改或删除的文件恢复为上次同步时的内容，返回恢复的文件数；之后新增的文件保持不变
+pub(crate) fn restore_synced(home: &Path, recorded: &[(String, String)]) -> Result<usize> {
+    let mut restored = 0;
+    for (path, sha256) in recorded {
+        let target = local_path(home, path)?;
+        if file_sha256(&target).ok().as_ref() == Some(sha256) {
+            continue;
+        }
+        let content = load_blob(sha256)
+            .with_context(|| format!("Last synced content is no longer stored / 上次同步的内容已不在本地: ~/{}", path))?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, content)?;
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+/// 执行恢复命令：把一个 skill 恢复为上次同步时的内容，或 git 历史中某个快照（提交）中的内容，其他 skill 不受影响
+///
+/// list 为 true 时只列出涉及该 skill 的快照
+pub fn execute_restore(dirs: Vec<PathBuf>, name: String, from: Option<String>, list: bool) -> Result<()> {
+    let (root, _, skill) = find_skill(dirs, &name)?;
+    let dir = skill.file.parent().context("Invalid skill path / 无效的 skill 路径")?;
+    // git 路径以 skills 根目录为基准，统一使用正斜杠
+    let relative = strip_long_prefix(dir)
+        .strip_prefix(&root)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| name.clone());
+
+    if list {
+        return git::list_snapshots(&root, &relative);
+    }
+
+    let _lock = lock_roots(std::slice::from_ref(&root), &format!("restore {}", name))?;
+    match from {
+        Some(snapshot) => {
+            git::restore(&root, &relative, &snapshot)?;
+            println!("↩️  Restored {} from snapshot {} / 已从快照 {} 恢复 {}", name, snapshot, snapshot, name);
+        }
+        None => {
+            let home = dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")?;
+            let recorded = synced_files(&SyncState::load()?, &original_path(dir));
+            if recorded.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "{} was never synced, nothing to restore / {} 从未同步，没有可恢复的内容",
+                    name,
+                    name
+                ));
+            }
+            let restored = restore_synced(&home, &recorded)?;
+            println!(
+                "↩️  Restored {} file(s) of {} to the last sync / 已将 {} 的 {} 个文件恢复为上次同步的内容",
+                restored, name, name, restored
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_files_of_one_skill() {
+        let mut state = SyncState::default();
+        for path in [".claude/skills/a/SKILL.md", ".claude/skills/a/x.sh", ".claude/skills/ab/SKILL.md"] {
+            state.synced.insert(path.to_string(), "00".to_string());
+        }
+        let files = synced_files(&state, ".claude/skills/a");
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|(path, _)| path.starts_with(".claude/skills/a/")));
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::sync::client::{execute_download, execute_upload, resolve_server, DownloadOptions, UploadOptions};
+use crate::sync::notify::notify_result;
+use crate::sync::state::SyncState;
+use crate::sync::summary;
+use crate::sync::ws;
+
+/// 连续失败时等待时间最多翻到间隔的 2^MAX_BACKOFF_SHIFT 倍
+const MAX_BACKOFF_SHIFT: u32 = 4;
+
+/// 两次同步之间的最长等待时间，退避后的间隔和过大的 --every 都不超过该值
+const MAX_DELAY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// 定时同步参数
+#[derive(Debug, Clone)]
+pub struct ScheduleOptions {
+    /// 同步间隔
+    pub every: Duration,
+    /// 本地 skills 目录
+    pub dir: Option<PathBuf>,
+    /// 每轮上传本地变化（作为同一个业务码的新版本，无变化时跳过）
+    pub push: Option<UploadOptions>,
+    /// 每轮下载该业务码
+    pub pull: Option<(String, DownloadOptions)>,
+    /// 同步失败及恢复时发送桌面通知
+    pub notify: bool,
+    /// 每轮结束时写入本轮结果的 JSON 文件（--summary-json）
+    pub summary_json: Option<PathBuf>,
+}
+
+/// 在间隔上加入 ±10% 的随机抖动，避免多台机器同时请求服务器
+fn with_jitter(interval: Duration) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    // 取 [-1.0, 1.0) 之间的伪随机数
+    let factor = (nanos % 2000) as f64 / 1000.0 - 1.0;
+    interval.mul_f64(1.0 + factor * 0.1)
+}
+
+/// 连续失败 failures 次后的等待时间：按 2 的幂退避，溢出或超过 MAX_DELAY 时取 MAX_DELAY
+fn backoff(every: Duration, failures: u32) -> Duration {
+    every
+        .checked_mul(1 << failures.min(MAX_BACKOFF_SHIFT))
+        .unwrap_or(MAX_DELAY)
+        .min(MAX_DELAY)
+}
+
+/// 执行一轮同步：先上传再下载
+async fn sync_once(server_url: &str, options: &ScheduleOptions) -> Result<()> {
+    if let Some(upload) = &options.push {
+        // 始终推送到同一个业务码：拉取方的业务码不变，也不会产生不断增长的增量链
+        let mut upload = upload.clone();
+        if upload.code.is_none() {
+            upload.code = SyncState::load()?.uploads.get(server_url).map(|r| r.code.clone());
+        }
+        execute_upload(options.dir.clone().into_iter().collect(), vec![server_url.to_string()], upload).await?;
+    }
+    if let Some((code, download)) = &options.pull {
+        execute_download(
+            code.clone(),
+            options.dir.clone(),
+            Some(server_url.to_string()),
+            download.clone(),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// 等待下一轮同步：使用 WebSocket 且拉取业务码时，服务器推送新版本会提前开始下一轮
+async fn wait_next(server_url: &str, options: &ScheduleOptions, delay: Duration) {
+    let Some((code, _)) = options.pull.as_ref().filter(|_| ws::enabled()) else {
+        tokio::time::sleep(delay).await;
+        return;
+    };
+
+    let started = std::time::Instant::now();
+    match tokio::time::timeout(delay, ws::wait_for_update(server_url, code)).await {
+        // 收到推送或等满间隔
+        Ok(Ok(())) | Err(_) => {}
+        Ok(Err(e)) => {
+            println!("⚠️  Update notifications unavailable, polling instead / 无法接收更新推送，改为定时轮询: {:#}", e);
+            tokio::time::sleep(delay.saturating_sub(started.elapsed())).await;
+        }
+    }
+}
+
+/// 执行定时同步命令：常驻运行，按间隔周期性同步，失败时指数退避，直到 Ctrl-C 退出
+pub async fn execute_schedule(server: Option<String>, options: ScheduleOptions) -> Result<()> {
+    // 服务器只选择一次，避免每轮重复检测地理位置
+    let server_url = resolve_server(server).await?;
+    let mut failures: u32 = 0;
+
+    loop {
+        println!(
+            "🔄 Sync started at / 开始同步: {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+        );
+
+        // 每轮单独汇总：常驻运行时不会无限累计，按 Ctrl-C 退出也不会丢失已完成轮次的汇总
+        summary::reset();
+        let result = sync_once(&server_url, &options).await;
+        summary::finish("sync", options.summary_json.as_deref(), result.as_ref().err());
+        // 只在失败和从失败中恢复时通知，避免每轮成功都弹出通知
+        if result.is_err() || failures > 0 {
+            notify_result(options.notify, "Sync", &result);
+        }
+
+        let delay = match result {
+            Ok(()) => {
+                failures = 0;
+                with_jitter(backoff(options.every, 0))
+            }
+            Err(e) => {
+                failures = failures.saturating_add(1);
+                println!("❌ Sync failed / 同步失败 ({}): {:#}", failures, e);
+                with_jitter(backoff(options.every, failures))
+            }
+        };
+
+        println!(
+            "⏰ Next sync in / 下次同步: {:.0}s (Ctrl-C to stop / 按 Ctrl-C 停止)",
+            delay.as_secs_f64()
+        );
+
+        // Ctrl-C 由全局处理器负责（清理进行中的临时归档后退出）
+        wait_next(&server_url, &options, delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_shift_limit() {
+        let every = Duration::from_secs(60);
+        assert_eq!(backoff(every, 0), every);
+        assert_eq!(backoff(every, 1), every * 2);
+        assert_eq!(backoff(every, 4), every * 16);
+        assert_eq!(backoff(every, 100), every * 16);
+    }
+
+    #[test]
+    fn backoff_is_clamped_instead_of_overflowing() {
+        assert_eq!(backoff(Duration::MAX, 3), MAX_DELAY);
+        assert_eq!(backoff(Duration::from_secs(u64::MAX / 2), 0), MAX_DELAY);
+        // 抖动后的等待时间也不会溢出
+        with_jitter(backoff(Duration::MAX, 1));
+    }
+}
@@ -0,0 +1,203 @@
+//! 服务器返回的错误：按约定的错误格式解析出错误码，已知的错误码转换为可操作的提示和不同的退出码，
+//! 而不是把状态码和原始响应（常常是 HTML 错误页）直接打印出来
+//!
+//! 错误响应格式：
+//! ```json
+//! { "error": { "code": "code-not-found", "message": "no such code", "details": { "code": "ABC123" } } }
+//! ```
+
+use reqwest::{Response, StatusCode};
+use serde::Deserialize;
+use std::fmt;
+
+use crate::sync::http;
+
+/// 原始响应最多显示的字符数
+const MAX_RAW_MESSAGE: usize = 200;
+
+/// 服务器错误响应中的 error 字段
+#[derive(Debug, Default, Deserialize)]
+struct ErrorPayload {
+    code: Option<String>,
+    message: Option<String>,
+    #[serde(default)]
+    details: serde_json::Value,
+}
+
+/// 已知的服务器错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// 业务码（或别名）不存在
+    CodeNotFound,
+    /// 业务码已过期
+    Expired,
+    /// 存储或令牌配额已用尽
+    QuotaExceeded,
+    /// 上传内容超过服务器限制
+    TooLarge,
+}
+
+impl ErrorKind {
+    /// 错误响应中的错误码
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "code-not-found" | "not-found" => Some(ErrorKind::CodeNotFound),
+            "expired" | "code-expired" => Some(ErrorKind::Expired),
+            "quota-exceeded" => Some(ErrorKind::QuotaExceeded),
+            "too-large" | "payload-too-large" => Some(ErrorKind::TooLarge),
+            _ => None,
+        }
+    }
+
+    /// 没有错误码时按状态码判断；只有请求针对某个业务码时，404 才表示业务码不存在
+    fn from_status(status: StatusCode, for_code: bool) -> Option<Self> {
+        match status {
+            StatusCode::NOT_FOUND if for_code => Some(ErrorKind::CodeNotFound),
+            StatusCode::GONE => Some(ErrorKind::Expired),
+            StatusCode::INSUFFICIENT_STORAGE => Some(ErrorKind::QuotaExceeded),
+            StatusCode::PAYLOAD_TOO_LARGE => Some(ErrorKind::TooLarge),
+            _ => None,
+        }
+    }
+
+    /// 进程退出码，脚本可据此区分失败原因（其他错误为 1）
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::CodeNotFound => 3,
+            ErrorKind::Expired => 4,
+            ErrorKind::QuotaExceeded => 5,
+            ErrorKind::TooLarge => 6,
+        }
+    }
+}
+
+/// 已识别类别的服务器错误
+#[derive(Debug)]
+pub struct ServerError {
+    kind: ErrorKind,
+    /// 请求针对的业务码
+    code: Option<String>,
+    /// 服务器给出的说明
+    message: Option<String>,
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let code = self.code.as_deref().unwrap_or("");
+        match self.kind {
+            ErrorKind::CodeNotFound => write!(
+                f,
+                "Code not found: {}; check it for typos / 业务码不存在：{}，请检查是否输错",
+                code, code
+            )?,
+            ErrorKind::Expired => write!(
+                f,
+                "Code expired: {}; ask the sender to upload again / 业务码已过期：{}，请让对方重新上传",
+                code, code
+            )?,
+            ErrorKind::QuotaExceeded => write!(
+                f,
+                "Quota exceeded on the server; wait for old codes to expire or ask the server operator for more / 服务器配额已用尽，请等待旧的业务码过期或联系服务器管理员"
+            )?,
+            ErrorKind::TooLarge => write!(
+                f,
+                "Upload too large for the server; split it with --max-part-size or leave out large files with --exclude / 超出服务器上传限制，可使用 --max-part-size 拆分或用 --exclude 排除大文件"
+            )?,
+        }
+        if let Some(message) = &self.message {
+            write!(f, " ({})", message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+/// 错误的退出码：错误链中有已识别的服务器错误时使用其退出码，否则为 1
+pub fn exit_code(error: &anyhow::Error) -> i32 {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ServerError>())
+        .map_or(1, |e| e.kind.exit_code())
+}
+
+/// 根据状态码和响应内容生成错误；code 为请求针对的业务码
+fn classify(action: &str, status: StatusCode, text: &str, code: Option<&str>) -> anyhow::Error {
+    let payload = serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|value| serde_json::from_value::<ErrorPayload>(value["error"].clone()).ok())
+        .unwrap_or_default();
+    let kind = payload
+        .code
+        .as_deref()
+        .and_then(ErrorKind::from_code)
+        .or_else(|| ErrorKind::from_status(status, code.is_some()));
+    if let Some(kind) = kind {
+        let code = code
+            .map(str::to_string)
+            .or_else(|| payload.details["code"].as_str().map(str::to_string));
+        return ServerError { kind, code, message: payload.message }.into();
+    }
+    if let Err(e) = http::check_access(status) {
+        return e;
+    }
+
+    // 未知错误：优先显示服务器的说明，不显示 HTML 错误页
+    let message = match (payload.message, payload.code) {
+        (Some(message), Some(code)) => format!("{}: {}", code, message),
+        (Some(message), None) => message,
+        (None, Some(code)) => code,
+        (None, None) if text.trim_start().starts_with('<') => String::new(),
+        (None, None) => text.trim().chars().take(MAX_RAW_MESSAGE).collect(),
+    };
+    if message.is_empty() {
+        anyhow::anyhow!("{}: {}", action, status)
+    } else {
+        anyhow::anyhow!("{}: {} - {}", action, status, message)
+    }
+}
+
+/// 将失败的响应转换为错误，action 为失败的操作（如 "Download failed / 下载失败"）
+pub async fn from_response(response: Response, action: &str, code: Option<&str>) -> anyhow::Error {
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+    classify(action, status, &text, code)
+}
+
+/// WebSocket 等不经由 HTTP 响应的错误：只有状态码和服务器的说明
+#[cfg_attr(not(feature = "websocket"), allow(dead_code))]
+pub fn from_status(action: &str, status: StatusCode, message: &str, code: Option<&str>) -> anyhow::Error {
+    classify(action, status, message, code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_error_codes_and_statuses() {
+        let kind = |e: anyhow::Error| e.downcast::<ServerError>().ok().map(|e| e.kind);
+
+        let body = r#"{"error":{"code":"expired","message":"expired 2 days ago"}}"#;
+        let error = classify("Download failed", StatusCode::FORBIDDEN, body, Some("ABC123"));
+        assert_eq!(exit_code(&error), 4);
+        assert_eq!(
+            error.to_string(),
+            "Code expired: ABC123; ask the sender to upload again / 业务码已过期：ABC123，请让对方重新上传 (expired 2 days ago)"
+        );
+
+        let body = r#"{"error":{"code":"quota-exceeded"}}"#;
+        assert_eq!(kind(classify("Upload failed", StatusCode::FORBIDDEN, body, None)), Some(ErrorKind::QuotaExceeded));
+        assert_eq!(kind(classify("x", StatusCode::PAYLOAD_TOO_LARGE, "", None)), Some(ErrorKind::TooLarge));
+        assert_eq!(kind(classify("x", StatusCode::NOT_FOUND, "<html>", Some("A"))), Some(ErrorKind::CodeNotFound));
+        // 不针对业务码的 404 不是“业务码不存在”
+        assert_eq!(kind(classify("x", StatusCode::NOT_FOUND, "", None)), None);
+
+        let error = classify("Upload failed", StatusCode::BAD_GATEWAY, "<html><body>502</body></html>", None);
+        assert_eq!(error.to_string(), "Upload failed: 502 Bad Gateway");
+        assert_eq!(exit_code(&error), 1);
+        let body = r#"{"error":{"code":"maintenance","message":"back soon"}}"#;
+        let error = classify("Upload failed", StatusCode::SERVICE_UNAVAILABLE, body, None);
+        assert_eq!(error.to_string(), "Upload failed: 503 Service Unavailable - maintenance: back soon");
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use comfy_table::{presets::UTF8_FULL, ContentArrangement, Table};
+use indicatif::HumanBytes;
+use std::collections::BTreeMap;
+
+use crate::sync::client::{fetch_archive, resolve_server};
+use crate::sync::manifest::{Manifest, ManifestEntry};
+use crate::sync::table::{truncate, DEFAULT_TRUNCATE};
+use crate::sync::temp::TempArchive;
+
+/// 一个远端 skill 的摘要
+#[derive(Default)]
+struct SkillSummary<'a> {
+    /// SKILL.md 的清单条目（带 front matter 信息）
+    entry: Option<&'a ManifestEntry>,
+    files: usize,
+    size: u64,
+}
+
+/// 清单条目所属的 skill 名称：新版清单记录了 skill_name，v1 清单取所在目录名
+fn skill_name(entry: &ManifestEntry) -> String {
+    entry.skill_name.clone().unwrap_or_else(|| {
+        let mut components = entry.original_path.rsplit('/');
+        components.next();
+        components.next().unwrap_or("unknown").to_string()
+    })
+}
+
+/// 按 (来源, skill) 汇总清单中的文件
+fn summarize(manifest: &Manifest) -> BTreeMap<(String, String), SkillSummary<'_>> {
+    let mut skills: BTreeMap<(String, String), SkillSummary> = BTreeMap::new();
+    for entry in &manifest.files {
+        let key = (entry.source.clone().unwrap_or_default(), skill_name(entry));
+        let summary = skills.entry(key).or_default();
+        summary.files += 1;
+        summary.size += entry.size.unwrap_or(0);
+        if entry.description.is_some() {
+            summary.entry = Some(entry);
+        }
+    }
+    skills
+}
+
+/// 执行预览命令：只读取归档清单，列出业务码中每个 skill 的名称、版本、描述和标签，不解压任何文件
+///
+/// 归档会进入下载缓存，随后的 download 不必再次下载
+pub async fn execute_show(code: String, server: Option<String>, jobs: usize, no_cache: bool) -> Result<()> {
+    let server_url = resolve_server(server).await?;
+
+    let archive = TempArchive::new("skills_show", "download");
+    fetch_archive(&code, &server_url, archive.path(), jobs, no_cache).await?;
+    let manifest = Manifest::read_from_archive(archive.path())?.unwrap_or_default();
+    drop(archive);
+
+    let skills = summarize(&manifest);
+    if skills.is_empty() {
+        println!("❌ No skills in {} / {} 中没有 skills", code, code);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "Skill / 名称",
+            "Version / 版本",
+            "Description / 描述",
+            "Tags / 标签",
+            "Source / 来源",
+            "Files / 文件",
+        ]);
+    for ((source, name), summary) in &skills {
+        let entry = summary.entry;
+        // front matter 中的名称与目录名不同时一并显示
+        let title = match entry.and_then(|e| e.name.as_deref()) {
+            Some(declared) if declared != name => format!("{} ({})", name, declared),
+            _ => name.clone(),
+        };
+        table.add_row(vec![
+            title,
+            entry.and_then(|e| e.version.clone()).unwrap_or_default(),
+            truncate(entry.and_then(|e| e.description.as_deref()).unwrap_or_default(), DEFAULT_TRUNCATE),
+            entry.map(|e| e.tags.join(", ")).unwrap_or_default(),
+            source.clone(),
+            format!("{} ({})", summary.files, HumanBytes(summary.size)),
+        ]);
+    }
+
+    let total_size: u64 = skills.values().map(|s| s.size).sum();
+    println!();
+    println!(
+        "📦 {}: {} skills, {} files, {} / {} 个 skill，{} 个文件，{}",
+        code,
+        skills.len(),
+        manifest.files.len(),
+        HumanBytes(total_size),
+        skills.len(),
+        manifest.files.len(),
+        HumanBytes(total_size)
+    );
+    if let Some(created_at) = manifest.created_at {
+        println!(
+            "   Uploaded / 上传于: {} (skills-sync {})",
+            created_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+            manifest.tool_version.as_deref().unwrap_or("?")
+        );
+    }
+    println!("{table}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_files_by_skill() {
+        let manifest = Manifest::from_json(
+            r#"{"version": 2, "files": [
+                {"archive_name": "a.md", "original_path": ".claude/skills/a/SKILL.md", "skill_name": "a", "description": "A", "source": "claude", "sha256": null, "size": 10, "version": "1.0.0", "tags": ["x"]},
+                {"archive_name": "a/run.sh", "original_path": ".claude/skills/a/run.sh", "skill_name": "a", "description": null, "source": "claude", "sha256": null, "size": 5}
+            ]}"#,
+        )
+        .unwrap();
+        let skills = summarize(&manifest);
+        let a = &skills[&("claude".to_string(), "a".to_string())];
+        assert_eq!((a.files, a.size), (2, 15));
+        assert_eq!(a.entry.and_then(|e| e.version.as_deref()), Some("1.0.0"));
+
+        let v1 = Manifest::from_v1("b.md=.codex/skills/b/SKILL.md\n");
+        assert!(summarize(&v1).keys().any(|(_, name)| name == "b"));
+    }
+}
@@ -0,0 +1,260 @@
+use regex::Regex;
+use serde_yaml::{Mapping, Value};
+
+/// 没有任何描述时显示的文字
+pub const NO_DESCRIPTION: &str = "No description";
+
+/// SKILL.md 的 YAML front matter
+///
+/// 宽松解析：单个字段类型不对时只忽略该字段，不影响其他字段
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SkillMetadata {
+    pub name: Option<String>,
+    /// 多行描述合并为一行
+    pub description: Option<String>,
+    /// allowed-tools，列表或逗号分隔的字符串
+    pub allowed_tools: Vec<String>,
+    /// version 或 metadata.version，数字也按字符串保存
+    pub version: Option<String>,
+    /// tags 或 metadata.tags
+    pub tags: Vec<String>,
+    /// sync: false 或 private: true（也可写在 metadata 下）：默认不打包、不导出
+    pub private: bool,
+}
+
+/// 拆分 front matter 与正文：文件以 --- 行开头，到下一个 --- 行结束
+///
+/// 返回 (YAML, 正文)；没有 front matter 时返回 None
+pub fn split_front_matter(content: &str) -> Option<(&str, &str)> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let (opening, rest) = content.strip_prefix("---")?.split_once('\n')?;
+    if !opening.trim().is_empty() {
+        return None;
+    }
+    let end = if rest.starts_with("---") { 0 } else { rest.find("\n---")? + 1 };
+    let body = rest[end + 3..].split_once('\n').map(|(_, b)| b).unwrap_or("");
+    Some((&rest[..end], body))
+}
+
+/// 去掉 front matter，返回正文；没有 front matter 时返回全部内容
+pub fn strip_front_matter(content: &str) -> &str {
+    split_front_matter(content).map(|(_, body)| body).unwrap_or(content)
+}
+
+/// 字符串值；数字和布尔值也转成字符串
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// 字符串列表；也接受逗号分隔的单个字符串
+fn list(value: &Value) -> Vec<String> {
+    match value {
+        Value::Sequence(items) => items.iter().filter_map(scalar).collect(),
+        Value::String(s) => s.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 顶层字段，没有时查找 metadata 下的同名字段
+pub(crate) fn field<'a>(map: &'a Mapping, key: &str) -> Option<&'a Value> {
+    map.get(key)
+        .or_else(|| map.get("metadata").and_then(|m| m.as_mapping()).and_then(|m| m.get(key)))
+}
+
+/// front matter 的 YAML 映射；没有 front matter 或 YAML 无效时返回 None
+pub(crate) fn front_matter(content: &str) -> Option<Mapping> {
+    let (yaml, _) = split_front_matter(content)?;
+    match serde_yaml::from_str(yaml).ok()? {
+        Value::Mapping(map) => Some(map),
+        _ => None,
+    }
+}
+
+/// 解析 SKILL.md 的 front matter；没有 front matter 或 YAML 无效时返回 None
+pub fn parse(content: &str) -> Option<SkillMetadata> {
+    let map = &front_matter(content)?;
+    Some(SkillMetadata {
+        name: map.get("name").and_then(scalar),
+        description: map.get("description").and_then(scalar).map(|d| {
+            // 清理换行符和多余空格
+            d.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ")
+        }),
+        allowed_tools: map.get("allowed-tools").map(list).unwrap_or_default(),
+        version: field(map, "version").and_then(scalar),
+        tags: field(map, "tags").map(list).unwrap_or_default(),
+        private: field(map, "sync").and_then(Value::as_bool) == Some(false)
+            || field(map, "private").and_then(Value::as_bool) == Some(true),
+    })
+}
+
+/// SKILL.md 的描述：优先使用 front matter 中的 description，
+/// 否则依次尝试正文中的 "## Description" 段、"[description]: ..." 和第一行非标题文本
+pub fn description(content: &str) -> String {
+    if let Some(description) = parse(content).and_then(|m| m.description) {
+        return description;
+    }
+    let body = strip_front_matter(content);
+
+    let patterns = [
+        // 匹配 ## Description / ## 描述 部分
+        "##\\s*(?:Description|描述)\\s*\\n\\s*([^\\n]+)",
+        // 匹配 [!description]: ... 格式
+        "\\[!?description\\]:\\s*([^\\n]+)",
+    ];
+    for pattern in patterns {
+        if let Some(desc) = Regex::new(pattern).ok().and_then(|re| re.captures(body)).and_then(|caps| caps.get(1)) {
+            return desc.as_str().trim().to_string();
+        }
+    }
+
+    // 提取第一段非空文本，跳过标题行
+    body.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("---"))
+        .map(str::to_string)
+        .unwrap_or_else(|| NO_DESCRIPTION.to_string())
+}
+
+/// front matter 在 content 中的范围（不含开头和结尾的 --- 行），没有 front matter 时返回 None
+fn front_matter_range(content: &str) -> Option<(usize, usize)> {
+    let rest = content.strip_prefix("---")?;
+    let start = 3 + rest.find('\n')? + 1;
+    let end = start + content[start..].find("\n---")?;
+    Some((start, end))
+}
+
+/// 将 start..end 中缩进为 indented 的第一个 key 字段改为 value，保留缩进、引号和换行符
+fn replace_field(content: &str, (start, end): (usize, usize), key: &str, indented: bool, value: &str) -> Option<String> {
+    let mut offset = start;
+    for line in content[start..end].split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if let Some(old) = trimmed.strip_prefix(key).and_then(|rest| rest.strip_prefix(':')) {
+            if indent.is_empty() != indented {
+                let line_end = if old.ends_with("\r\n") {
+                    "\r\n"
+                } else if old.ends_with('\n') {
+                    "\n"
+                } else {
+                    ""
+                };
+                let quote = match old.trim_start().chars().next() {
+                    Some(q @ ('"' | '\'')) => q.to_string(),
+                    _ => String::new(),
+                };
+                return Some(format!(
+                    "{}{}{}: {}{}{}{}{}",
+                    &content[..offset],
+                    indent,
+                    key,
+                    quote,
+                    value,
+                    quote,
+                    line_end,
+                    &content[offset + line.len()..]
+                ));
+            }
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// 将 front matter 中顶层的 name 字段改为 new_name，没有 front matter 或 name 字段时返回 None
+pub fn set_name(content: &str, new_name: &str) -> Option<String> {
+    replace_field(content, front_matter_range(content)?, "name", false, new_name)
+}
+
+/// 将 front matter 中的 version（或 metadata.version）改为 version，都没有时在 front matter 末尾添加；
+/// 没有 front matter 时返回 None
+pub fn set_version(content: &str, version: &str) -> Option<String> {
+    let (start, end) = front_matter_range(content)?;
+    if let Some(updated) = replace_field(content, (start, end), "version", false, version) {
+        return Some(updated);
+    }
+
+    // metadata 段：metadata: 行之后到下一个顶层字段之前
+    let mut offset = start;
+    let mut metadata = None;
+    for line in content[start..end].split_inclusive('\n') {
+        match metadata {
+            None if line.starts_with("metadata:") => metadata = Some((offset + line.len(), end)),
+            Some((block_start, _)) if !line.trim().is_empty() && !line.starts_with([' ', '\t']) => {
+                metadata = Some((block_start, offset));
+                break;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some(updated) = metadata.and_then(|block| replace_field(content, block, "version", true, version)) {
+        return Some(updated);
+    }
+
+    let line_end = if content[..end].ends_with('\r') { "\r\n" } else { "\n" };
+    let at = end + 1 - line_end.len();
+    Some(format!("{}{}version: {}{}", &content[..at], line_end, version, &content[at..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_full_metadata_leniently() {
+        let content = "---\nname: writer\ndescription: |\n  Writes things.\n  Carefully.\nallowed-tools: Read, Grep\nmetadata:\n  version: 1.2\n  tags: [docs, prose]\n---\n# Writer\n";
+        let metadata = parse(content).unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("writer"));
+        assert_eq!(metadata.description.as_deref(), Some("Writes things. Carefully."));
+        assert_eq!(metadata.allowed_tools, vec!["Read", "Grep"]);
+        assert_eq!(metadata.version.as_deref(), Some("1.2"));
+        assert_eq!(metadata.tags, vec!["docs", "prose"]);
+        assert!(!metadata.private);
+        assert_eq!(strip_front_matter(content), "# Writer\n");
+        assert!(parse("---\nname: x\nsync: false\n---\n").unwrap().private);
+        assert!(parse("---\nname: x\nmetadata:\n  private: true\n---\n").unwrap().private);
+    }
+
+    #[test]
+    fn front_matter_must_open_the_file() {
+        let content = "# Title\n\nSome text\n\n---\nname: x\n---\n";
+        assert_eq!(parse(content), None);
+        assert_eq!(strip_front_matter(content), content);
+        assert_eq!(description(content), "Some text");
+        assert_eq!(description("---\nname: x\n---\n## Description\nFrom the body\n"), "From the body");
+        assert_eq!(description(""), NO_DESCRIPTION);
+    }
+
+    #[test]
+    fn rewrites_only_the_top_level_name() {
+        let content = "---\r\nname: old\r\nmetadata:\r\n  name: nested\r\n---\r\nname: body\r\n";
+        assert_eq!(
+            set_name(content, "new").as_deref(),
+            Some("---\r\nname: new\r\nmetadata:\r\n  name: nested\r\n---\r\nname: body\r\n")
+        );
+        assert_eq!(set_name("---\ndescription: x\n---\nname: body\n", "new"), None);
+        assert_eq!(set_name("name: old\n", "new"), None);
+    }
+
+    #[test]
+    fn sets_the_version_where_it_is_declared() {
+        assert_eq!(
+            set_version("---\nname: a\nversion: \"1.0.0\"\n---\n", "1.0.1").as_deref(),
+            Some("---\nname: a\nversion: \"1.0.1\"\n---\n")
+        );
+        assert_eq!(
+            set_version("---\nmetadata:\n  version: 1.2\ntags: [x]\n---\nversion: body\n", "1.3.0").as_deref(),
+            Some("---\nmetadata:\n  version: 1.3.0\ntags: [x]\n---\nversion: body\n")
+        );
+        assert_eq!(
+            set_version("---\r\nname: a\r\n---\r\n", "0.1.0").as_deref(),
+            Some("---\r\nname: a\r\nversion: 0.1.0\r\n---\r\n")
+        );
+        assert_eq!(set_version("# No front matter\n", "0.1.0"), None);
+    }
+}
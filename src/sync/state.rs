@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sync::locations;
+use crate::sync::paths::normalize_nfc;
+
+/// 当前状态文件格式版本
+///
+/// - 0：没有 version 字段的旧版状态文件，路径键可能未做百分号编码和 NFC 规范化
+/// - 1：路径键与 manifest 中的原始路径格式一致
+pub const STATE_VERSION: u32 = 1;
+
+/// 本地同步状态（~/.local/state/skills-sync/state.json）
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncState {
+    /// 格式版本，旧版状态文件没有该字段（读取为 0）
+    pub version: u32,
+    /// 每个服务器最近一次上传的记录，键为服务器地址
+    pub uploads: BTreeMap<String, UploadRecord>,
+    /// 每个文件最近一次同步（上传或下载）时的内容 SHA256，作为三方合并的基准，键为原始路径
+    pub synced: BTreeMap<String, String>,
+    /// 服务器返回的业务码所有者令牌（服务器地址 -> 业务码 -> 令牌），用于在同一业务码下重新上传
+    pub owner_tokens: BTreeMap<String, BTreeMap<String, String>>,
+    /// 每个 skill 最近一次上传和下载的时间，键为 skill 目录的原始路径
+    pub last_synced: BTreeMap<String, SkillSyncTimes>,
+}
+
+/// 一个 skill 最近一次上传和下载的时间
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillSyncTimes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uploaded: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub downloaded: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl SkillSyncTimes {
+    /// 最近一次同步（上传或下载）的时间
+    pub fn last(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.uploaded.max(self.downloaded)
+    }
+}
+
+/// 一次上传的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadRecord {
+    /// 上传得到的业务码
+    pub code: String,
+    /// 归档 SHA256
+    pub archive_sha256: String,
+    /// 上传时间
+    pub uploaded_at: chrono::DateTime<chrono::Utc>,
+    /// 完整文件集合：原始路径 -> 文件 SHA256
+    pub files: BTreeMap<String, String>,
+    /// 增量链深度：完整上传为 0，每次基于上一次的增量上传加 1
+    #[serde(default)]
+    pub chain_depth: usize,
+}
+
+impl SyncState {
+    /// 状态文件路径
+    pub fn path() -> Result<PathBuf> {
+        Ok(locations::state_dir()?.join("state.json"))
+    }
+
+    /// 读取状态文件，文件不存在时返回空状态；旧版本的状态在内存中升级（保存时写入新格式）
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(SyncState {
+                version: STATE_VERSION,
+                ..Default::default()
+            });
+        }
+
+        let mut state = Self::read(&path)?;
+        state.upgrade();
+        Ok(state)
+    }
+
+    /// 读取指定的状态文件（不升级），拒绝更新版本写入的状态文件
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).context("Failed to read state file / 读取状态文件失败")?;
+        let state: SyncState = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid state file / 状态文件格式错误: {}", path.display()))?;
+        if state.version > STATE_VERSION {
+            return Err(anyhow::anyhow!(
+                "State file version {} is newer than supported ({}), please upgrade skills-sync / 状态文件版本 {} 高于支持的版本（{}），请升级 skills-sync: {}",
+                state.version,
+                STATE_VERSION,
+                state.version,
+                STATE_VERSION,
+                path.display()
+            ));
+        }
+        Ok(state)
+    }
+
+    /// 升级到当前格式版本，返回所做修改的说明（已是最新时为空）
+    pub fn upgrade(&mut self) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.version >= STATE_VERSION {
+            return changes;
+        }
+
+        // v0 -> v1：路径键统一为 manifest 的原始路径格式
+        let mut renamed = normalize_keys(&mut self.synced);
+        for record in self.uploads.values_mut() {
+            renamed += normalize_keys(&mut record.files);
+        }
+        if renamed > 0 {
+            changes.push(format!("normalized {} path key(s) / 规范化了 {} 个路径键", renamed, renamed));
+        }
+        changes.push(format!("state format v{} -> v{} / 状态格式 v{} -> v{}", self.version, STATE_VERSION, self.version, STATE_VERSION));
+        self.version = STATE_VERSION;
+        changes
+    }
+
+    /// 记录 files（原始路径）所属的 skills 刚刚上传
+    pub fn mark_uploaded<'a>(&mut self, files: impl IntoIterator<Item = &'a String>) {
+        let now = chrono::Utc::now();
+        for root in skill_roots(files) {
+            self.last_synced.entry(root).or_default().uploaded = Some(now);
+        }
+    }
+
+    /// 记录 files（原始路径）所属的 skills 刚刚下载
+    pub fn mark_downloaded<'a>(&mut self, files: impl IntoIterator<Item = &'a String>) {
+        let now = chrono::Utc::now();
+        for root in skill_roots(files) {
+            self.last_synced.entry(root).or_default().downloaded = Some(now);
+        }
+    }
+
+    /// 写入状态文件（先写临时文件再重命名，避免中断时损坏）
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, &path).context("Failed to write state file / 写入状态文件失败")?;
+        Ok(())
+    }
+}
+
+/// 原始路径中各 SKILL.md 所在的 skill 目录
+fn skill_roots<'a>(files: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+    files
+        .into_iter()
+        .filter_map(|path| path.strip_suffix("/SKILL.md").or_else(|| path.strip_suffix("/skill.md")))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 将路径键转换为 manifest 的原始路径格式（百分号编码、NFC），返回改变的键数
+fn normalize_keys(map: &mut BTreeMap<String, String>) -> usize {
+    let mut renamed = 0;
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(key, value)| {
+            let normalized = normalize_nfc(&escape_lone_percent(&key));
+            if normalized != key {
+                renamed += 1;
+            }
+            (normalized, value)
+        })
+        .collect();
+    renamed
+}
+
+/// 转义不构成百分号编码的 "%"（旧版未编码的原始路径），已编码的 %XX 保持不变，重复执行结果不变
+fn escape_lone_percent(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut out = String::with_capacity(path.len());
+    for (i, c) in path.char_indices() {
+        let encoded = c == '%'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit);
+        if c == '%' && !encoded {
+            out.push_str("%25");
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrade_normalizes_legacy_path_keys_once() {
+        let mut state: SyncState = serde_json::from_str(
+            r#"{"synced": {".claude/skills/100%/SKILL.md": "a", ".claude/skills/cafe\u0301/SKILL.md": "b", ".claude/skills/x%25y/SKILL.md": "c"}}"#,
+        )
+        .unwrap();
+        assert_eq!(state.version, 0);
+
+        assert!(!state.upgrade().is_empty());
+        assert_eq!(state.version, STATE_VERSION);
+        let keys: Vec<_> = state.synced.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            [".claude/skills/100%25/SKILL.md", ".claude/skills/caf\u{e9}/SKILL.md", ".claude/skills/x%25y/SKILL.md"]
+        );
+        assert!(state.upgrade().is_empty());
+    }
+
+    #[test]
+    fn records_sync_times_per_skill() {
+        let mut state = SyncState::default();
+        let files = [".claude/skills/a/SKILL.md", ".claude/skills/a/run.sh", ".codex/skills/b/skill.md"].map(String::from);
+        state.mark_uploaded(&files);
+        state.mark_downloaded(&files[..2]);
+
+        assert_eq!(state.last_synced.len(), 2);
+        let a = state.last_synced[".claude/skills/a"];
+        assert!(a.uploaded.is_some() && a.downloaded.is_some());
+        assert_eq!(a.last(), a.downloaded.max(a.uploaded));
+        assert_eq!(state.last_synced[".codex/skills/b"].downloaded, None);
+    }
+}
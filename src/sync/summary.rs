@@ -0,0 +1,214 @@
+//! 命令结束时的汇总：扫描、打包、传输的字节数、冲突、保留的归档和业务码等关键结果集中显示，
+//! 不必在各步骤的输出中查找；命令执行过程中各处通过 record 累计。
+//! 常驻运行的 sync --every 每轮结束时显示本轮的汇总并重新开始累计
+//!
+//! 指定 --summary-json 时同样的结果（另含文件列表、各文件的 SHA256、用时和警告）写入 JSON 文件，
+//! 包装 CLI 的脚本无需解析 stdout；sync --every 每轮结束时覆盖写入本轮的结果
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use indicatif::HumanBytes;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static STARTED: Mutex<Option<(Instant, DateTime<Utc>)>> = Mutex::new(None);
+
+static SUMMARY: Mutex<Summary> = Mutex::new(Summary {
+    scanned: 0,
+    packaged: 0,
+    uploaded: 0,
+    downloaded: 0,
+    upload_ms: 0,
+    download_ms: 0,
+    extracted: 0,
+    conflicts: 0,
+    archives_kept: 0,
+    codes: Vec::new(),
+    sha256: Vec::new(),
+    files: Vec::new(),
+    warnings: Vec::new(),
+});
+
+/// 打包或下载的文件及其内容的 SHA256
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRecord {
+    /// 原始路径（相对用户目录）
+    pub path: String,
+    pub sha256: Option<String>,
+}
+
+/// 本次命令累计的结果
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    /// 扫描到的 SKILL.md 数
+    pub scanned: usize,
+    /// 存入归档的文件数
+    pub packaged: usize,
+    /// 上传的字节数
+    pub uploaded: u64,
+    /// 下载的字节数
+    pub downloaded: u64,
+    /// 上传用时（毫秒）
+    pub upload_ms: u64,
+    /// 下载用时（毫秒）
+    pub download_ms: u64,
+    /// 下载后写入磁盘的文件数
+    pub extracted: usize,
+    /// 存在合并冲突的文件数
+    pub conflicts: usize,
+    /// 保留的归档数
+    pub archives_kept: usize,
+    /// 上传得到或下载的业务码
+    pub codes: Vec<String>,
+    /// 上传或下载的归档的 SHA256
+    pub sha256: Vec<String>,
+    /// 打包或下载的文件
+    pub files: Vec<FileRecord>,
+    /// 执行过程中的警告
+    pub warnings: Vec<String>,
+}
+
+/// --summary-json 写入的内容
+#[derive(Serialize)]
+struct Report<'a> {
+    command: &'a str,
+    success: bool,
+    error: Option<String>,
+    started_at: Option<DateTime<Utc>>,
+    duration_ms: u64,
+    #[serde(flatten)]
+    summary: &'a Summary,
+}
+
+impl Summary {
+    /// 要显示的各项（标签, 值），没有发生的项不显示
+    fn lines(&self, elapsed: Duration) -> Vec<(&'static str, String)> {
+        let counts = [
+            ("Skills scanned / 扫描的 skill", self.scanned),
+            ("Files packaged / 打包的文件", self.packaged),
+            ("Files extracted / 写入的文件", self.extracted),
+            ("Conflicts / 冲突", self.conflicts),
+            ("Archives kept / 保留的归档", self.archives_kept),
+            ("Warnings / 警告", self.warnings.len()),
+        ];
+        let mut lines: Vec<_> = counts
+            .into_iter()
+            .filter(|&(_, count)| count > 0)
+            .map(|(label, count)| (label, count.to_string()))
+            .collect();
+        if self.uploaded > 0 {
+            lines.push(("Uploaded / 上传", HumanBytes(self.uploaded).to_string()));
+        }
+        if self.downloaded > 0 {
+            lines.push(("Downloaded / 下载", HumanBytes(self.downloaded).to_string()));
+        }
+        if !self.codes.is_empty() {
+            lines.push(("Code / 业务码", self.codes.join(", ")));
+        }
+        if !lines.is_empty() {
+            lines.push(("Duration / 用时", format!("{:.1}s", elapsed.as_secs_f64())));
+        }
+        lines
+    }
+}
+
+/// 记录命令开始的时间
+pub fn start() {
+    *STARTED.lock().unwrap() = Some((Instant::now(), Utc::now()));
+}
+
+/// 清空已累计的结果并重新计时（sync --every 每轮开始时调用）
+pub fn reset() {
+    *SUMMARY.lock().unwrap() = Summary::default();
+    start();
+}
+
+fn elapsed() -> Duration {
+    STARTED.lock().unwrap().map_or(Duration::ZERO, |(started, _)| started.elapsed())
+}
+
+/// 累计一项结果
+pub fn record(update: impl FnOnce(&mut Summary)) {
+    update(&mut SUMMARY.lock().unwrap());
+}
+
+/// 显示并记录一条警告
+pub fn warn(message: &str) {
+    println!("⚠️  {}", message);
+    record(|s| s.warnings.push(message.to_string()));
+}
+
+/// 显示汇总；命令没有扫描、传输或写入任何内容时不显示
+pub fn print() {
+    let lines = SUMMARY.lock().unwrap().lines(elapsed());
+    if lines.is_empty() {
+        return;
+    }
+    println!();
+    println!("📋 Summary / 汇总");
+    for (label, value) in lines {
+        println!("   {}: {}", label, value);
+    }
+}
+
+/// 显示汇总，指定了 --summary-json 时同时写入该文件（写入失败只提示，不影响命令结果）
+pub fn finish(command: &str, json: Option<&Path>, error: Option<&anyhow::Error>) {
+    print();
+    if let Some(path) = json {
+        if let Err(e) = write_json(path, command, error) {
+            eprintln!("⚠️  {:#}", e);
+        }
+    }
+}
+
+/// 将结果写入 --summary-json 指定的文件；命令失败时同样写入，success 为 false 并附带错误信息
+pub fn write_json(path: &Path, command: &str, error: Option<&anyhow::Error>) -> Result<()> {
+    let started_at = STARTED.lock().unwrap().map(|(_, at)| at);
+    let summary = SUMMARY.lock().unwrap();
+    let report = Report {
+        command,
+        success: error.is_none(),
+        error: error.map(|e| format!("{:#}", e)),
+        started_at,
+        duration_ms: elapsed().as_millis() as u64,
+        summary: &summary,
+    };
+    let json = serde_json::to_string_pretty(&report)?;
+    fs::write(path, json + "\n")
+        .with_context(|| format!("Failed to write summary / 写入结果文件失败: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_only_what_happened() {
+        assert!(Summary::default().lines(Duration::from_secs(3)).is_empty());
+
+        let summary = Summary {
+            scanned: 4,
+            packaged: 6,
+            uploaded: 2048,
+            codes: vec!["ABC123".to_string()],
+            files: vec![FileRecord { path: ".claude/skills/a/SKILL.md".to_string(), sha256: Some("ab".to_string()) }],
+            ..Default::default()
+        };
+        let lines = summary.lines(Duration::from_millis(1500));
+        let labels: Vec<_> = lines.iter().map(|(label, _)| label.split(" / ").next().unwrap()).collect();
+        assert_eq!(labels, ["Skills scanned", "Files packaged", "Uploaded", "Code", "Duration"]);
+        assert_eq!(lines[2].1, "2.00 KiB");
+        assert_eq!(lines[3].1, "ABC123");
+        assert_eq!(lines[4].1, "1.5s");
+
+        let report = Report { command: "upload", success: true, error: None, started_at: None, duration_ms: 1500, summary: &summary };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["codes"][0], "ABC123");
+        assert_eq!(json["files"][0]["sha256"], "ab");
+        assert_eq!(json["duration_ms"], 1500);
+        assert_eq!(json["warnings"].as_array().unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,289 @@
+use clap::ValueEnum;
+use comfy_table::{presets, Cell, ContentArrangement, Table};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::sync::client::SkillInfo;
+use crate::sync::paths::strip_long_prefix;
+
+/// list 表格中描述默认最多显示的字符数
+pub const DEFAULT_TRUNCATE: usize = 100;
+
+/// list 表格中的一列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ListColumn {
+    /// skill 名称 / Skill name
+    Name,
+    /// front matter 中的描述 / Description from the front matter
+    Description,
+    /// 来源目录 / Source directory
+    Source,
+    /// SKILL.md 路径 / Path of SKILL.md
+    Path,
+    /// SKILL.md 大小 / Size of SKILL.md
+    Size,
+    /// 修改时间 / Modification time
+    Modified,
+    /// 标签 / Tags
+    Tags,
+    /// front matter 中的版本 / Version from the front matter
+    Version,
+    /// 最近一次上传的时间 / Time of the last upload
+    Uploaded,
+    /// 最近一次下载的时间 / Time of the last download
+    Downloaded,
+}
+
+impl ListColumn {
+    /// 表头
+    pub fn header(self) -> &'static str {
+        match self {
+            ListColumn::Name => "Name / 名称",
+            ListColumn::Description => "Description / 描述",
+            ListColumn::Source => "Source / 来源",
+            ListColumn::Path => "Path / 路径",
+            ListColumn::Size => "Size / 大小",
+            ListColumn::Modified => "Modified / 修改时间",
+            ListColumn::Tags => "Tags / 标签",
+            ListColumn::Version => "Version / 版本",
+            ListColumn::Uploaded => "Uploaded / 上次上传",
+            ListColumn::Downloaded => "Downloaded / 上次下载",
+        }
+    }
+
+    /// CSV / TSV 清单中的列名
+    pub fn key(self) -> &'static str {
+        match self {
+            ListColumn::Name => "name",
+            ListColumn::Description => "description",
+            ListColumn::Source => "source",
+            ListColumn::Path => "path",
+            ListColumn::Size => "size",
+            ListColumn::Modified => "modified",
+            ListColumn::Tags => "tags",
+            ListColumn::Version => "version",
+            ListColumn::Uploaded => "uploaded",
+            ListColumn::Downloaded => "downloaded",
+        }
+    }
+}
+
+/// comfy-table 的边框样式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TablePreset {
+    #[default]
+    Utf8Full,
+    Utf8Condensed,
+    Utf8BordersOnly,
+    Utf8HorizontalOnly,
+    AsciiFull,
+    AsciiMarkdown,
+    /// 不画边框，只用空格对齐
+    Nothing,
+}
+
+impl TablePreset {
+    fn preset(self) -> &'static str {
+        match self {
+            TablePreset::Utf8Full => presets::UTF8_FULL,
+            TablePreset::Utf8Condensed => presets::UTF8_FULL_CONDENSED,
+            TablePreset::Utf8BordersOnly => presets::UTF8_BORDERS_ONLY,
+            TablePreset::Utf8HorizontalOnly => presets::UTF8_HORIZONTAL_ONLY,
+            TablePreset::AsciiFull => presets::ASCII_FULL,
+            TablePreset::AsciiMarkdown => presets::ASCII_MARKDOWN,
+            TablePreset::Nothing => presets::NOTHING,
+        }
+    }
+}
+
+/// 表头颜色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TableColor {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Grey,
+}
+
+impl From<TableColor> for comfy_table::Color {
+    fn from(color: TableColor) -> Self {
+        match color {
+            TableColor::Red => comfy_table::Color::Red,
+            TableColor::Green => comfy_table::Color::Green,
+            TableColor::Yellow => comfy_table::Color::Yellow,
+            TableColor::Blue => comfy_table::Color::Blue,
+            TableColor::Magenta => comfy_table::Color::Magenta,
+            TableColor::Cyan => comfy_table::Color::Cyan,
+            TableColor::White => comfy_table::Color::White,
+            TableColor::Grey => comfy_table::Color::Grey,
+        }
+    }
+}
+
+/// list 表格的列和样式（config.toml 中的 [list] 段）
+///
+/// 示例：
+/// ```toml
+/// [list]
+/// columns = ["name", "path"]
+/// preset = "utf8-condensed"
+/// header-color = "cyan"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ListStyle {
+    /// 表格显示的列（只影响表格，CSV / TSV 清单不受影响）
+    pub columns: Vec<ListColumn>,
+    /// 边框样式
+    pub preset: TablePreset,
+    /// 表头颜色，未设置时使用终端默认颜色
+    pub header_color: Option<TableColor>,
+}
+
+impl Default for ListStyle {
+    fn default() -> Self {
+        ListStyle {
+            columns: vec![ListColumn::Name, ListColumn::Description, ListColumn::Path],
+            preset: TablePreset::default(),
+            header_color: None,
+        }
+    }
+}
+
+impl ListStyle {
+    /// 按样式创建带表头的空表格
+    pub fn table(&self, columns: &[ListColumn]) -> Table {
+        let mut table = Table::new();
+        table
+            .load_preset(self.preset.preset())
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(columns.iter().map(|column| {
+                let cell = Cell::new(column.header());
+                match self.header_color {
+                    Some(color) => cell.fg(color.into()),
+                    None => cell,
+                }
+            }));
+        table
+    }
+}
+
+/// Path 列的显示方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PathStyle {
+    /// 用户目录下的路径以 ~/ 开头 / Paths under the home directory start with ~/
+    #[default]
+    Home,
+    /// 绝对路径 / Absolute paths
+    Absolute,
+    /// 相对于当前目录的路径 / Paths relative to the current directory
+    Relative,
+}
+
+/// 单元格内容的显示方式
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CellFormat {
+    /// 描述最多显示的字符数，None 表示显示完整描述
+    pub truncate: Option<usize>,
+    /// Path 列的显示方式
+    pub paths: PathStyle,
+}
+
+/// path 相对于 base 的路径；两者没有共同的根（如 Windows 上不同的盘符）时返回 path 本身
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return path.iter().collect();
+    }
+    let mut relative: PathBuf = std::iter::repeat_n(Component::ParentDir, base.len() - common).collect();
+    relative.extend(&path[common..]);
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+    relative
+}
+
+fn display_path(skill: &SkillInfo, style: PathStyle) -> String {
+    let file = strip_long_prefix(&skill.file);
+    match style {
+        PathStyle::Home => skill.path.clone(),
+        PathStyle::Absolute => file.display().to_string(),
+        PathStyle::Relative => match std::env::current_dir() {
+            Ok(cwd) => relative_to(&file, &cwd).display().to_string(),
+            Err(_) => file.display().to_string(),
+        },
+    }
+}
+
+/// 超过 max 个字符时截断并加上省略号
+pub(crate) fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// 时间列的内容（RFC 3339，精确到秒），没有时间时为空
+fn timestamp(time: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    time.map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)).unwrap_or_default()
+}
+
+/// 一个 skill 在某列中的内容
+pub(crate) fn cell(column: ListColumn, source: &str, skill: &SkillInfo, format: CellFormat) -> String {
+    match column {
+        ListColumn::Name => skill.name.clone(),
+        ListColumn::Description => match format.truncate {
+            Some(max) => truncate(&skill.description, max),
+            None => skill.description.clone(),
+        },
+        ListColumn::Source => source.to_string(),
+        ListColumn::Path => display_path(skill, format.paths),
+        ListColumn::Size => skill.size.to_string(),
+        ListColumn::Modified => timestamp(skill.modified),
+        ListColumn::Tags => skill.tags.join(";"),
+        ListColumn::Version => skill.version.clone().unwrap_or_default(),
+        ListColumn::Uploaded => timestamp(skill.synced.uploaded),
+        ListColumn::Downloaded => timestamp(skill.synced.downloaded),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_on_character_boundaries() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly", 7), "exactly");
+        assert_eq!(truncate("描述很长的文本", 2), "描述…");
+    }
+
+    #[test]
+    fn computes_relative_paths() {
+        let file = Path::new("/home/u/.claude/skills/a/SKILL.md");
+        assert_eq!(relative_to(file, Path::new("/home/u")), Path::new(".claude/skills/a/SKILL.md"));
+        assert_eq!(relative_to(file, Path::new("/home/u/work/x")), Path::new("../../.claude/skills/a/SKILL.md"));
+        assert_eq!(relative_to(Path::new("/home/u"), Path::new("/home/u")), Path::new("."));
+    }
+
+    #[test]
+    fn parses_list_style_from_config() {
+        let style: ListStyle = toml::from_str("columns = [\"name\", \"path\"]\npreset = \"ascii-markdown\"\nheader-color = \"cyan\"\n").unwrap();
+        assert_eq!(style.columns, vec![ListColumn::Name, ListColumn::Path]);
+        assert_eq!(style.preset, TablePreset::AsciiMarkdown);
+        assert_eq!(style.header_color, Some(TableColor::Cyan));
+
+        let style: ListStyle = toml::from_str("").unwrap();
+        assert_eq!(style.columns.len(), 3);
+        assert!(toml::from_str::<ListStyle>("preset = \"fancy\"").is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! 可选的匿名使用统计（配置 telemetry = true，默认关闭）：只统计各命令的使用次数和失败的错误类别，
+//! 不包含参数、路径、业务码、服务器地址或错误信息，也不带任何标识用户或设备的 ID
+//!
+//! 统计先累计在状态目录的 usage.json 中，每天最多发送一次，发送成功后清零；
+//! `skills-sync report` 显示的就是下次将发送的内容
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use comfy_table::{presets::UTF8_FULL, Cell, Table};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::sync::cli::Command;
+use crate::sync::http::apply_network;
+use crate::sync::locations;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// 接收统计的地址（项目维护者的服务器，与自建的同步服务器无关）
+const TELEMETRY_URL: &str = "https://www.937453.xyz/api/telemetry";
+
+/// 两次发送之间的最短间隔
+const SEND_INTERVAL: chrono::Duration = chrono::Duration::hours(24);
+
+/// 发送的超时时间，避免网络不通时拖慢命令退出
+const SEND_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 设置是否收集使用统计
+pub fn init(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+/// 当前是否收集使用统计
+pub fn enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
+
+/// 本地累计的使用统计
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Usage {
+    /// 本轮统计的开始时间（上次发送成功或首次统计的时间）
+    since: Option<DateTime<Utc>>,
+    /// 上次尝试发送的时间
+    last_sent: Option<DateTime<Utc>>,
+    /// 命令 -> 使用次数
+    commands: BTreeMap<String, u64>,
+    /// 错误类别 -> 次数
+    errors: BTreeMap<String, u64>,
+}
+
+/// 发送的内容
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    version: &'static str,
+    os: &'static str,
+    since: Option<DateTime<Utc>>,
+    commands: &'a BTreeMap<String, u64>,
+    errors: &'a BTreeMap<String, u64>,
+}
+
+impl Usage {
+    fn path() -> Result<PathBuf> {
+        Ok(locations::state_dir()?.join("usage.json"))
+    }
+
+    /// 读取统计文件，不存在或无法解析时从头开始
+    fn load() -> Result<Self> {
+        let content = match fs::read_to_string(Self::path()?) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Usage::default()),
+            Err(e) => return Err(e).context("Failed to read usage statistics / 读取使用统计失败"),
+        };
+        Ok(serde_json::from_str(&content).unwrap_or_default())
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn payload(&self) -> Payload<'_> {
+        Payload {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            since: self.since,
+            commands: &self.commands,
+            errors: &self.errors,
+        }
+    }
+}
+
+/// 统计用的命令名：子命令名本身（kebab-case），不含任何参数；插件统一记为 plugin，不暴露插件名
+pub fn command_name(command: &Command) -> String {
+    if matches!(command, Command::External(_)) {
+        return "plugin".to_string();
+    }
+    let debug = format!("{:?}", command);
+    let mut name = String::new();
+    for c in debug.chars().take_while(|c| c.is_ascii_alphanumeric()) {
+        if c.is_ascii_uppercase() && !name.is_empty() {
+            name.push('-');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+/// 错误的类别，只取错误链中第一个可识别的底层错误类型，不包含错误信息
+fn error_category(error: &anyhow::Error) -> &'static str {
+    error
+        .chain()
+        .find_map(|cause| {
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                Some(if e.is_timeout() { "timeout" } else { "network" })
+            } else if cause.is::<std::io::Error>() {
+                Some("io")
+            } else if cause.is::<toml::de::Error>() {
+                Some("config")
+            } else if cause.is::<zip::result::ZipError>() {
+                Some("archive")
+            } else if cause.is::<serde_json::Error>() {
+                Some("format")
+            } else {
+                None
+            }
+        })
+        .unwrap_or("other")
+}
+
+/// 记录一次命令的使用（及失败时的错误类别），到期时发送累计的统计；未开启时什么都不做，失败时静默忽略
+pub async fn record(command: &str, error: Option<&anyhow::Error>) {
+    if !enabled() {
+        return;
+    }
+    let Ok(mut usage) = Usage::load() else {
+        return;
+    };
+    let now = Utc::now();
+    usage.since.get_or_insert(now);
+    *usage.commands.entry(command.to_string()).or_default() += 1;
+    if let Some(error) = error {
+        *usage.errors.entry(error_category(error).to_string()).or_default() += 1;
+    }
+
+    // 首次统计时从现在开始计时，满一天后再发送
+    let last_sent = *usage.last_sent.get_or_insert(now);
+    if now - last_sent >= SEND_INTERVAL {
+        usage.last_sent = Some(now);
+        if send(&usage).await.is_ok() {
+            usage.since = Some(now);
+            usage.commands.clear();
+            usage.errors.clear();
+        }
+    }
+    let _ = usage.save();
+}
+
+/// 发送累计的统计；使用不带 API 令牌的普通客户端
+async fn send(usage: &Usage) -> Result<()> {
+    let client = apply_network(reqwest::Client::builder()).timeout(SEND_TIMEOUT).build()?;
+    client
+        .post(TELEMETRY_URL)
+        .json(&usage.payload())
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// 执行报告命令：显示本地累计、下次将发送的使用统计；json 为 true 时输出原样发送的 JSON
+pub fn execute_report(json: bool) -> Result<()> {
+    let usage = Usage::load()?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&usage.payload())?);
+        return Ok(());
+    }
+
+    if enabled() {
+        println!("📊 Anonymous usage statistics are on / 匿名使用统计已开启 (telemetry = true)");
+    } else {
+        println!("📊 Anonymous usage statistics are off / 匿名使用统计未开启 (telemetry = false)");
+    }
+    if usage.commands.is_empty() {
+        println!("   Nothing collected yet / 尚未收集任何数据");
+        return Ok(());
+    }
+    if let Some(since) = usage.since {
+        println!(
+            "   Collected since / 统计开始于: {}",
+            since.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).set_header(vec!["Command / 命令", "Runs / 次数"]);
+    for (command, count) in &usage.commands {
+        table.add_row(vec![Cell::new(command), Cell::new(count)]);
+    }
+    println!("{}", table);
+    if !usage.errors.is_empty() {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL).set_header(vec!["Error category / 错误类别", "Count / 次数"]);
+        for (category, count) in &usage.errors {
+            table.add_row(vec![Cell::new(category), Cell::new(count)]);
+        }
+        println!("{}", table);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_commands_and_categorizes_errors() {
+        assert_eq!(command_name(&Command::Mcp), "mcp");
+        assert_eq!(command_name(&Command::External(vec!["secret-plugin".into()])), "plugin");
+        assert_eq!(
+            command_name(&Command::VerifyArchive { archive: PathBuf::from("/home/me/a.zip") }),
+            "verify-archive"
+        );
+
+        let io = anyhow::Error::new(std::io::Error::other("disk")).context("Failed to write / 写入失败");
+        assert_eq!(error_category(&io), "io");
+        let json = anyhow::Error::new(serde_json::from_str::<u8>("x").unwrap_err());
+        assert_eq!(error_category(&json), "format");
+        assert_eq!(error_category(&anyhow::anyhow!("something else")), "other");
+    }
+}
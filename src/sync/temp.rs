@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::sync::locations;
+
+/// 当前进程中尚未清理的临时归档，Ctrl-C 时统一删除
+static ACTIVE: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// 临时归档文件守卫：离开作用域时删除归档及其派生文件（<归档>.partN、<归档>.baseN、<归档>.parts.json 等）
+///
+/// 无论命令成功、出错返回还是被 Ctrl-C 中断，都不会在临时目录中留下文件
+pub struct TempArchive {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl TempArchive {
+    /// 在私有临时目录（缓存目录下的 tmp）中创建 <prefix>_<进程号>_<随机串>.<ext> 路径（文件本身由调用方写入）
+    ///
+    /// 无法创建该目录时退回系统临时目录，文件名同样不可预测
+    pub fn new(prefix: &str, extension: &str) -> Self {
+        let dir = locations::temp_dir().unwrap_or_else(|_| std::env::temp_dir());
+        let path = dir.join(format!(
+            "{}_{}_{}.{}",
+            prefix,
+            std::process::id(),
+            locations::random_suffix(),
+            extension
+        ));
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.push(path.clone());
+        }
+        TempArchive { path, keep: false }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 下载完成、得知实际格式后改用对应的扩展名（重命名已写入的文件，旧名称的派生文件随之清理）
+    pub fn set_extension(&mut self, extension: &str) -> std::io::Result<()> {
+        let path = self.path.with_extension(extension);
+        if path == self.path {
+            return Ok(());
+        }
+        fs::rename(&self.path, &path)?;
+        remove_derived(&self.path);
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.retain(|p| p != &self.path);
+            active.push(path.clone());
+        }
+        self.path = path;
+        Ok(())
+    }
+
+    /// 保留归档本身（派生文件仍会被清理），返回归档路径
+    pub fn keep(mut self) -> PathBuf {
+        self.keep = true;
+        self.path.clone()
+    }
+}
+
+impl Drop for TempArchive {
+    fn drop(&mut self) {
+        if let Ok(mut active) = ACTIVE.lock() {
+            active.retain(|p| p != &self.path);
+        }
+        if !self.keep {
+            let _ = fs::remove_file(&self.path);
+        }
+        remove_derived(&self.path);
+    }
+}
+
+/// 删除以 "<归档文件名>." 开头的派生文件
+fn remove_derived(path: &Path) {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name().and_then(|n| n.to_str())) else {
+        return;
+    };
+    let prefix = format!("{}.", name);
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        if entry.file_name().to_str().is_some_and(|n| n.starts_with(&prefix)) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// 安装 Ctrl-C 处理：删除所有未清理的临时归档后以 130 退出
+pub fn install_ctrl_c_handler() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        let paths = ACTIVE.lock().map(|a| a.clone()).unwrap_or_default();
+        for path in &paths {
+            let _ = fs::remove_file(path);
+            remove_derived(path);
+        }
+        eprintln!();
+        eprintln!("⚠️  Interrupted, temporary files cleaned / 已中断，临时文件已清理");
+        std::process::exit(130);
+    });
+}
@@ -0,0 +1,472 @@
+//! 可选的终端界面（ui 命令）：按来源列出本地 skills 及同步状态、各服务器最近一次上传，可在界面中上传、下载、查看本地修改和回滚
+//!
+//! 只是现有客户端功能的前端，上传、下载时暂时退出界面，直接复用命令行的输出和进度条。
+//! 需要以 `--features tui` 编译，未启用时使用 ui 命令会报错
+
+/// 本地 skill 相对上次同步的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+enum SyncStatus {
+    /// 所有记录过的文件都与上次同步时一致
+    InSync,
+    /// 有记录过的文件被修改或删除
+    Modified,
+    /// 从未同步过
+    NeverSynced,
+}
+
+/// 根据 skill 目录下记录过的文件（原始路径, SHA256）和本地文件的当前 SHA256 判断同步状态
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+fn sync_status(recorded: &[(String, String)], current: impl Fn(&str) -> Option<String>) -> SyncStatus {
+    if recorded.is_empty() {
+        SyncStatus::NeverSynced
+    } else if recorded.iter().all(|(path, sha256)| current(path).as_ref() == Some(sha256)) {
+        SyncStatus::InSync
+    } else {
+        SyncStatus::Modified
+    }
+}
+
+#[cfg(feature = "tui")]
+pub use dashboard::execute_ui;
+
+#[cfg(feature = "tui")]
+mod dashboard {
+    use anyhow::{Context, Result};
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use ratatui::layout::{Constraint, Direction, Layout};
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+    use ratatui::{DefaultTerminal, Frame};
+    use std::fs;
+    use std::io::IsTerminal;
+    use std::path::{Path, PathBuf};
+
+    use super::{sync_status, SyncStatus};
+    use crate::sync::client::{
+        collect_skills, execute_download_all, execute_upload, resolve_skill_dirs, DownloadOptions, SkillInfo,
+        UploadOptions,
+    };
+    use crate::sync::lock::lock_roots;
+    use crate::sync::merge::load_blob;
+    use crate::sync::parts::file_sha256;
+    use crate::sync::paths::{local_path, original_path};
+    use crate::sync::restore::{restore_synced, synced_files};
+    use crate::sync::state::SyncState;
+
+    /// 列表中的一个本地 skill
+    struct Row {
+        root: PathBuf,
+        source: String,
+        skill: SkillInfo,
+        /// 同步状态中该 skill 目录下的记录（原始路径, SHA256）
+        recorded: Vec<(String, String)>,
+        status: SyncStatus,
+    }
+
+    /// 当前的输入状态
+    enum Mode {
+        Normal,
+        /// 正在输入要下载的业务码
+        Code(String),
+        /// 等待确认回滚
+        ConfirmRollback,
+    }
+
+    struct App {
+        dirs: Vec<PathBuf>,
+        rows: Vec<Row>,
+        list: ListState,
+        /// 每个服务器最近一次上传：服务器, 业务码, 时间, 文件数
+        uploads: Vec<(String, String, String, usize)>,
+        detail: Vec<Line<'static>>,
+        scroll: u16,
+        mode: Mode,
+    }
+
+    /// 上传或下载前离开界面时要执行的操作
+    enum Suspend {
+        Upload(usize),
+        Download(String),
+    }
+
+    fn home() -> Result<PathBuf> {
+        dirs::home_dir().context("Failed to get home directory / 无法获取用户目录")
+    }
+
+    fn current_sha256(home: &Path, path: &str) -> Option<String> {
+        local_path(home, path).ok().and_then(|p| file_sha256(&p).ok())
+    }
+
+    impl App {
+        fn new(dirs: Vec<PathBuf>) -> Result<Self> {
+            let mut app = App {
+                dirs,
+                rows: Vec::new(),
+                list: ListState::default(),
+                uploads: Vec::new(),
+                detail: vec![Line::from("Enter / f: diff against the last sync / 与上次同步比较")],
+                scroll: 0,
+                mode: Mode::Normal,
+            };
+            app.reload()?;
+            Ok(app)
+        }
+
+        /// 重新扫描本地 skills 并读取同步状态
+        fn reload(&mut self) -> Result<()> {
+            let home = home()?;
+            let state = SyncState::load()?;
+            let mut rows = Vec::new();
+            for root in resolve_skill_dirs(self.dirs.clone())? {
+                for (source, skills) in collect_skills(vec![root.clone()])? {
+                    for skill in skills {
+                        let recorded = skill
+                            .file
+                            .parent()
+                            .map(|dir| synced_files(&state, &original_path(dir)))
+                            .unwrap_or_default();
+                        let status = sync_status(&recorded, |path| current_sha256(&home, path));
+                        rows.push(Row {
+                            root: root.clone(),
+                            source: source.clone(),
+                            skill,
+                            recorded,
+                            status,
+                        });
+                    }
+                }
+            }
+            self.rows = rows;
+
+            let mut uploads: Vec<_> = state.uploads.iter().collect();
+            uploads.sort_by_key(|(_, record)| std::cmp::Reverse(record.uploaded_at));
+            self.uploads = uploads
+                .into_iter()
+                .map(|(server, record)| {
+                    (
+                        server.clone(),
+                        record.code.clone(),
+                        record.uploaded_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string(),
+                        record.files.len(),
+                    )
+                })
+                .collect();
+
+            let selected = self.list.selected().unwrap_or(0).min(self.rows.len().saturating_sub(1));
+            self.list.select((!self.rows.is_empty()).then_some(selected));
+            Ok(())
+        }
+
+        fn selected(&self) -> Option<&Row> {
+            self.list.selected().and_then(|i| self.rows.get(i))
+        }
+
+        fn message(&mut self, line: impl Into<Line<'static>>) {
+            self.detail = vec![line.into()];
+            self.scroll = 0;
+        }
+
+        /// 在详情区显示选中 skill 的本地修改（以上次同步的内容为基准）
+        fn show_diff(&mut self) -> Result<()> {
+            let Some(row) = self.selected() else {
+                return Ok(());
+            };
+            let home = home()?;
+            let mut lines = Vec::new();
+            for (path, sha256) in &row.recorded {
+                let local = local_path(&home, path).ok().and_then(|p| fs::read(p).ok());
+                let Some(local) = local else {
+                    lines.push(Line::styled(format!("🗑️  deleted locally / 本地已删除: ~/{}", path), Color::Red));
+                    continue;
+                };
+                if current_sha256(&home, path).as_ref() == Some(sha256) {
+                    continue;
+                }
+                let base = load_blob(sha256).unwrap_or_default();
+                lines.push(Line::styled(format!("~/{}", path), Modifier::BOLD));
+                match (std::str::from_utf8(&base), std::str::from_utf8(&local)) {
+                    (Ok(base), Ok(local)) => {
+                        let patch = diffy::create_patch(base, local).to_string();
+                        for text in patch.lines().skip(2) {
+                            let style = match text.chars().next() {
+                                Some('+') => Style::new().fg(Color::Green),
+                                Some('-') => Style::new().fg(Color::Red),
+                                Some('@') => Style::new().fg(Color::Cyan),
+                                _ => Style::new(),
+                            };
+                            lines.push(Line::styled(text.to_string(), style));
+                        }
+                    }
+                    _ => lines.push(Line::from("  (binary file differs / 二进制文件不同)")),
+                }
+            }
+            if lines.is_empty() {
+                lines.push(Line::from(match row.status {
+                    SyncStatus::NeverSynced => "Never synced, nothing to compare / 从未同步，无可比较的内容",
+                    _ => "No local changes since the last sync / 上次同步后没有本地修改",
+                }));
+            }
+            self.detail = lines;
+            self.scroll = 0;
+            Ok(())
+        }
+
+        /// 把选中 skill 中被修改或删除的文件恢复为上次同步时的内容
+        fn rollback(&mut self) -> Result<()> {
+            let Some(row) = self.selected() else {
+                return Ok(());
+            };
+            let home = home()?;
+            let _lock = lock_roots(std::slice::from_ref(&row.root), "rollback")?;
+            let restored = restore_synced(&home, &row.recorded)?;
+            let name = row.skill.name.clone();
+            self.reload()?;
+            self.message(format!("↩️  Restored {} file(s) of {} / 已恢复 {} 的 {} 个文件", restored, name, name, restored));
+            Ok(())
+        }
+
+        /// 处理一次按键，返回 Err 表示操作失败，Ok(Some) 表示需要离开界面执行上传或下载
+        fn on_key(&mut self, code: KeyCode) -> Result<Option<Suspend>> {
+            match &mut self.mode {
+                Mode::Code(input) => {
+                    match code {
+                        KeyCode::Enter => {
+                            let input = input.trim().to_string();
+                            self.mode = Mode::Normal;
+                            if !input.is_empty() {
+                                return Ok(Some(Suspend::Download(input)));
+                            }
+                        }
+                        KeyCode::Esc => self.mode = Mode::Normal,
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Char(c) => input.push(c),
+                        _ => {}
+                    }
+                    return Ok(None);
+                }
+                Mode::ConfirmRollback => {
+                    self.mode = Mode::Normal;
+                    if code == KeyCode::Char('y') {
+                        self.rollback()?;
+                    } else {
+                        self.message("Rollback cancelled / 已取消回滚");
+                    }
+                    return Ok(None);
+                }
+                Mode::Normal => {}
+            }
+
+            match code {
+                KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::Enter | KeyCode::Char('f') => self.show_diff()?,
+                KeyCode::Char('u') => return Ok(self.list.selected().map(Suspend::Upload)),
+                KeyCode::Char('d') => self.mode = Mode::Code(String::new()),
+                KeyCode::Char('r') => {
+                    if let Some(row) = self.selected() {
+                        if row.status == SyncStatus::Modified {
+                            self.mode = Mode::ConfirmRollback;
+                        } else {
+                            self.message("Nothing to roll back / 没有需要回滚的修改");
+                        }
+                    }
+                }
+                KeyCode::Char('g') => {
+                    self.reload()?;
+                    self.message("Rescanned / 已重新扫描");
+                }
+                _ => {}
+            }
+            Ok(None)
+        }
+
+        fn draw(&mut self, frame: &mut Frame) {
+            let [main, footer] =
+                Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+            let [left, right] = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(main);
+            let [history, detail] =
+                Layout::vertical([Constraint::Length(self.uploads.len().clamp(1, 6) as u16 + 2), Constraint::Min(3)])
+                    .areas(right);
+
+            let items: Vec<ListItem> = self
+                .rows
+                .iter()
+                .map(|row| {
+                    let (mark, color) = match row.status {
+                        SyncStatus::InSync => ("✓", Color::Green),
+                        SyncStatus::Modified => ("✎", Color::Yellow),
+                        SyncStatus::NeverSynced => ("+", Color::Blue),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{} ", mark), color),
+                        Span::raw(row.skill.name.clone()),
+                        Span::styled(format!("  [{}]", row.source), Color::DarkGray),
+                    ]))
+                })
+                .collect();
+            let skills = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(format!(" Local skills / 本地 skills ({}) ", self.rows.len())))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+            frame.render_stateful_widget(skills, left, &mut self.list);
+
+            let uploads: Vec<Line> = if self.uploads.is_empty() {
+                vec![Line::from("No uploads yet / 尚无上传记录")]
+            } else {
+                self.uploads
+                    .iter()
+                    .map(|(server, code, at, files)| Line::from(format!("{}  {}  {} files  {}", code, at, files, server)))
+                    .collect()
+            };
+            frame.render_widget(
+                Paragraph::new(uploads).block(Block::default().borders(Borders::ALL).title(" Last uploads / 最近上传 ")),
+                history,
+            );
+
+            let title = match self.selected() {
+                Some(row) => format!(" {} ", row.skill.path),
+                None => " Details / 详情 ".to_string(),
+            };
+            frame.render_widget(
+                Paragraph::new(self.detail.clone())
+                    .block(Block::default().borders(Borders::ALL).title(title))
+                    .wrap(Wrap { trim: false })
+                    .scroll((self.scroll, 0)),
+                detail,
+            );
+
+            let help = match &self.mode {
+                Mode::Normal => "↑↓ select  Enter/f diff  u upload  d download  r roll back  g rescan  PgUp/PgDn scroll  q quit".to_string(),
+                Mode::Code(input) => format!("Code to download / 要下载的业务码: {}▏ (Enter / Esc)", input),
+                Mode::ConfirmRollback => "Restore the last synced content of this skill? / 恢复该 skill 上次同步的内容？ (y/N)".to_string(),
+            };
+            frame.render_widget(Paragraph::new(help).style(Style::new().add_modifier(Modifier::REVERSED)), footer);
+        }
+    }
+
+    /// 执行界面命令：左侧按来源列出本地 skills 及同步状态，右侧显示各服务器最近一次上传和选中 skill 的本地修改
+    ///
+    /// 上传、下载时暂时退出界面，完成后按回车返回
+    pub async fn execute_ui(
+        dirs: Vec<PathBuf>,
+        server: Option<String>,
+        upload: UploadOptions,
+        download: DownloadOptions,
+    ) -> Result<()> {
+        if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+            return Err(anyhow::anyhow!("ui needs an interactive terminal / ui 需要交互式终端"));
+        }
+        let mut app = App::new(dirs)?;
+        let mut terminal = ratatui::init();
+        let result = run(&mut app, &mut terminal, server, upload, download).await;
+        ratatui::restore();
+        result
+    }
+
+    async fn run(
+        app: &mut App,
+        terminal: &mut DefaultTerminal,
+        server: Option<String>,
+        upload: UploadOptions,
+        download: DownloadOptions,
+    ) -> Result<()> {
+        loop {
+            terminal.draw(|frame| app.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if matches!(app.mode, Mode::Normal) && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                return Ok(());
+            }
+            let suspend = match app.on_key(key.code) {
+                Ok(suspend) => suspend,
+                Err(e) => {
+                    app.message(Line::styled(format!("❌ {:#}", e), Color::Red));
+                    continue;
+                }
+            };
+            let Some(suspend) = suspend else {
+                continue;
+            };
+
+            // 离开界面，让命令行输出和进度条正常显示
+            ratatui::restore();
+            let result = match suspend {
+                Suspend::Upload(index) => {
+                    let row = &app.rows[index];
+                    let options = UploadOptions {
+                        skills: vec![row.skill.name.clone()],
+                        ..upload.clone()
+                    };
+                    execute_upload(vec![row.root.clone()], server.clone().into_iter().collect(), options).await
+                }
+                Suspend::Download(code) => execute_download_all(vec![code], None, server.clone(), download.clone()).await,
+            };
+            if let Err(e) = &result {
+                eprintln!("❌ {:#}", e);
+            }
+            println!();
+            println!("Press Enter to return / 按回车返回");
+            let _ = std::io::stdin().read_line(&mut String::new());
+            *terminal = ratatui::init();
+            app.reload()?;
+            app.message(match result {
+                Ok(()) => Line::styled("✅ Done / 完成", Color::Green),
+                Err(e) => Line::styled(format!("❌ {:#}", e), Color::Red),
+            });
+        }
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+pub use unavailable::execute_ui;
+
+/// 未启用 tui 特性时的占位实现，调用时报错
+#[cfg(not(feature = "tui"))]
+mod unavailable {
+    use anyhow::Result;
+    use std::path::PathBuf;
+
+    use crate::sync::client::{DownloadOptions, UploadOptions};
+
+    pub async fn execute_ui(
+        _dirs: Vec<PathBuf>,
+        _server: Option<String>,
+        _upload: UploadOptions,
+        _download: DownloadOptions,
+    ) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "The terminal UI is not available in this build (rebuild with --features tui) / 当前构建不包含终端界面（请使用 --features tui 重新编译）"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_sync_status() {
+        let recorded = vec![("a/SKILL.md".to_string(), "aa".to_string()), ("a/x.sh".to_string(), "bb".to_string())];
+        assert_eq!(sync_status(&[], |_| None), SyncStatus::NeverSynced);
+        assert_eq!(
+            sync_status(&recorded, |p| Some(if p.ends_with(".md") { "aa" } else { "bb" }.to_string())),
+            SyncStatus::InSync
+        );
+        assert_eq!(
+            sync_status(&recorded, |p| p.ends_with(".md").then(|| "aa".to_string())),
+            SyncStatus::Modified
+        );
+    }
+}
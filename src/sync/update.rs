@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sync::client::{commit_download, part_path, DownloadWriter};
+use crate::sync::http;
+use crate::sync::locations;
+
+/// 查询最新版本的 GitHub API 地址
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/lsk569937453/skills-sync/releases/latest";
+
+/// 覆盖最新版本查询地址的环境变量（镜像或内网发布站点）
+const RELEASE_URL_ENV: &str = "SKILLS_SYNC_RELEASE_URL";
+
+/// 当前版本
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 当前平台对应的发布文件名，与 .github/workflows/build.yaml 中的 name 一致
+const ASSET_NAME: Option<&str> = if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+    Some("skills-sync-x86_64-unknown-linux-musl")
+} else if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+    Some("skills-sync-windows-x86_64.exe")
+} else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+    Some("skills-sync-darwin-x86_64")
+} else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+    Some("skills-sync-darwin-aarch64")
+} else {
+    None
+};
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// 解析 x.y.z 版本号（忽略前缀 v 和 -rc 等后缀），无法解析时返回 None
+pub(crate) fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
+
+/// 访问 GitHub 的客户端：不附带同步服务器的令牌，GitHub API 要求设置 User-Agent
+fn github_client() -> Result<Client> {
+    http::apply_network(Client::builder().user_agent(concat!("skills-sync/", env!("CARGO_PKG_VERSION"))))
+        .build()
+        .context("Failed to create HTTP client / 创建 HTTP 客户端失败")
+}
+
+async fn fetch_latest(client: &Client) -> Result<Release> {
+    let url = std::env::var(RELEASE_URL_ENV).unwrap_or_else(|_| LATEST_RELEASE_URL.to_string());
+    let response = client
+        .get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .context("Failed to check for updates / 检查更新失败")?;
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(anyhow::anyhow!("Failed to check for updates / 检查更新失败: {} - {}", status, error_text));
+    }
+    response.json().await.context("Failed to parse release info / 解析版本信息失败")
+}
+
+/// 流式下载到 <path>.part，返回内容的 SHA256（校验通过后由调用方 commit_download）
+async fn download_to(client: &Client, url: &str, path: &Path) -> Result<String> {
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .context("Download request failed / 下载请求失败")?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Download failed / 下载失败: {} ({})", status, url));
+    }
+    let mut writer = DownloadWriter::create(path)?;
+    if let Some(length) = response.content_length() {
+        http::check_download_size(length)?;
+        writer.set_length(length)?;
+    }
+    while let Some(chunk) = response.chunk().await.context("Failed to read response / 读取响应内容失败")? {
+        writer.write(&chunk)?;
+    }
+    writer.finish()
+}
+
+/// 读取 <发布文件>.sha256 中的校验值（sha256sum 格式，第一列为十六进制摘要）
+async fn fetch_checksum(client: &Client, asset: &Asset) -> Result<String> {
+    let response = client
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .context("Failed to download checksum / 下载校验文件失败")?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow::anyhow!("Failed to download checksum / 下载校验文件失败: {}", status));
+    }
+    let text = response.text().await.context("Failed to download checksum / 下载校验文件失败")?;
+    text.split_whitespace()
+        .next()
+        .filter(|h| h.len() == 64 && h.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(|h| h.to_lowercase())
+        .with_context(|| format!("Invalid checksum file / 校验文件格式无效: {}", asset.name))
+}
+
+/// 用新文件替换当前可执行文件：新文件与可执行文件位于同一目录，重命名是原子的
+#[cfg(not(windows))]
+fn replace_exe(new: &Path, exe: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(exe).map(|m| m.permissions().mode()).unwrap_or(0o755);
+        fs::set_permissions(new, fs::Permissions::from_mode(mode | 0o111))
+            .context("Failed to set permissions / 设置文件权限失败")?;
+    }
+    fs::rename(new, exe).context("Failed to install the new executable / 安装新版本失败")
+}
+
+/// 用新文件替换当前可执行文件：Windows 上无法覆盖运行中的程序，先把它改名为 <程序>.old（下次更新时删除）
+#[cfg(windows)]
+fn replace_exe(new: &Path, exe: &Path) -> Result<()> {
+    let old = exe.with_extension("old");
+    let _ = fs::remove_file(&old);
+    fs::rename(exe, &old).context("Failed to move the current executable aside / 无法移走当前程序")?;
+    if let Err(e) = fs::rename(new, exe) {
+        let _ = fs::rename(&old, exe);
+        return Err(e).context("Failed to install the new executable / 安装新版本失败");
+    }
+    Ok(())
+}
+
+/// 删除守卫：替换失败时清理下载到程序目录中的新文件
+struct PendingFile(PathBuf);
+
+impl Drop for PendingFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+        let _ = fs::remove_file(part_path(&self.0));
+    }
+}
+
+/// 执行自更新命令：查询最新版本，下载当前平台的程序并校验 SHA256，然后替换当前可执行文件
+pub async fn execute_self_update(check: bool, force: bool) -> Result<()> {
+    let client = github_client()?;
+    let release = fetch_latest(&client).await?;
+    let latest = release.tag_name.trim_start_matches('v');
+
+    let newer = match (parse_version(latest), parse_version(CURRENT_VERSION)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => latest != CURRENT_VERSION,
+    };
+    if !newer && !force {
+        println!("✅ Already up to date / 已是最新版本: {}", CURRENT_VERSION);
+        return Ok(());
+    }
+    if check {
+        if newer {
+            println!("🆕 New version available / 有新版本: {} -> {}", CURRENT_VERSION, latest);
+            println!("   Run `skills-sync self-update` to install / 运行 `skills-sync self-update` 安装");
+        } else {
+            println!("✅ Already up to date / 已是最新版本: {}", CURRENT_VERSION);
+        }
+        return Ok(());
+    }
+
+    let asset_name = ASSET_NAME.context(
+        "No prebuilt binary for this platform, please build from source / 当前平台没有预编译程序，请从源码构建",
+    )?;
+    let asset = release
+        .asset(asset_name)
+        .with_context(|| format!("Release {} has no binary / 该版本没有发布程序: {}", latest, asset_name))?;
+    // 没有校验文件时不安装，避免替换为未经校验的程序
+    let checksum_asset = release.asset(&format!("{}.sha256", asset_name)).with_context(|| {
+        format!(
+            "Release {} has no checksum for {}, refusing to install / 该版本没有 {} 的校验文件，拒绝安装",
+            latest, asset_name, asset_name
+        )
+    })?;
+    let expected = fetch_checksum(&client, checksum_asset).await?;
+
+    let exe = std::env::current_exe()
+        .and_then(|p| p.canonicalize())
+        .context("Failed to locate the current executable / 无法定位当前程序")?;
+    let dir = exe.parent().context("Failed to locate the current executable / 无法定位当前程序")?;
+    let file_name = exe.file_name().and_then(|n| n.to_str()).unwrap_or("skills-sync");
+    let pending = PendingFile(dir.join(format!(".{}.{}.new", file_name, locations::random_suffix())));
+
+    println!("⬇️  Downloading / 正在下载: {} {}", asset_name, latest);
+    let actual = download_to(&client, &asset.browser_download_url, &pending.0).await?;
+    if actual != expected {
+        return Err(anyhow::anyhow!(
+            "Integrity check failed: binary SHA256 mismatch / 完整性校验失败: 程序 SHA256 不匹配\n  expected / 期望: {}\n  actual   / 实际: {}",
+            expected,
+            actual
+        ));
+    }
+    println!("🔒 SHA256 verified / 校验通过: {}", actual);
+    commit_download(&pending.0)?;
+
+    replace_exe(&pending.0, &exe)?;
+    println!("✅ Updated / 已更新: {} -> {} ({})", CURRENT_VERSION, latest, exe.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_release_tags() {
+        assert_eq!(parse_version("v0.0.4"), Some((0, 0, 4)));
+        assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("1.2.3-rc.1"), Some((1, 2, 3)));
+        assert_eq!(parse_version("latest"), None);
+        assert!(parse_version("0.1.0") > parse_version("0.0.10"));
+    }
+}
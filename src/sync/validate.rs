@@ -0,0 +1,122 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sync::client::collect_skills;
+use crate::sync::skill;
+
+/// 内置的 front matter 规则：name 和 description 必需，name 只含小写字母、数字和连字符
+const BUILTIN_SCHEMA: &str = r#"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "type": "object",
+  "required": ["name", "description"],
+  "properties": {
+    "name": { "type": "string", "pattern": "^[a-z0-9]+(-[a-z0-9]+)*$", "maxLength": 64 },
+    "description": { "type": "string", "minLength": 1, "maxLength": 1024 },
+    "allowed-tools": { "type": ["string", "array"] },
+    "version": { "type": ["string", "number"] },
+    "tags": { "type": ["string", "array"] },
+    "sync": { "type": "boolean" },
+    "private": { "type": "boolean" },
+    "metadata": { "type": "object" }
+  }
+}"#;
+
+/// 读取用户提供的 JSON Schema，未指定时使用内置规则
+fn load_schema(path: Option<&Path>) -> Result<Value> {
+    let Some(path) = path else {
+        return Ok(serde_json::from_str(BUILTIN_SCHEMA)?);
+    };
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read schema / 读取 schema 失败: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Schema is not valid JSON / schema 不是有效的 JSON: {}", path.display()))
+}
+
+/// 编译 schema
+fn validator(schema: &Value) -> Result<jsonschema::Validator> {
+    jsonschema::validator_for(schema).map_err(|e| anyhow::anyhow!("Invalid JSON Schema / 无效的 JSON Schema: {}", e))
+}
+
+/// 按 schema 检查一个 SKILL.md 的 front matter，返回各项错误（通过时为空）
+fn check(validator: &jsonschema::Validator, content: &str) -> Vec<String> {
+    let Some(front_matter) = skill::front_matter(content) else {
+        return vec!["no front matter or invalid YAML / 没有 front matter 或 YAML 无效".to_string()];
+    };
+    let instance = match serde_json::to_value(&front_matter) {
+        Ok(instance) => instance,
+        Err(e) => return vec![format!("front matter cannot be checked / front matter 无法检查: {}", e)],
+    };
+    validator
+        .iter_errors(&instance)
+        .map(|error| {
+            let path = error.instance_path().to_string();
+            if path.is_empty() {
+                error.to_string()
+            } else {
+                format!("{}: {}", path, error)
+            }
+        })
+        .collect()
+}
+
+/// 执行校验命令：按内置规则或 schema（JSON Schema 文件）检查每个本地 skill 的 front matter，有错误时列出并报错
+pub fn execute_validate(dirs: Vec<PathBuf>, schema: Option<PathBuf>) -> Result<()> {
+    let validator = validator(&load_schema(schema.as_deref())?)?;
+    let mut checked = 0;
+    let mut failed = 0;
+    for (_, skills) in collect_skills(dirs)? {
+        for skill in skills {
+            checked += 1;
+            let content = fs::read_to_string(&skill.file).unwrap_or_default();
+            let errors = check(&validator, &content);
+            if errors.is_empty() {
+                continue;
+            }
+            failed += 1;
+            println!("❌ {}", skill.path);
+            for error in errors {
+                println!("    - {}", error);
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(anyhow::anyhow!(
+            "{} of {} skill(s) failed validation / {} 个 skill 中有 {} 个未通过校验",
+            failed,
+            checked,
+            checked,
+            failed
+        ));
+    }
+    println!("✅ {} skill(s) valid / {} 个 skill 通过校验", checked, checked);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_against_builtin_and_custom_schemas() {
+        let builtin = validator(&load_schema(None).unwrap()).unwrap();
+        assert!(check(&builtin, "---\nname: pdf-tools\ndescription: Reads PDFs\n---\n").is_empty());
+        let errors = check(&builtin, "---\nname: PDF Tools\n---\n");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(check(&builtin, "# no front matter").len(), 1);
+
+        let custom = validator(&serde_json::json!({
+            "type": "object",
+            "required": ["team"],
+            "properties": { "team": { "enum": ["infra", "data"] } }
+        }))
+        .unwrap();
+        assert!(check(&custom, "---\nname: Anything Goes\nteam: data\n---\n").is_empty());
+        let errors = check(&custom, "---\nteam: sales\n---\n");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("/team: "));
+        assert!(validator(&serde_json::json!({ "type": 5 })).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::sync::archive::{self, ArchiveFormat};
+use crate::sync::client::{collect_skills, resolve_skill_dirs};
+use crate::sync::manifest::{Manifest, MANIFEST_JSON, MANIFEST_TXT, UPGRADE_NOTICE};
+use crate::sync::parts::file_sha256;
+use crate::sync::paths::{local_path, long_path, original_path, strip_long_prefix};
+use crate::sync::state::SyncState;
+use crate::sync::update::parse_version;
+
+/// 归档条目名是否可能写到解压目录之外：绝对路径、盘符、反斜杠或 ".." 组件
+fn is_traversal(name: &str) -> bool {
+    name.starts_with('/')
+        || name.contains('\\')
+        || name.as_bytes().get(1) == Some(&b':')
+        || name.split('/').any(|c| c == "..")
+}
+
+/// 归档中一个条目的摘要
+struct EntryDigest {
+    sha256: String,
+    size: u64,
+}
+
+/// 执行归档校验命令：检查本地 zip / tar.zst 归档的清单格式、每个文件的 SHA256 和大小、
+/// 越界路径，以及生成该归档的工具版本是否兼容；发现问题时返回错误
+pub fn execute_verify_archive(path: &Path) -> Result<()> {
+    println!("🔍 Verifying archive / 校验归档: {}", path.display());
+    let format = ArchiveFormat::detect(path)?;
+    println!("   Format / 格式: {}", format.extension());
+
+    let mut problems: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+
+    // 逐个条目计算摘要，不把整个归档读入内存
+    let mut digests: HashMap<String, EntryDigest> = HashMap::new();
+    let mut manifest_json = None;
+    let mut manifest_txt = None;
+    archive::for_each_entry(path, |name, reader| {
+        if is_traversal(name) {
+            problems.push(format!("Path traversal entry / 越界路径条目: {}", name));
+        }
+        let mut hasher = Sha256::new();
+        let mut content = Vec::new();
+        let size = if name == MANIFEST_JSON || name == MANIFEST_TXT {
+            reader.read_to_end(&mut content)? as u64
+        } else {
+            std::io::copy(reader, &mut hasher)?
+        };
+        match name {
+            MANIFEST_JSON => manifest_json = Some(String::from_utf8_lossy(&content).into_owned()),
+            MANIFEST_TXT => manifest_txt = Some(String::from_utf8_lossy(&content).into_owned()),
+            _ => {}
+        }
+        let digest = EntryDigest {
+            sha256: format!("{:x}", hasher.finalize()),
+            size,
+        };
+        if digests.insert(name.to_string(), digest).is_some() {
+            problems.push(format!("Duplicate entry / 重复的条目: {}", name));
+        }
+        Ok(())
+    })?;
+
+    let manifest = match (manifest_json, manifest_txt) {
+        (Some(json), _) => match Manifest::from_json(&json) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                problems.push(format!("{:#}", e));
+                None
+            }
+        },
+        (None, Some(txt)) => {
+            warnings.push("v1 manifest without hashes, file contents cannot be verified / v1 清单没有校验值，无法校验文件内容".to_string());
+            Some(Manifest::from_v1(&txt))
+        }
+        (None, None) => {
+            problems.push("No manifest.json or manifest.txt / 没有 manifest.json 或 manifest.txt".to_string());
+            None
+        }
+    };
+
+    if let Some(manifest) = &manifest {
+        let tool = manifest.tool_version.as_deref().unwrap_or("unknown / 未知");
+        println!("   Manifest / 清单: v{} (skills-sync {})", manifest.version, tool);
+        if let (Some(made_by), Some(current)) = (
+            manifest.tool_version.as_deref().and_then(parse_version),
+            parse_version(env!("CARGO_PKG_VERSION")),
+        ) {
+            if made_by > current {
+                warnings.push(format!(
+                    "Created by a newer skills-sync ({}), consider running self-update / 由更新版本的 skills-sync（{}）生成，建议运行 self-update",
+                    tool, tool
+                ));
+            }
+        }
+        // 更高版本的清单已被 from_json 拒绝
+        println!("   Compatible with this version / 与当前版本兼容: ✅");
+        if let Some(base) = &manifest.base_code {
+            let from_base = manifest.files.iter().filter(|e| e.from_base).count();
+            warnings.push(format!(
+                "Delta archive based on {}: {} file(s) come from the base archive and are not checked / 基于 {} 的增量归档：{} 个文件来自基础归档，未校验",
+                base, from_base, base, from_base
+            ));
+        }
+
+        let home = dirs::home_dir().unwrap_or_else(|| Path::new("/").to_path_buf());
+        let mut checked = 0;
+        let mut listed = HashSet::new();
+        for entry in &manifest.files {
+            if local_path(&home, &entry.original_path).is_err() {
+                problems.push(format!("Unsafe path in manifest / 清单中的路径不安全: {}", entry.original_path));
+            }
+            if entry.from_base {
+                continue;
+            }
+            listed.insert(entry.archive_name.as_str());
+            let Some(digest) = digests.get(&entry.archive_name) else {
+                problems.push(format!("Missing from archive / 归档中缺少: {} ({})", entry.original_path, entry.archive_name));
+                continue;
+            };
+            if entry.sha256.as_ref().is_some_and(|expected| *expected != digest.sha256) {
+                problems.push(format!("SHA256 mismatch / SHA256 不匹配: {}", entry.original_path));
+            } else if entry.size.is_some_and(|expected| expected != digest.size) {
+                problems.push(format!("Size mismatch / 大小不匹配: {}", entry.original_path));
+            } else if entry.sha256.is_some() {
+                checked += 1;
+            }
+        }
+        println!("   Files verified / 已校验文件: {}/{}", checked, manifest.files.len());
+
+        let mut unlisted: Vec<&String> = digests
+            .keys()
+            .filter(|n| ![MANIFEST_JSON, MANIFEST_TXT, UPGRADE_NOTICE].contains(&n.as_str()))
+            .filter(|n| !listed.contains(n.as_str()))
+            .collect();
+        unlisted.sort();
+        for name in unlisted {
+            warnings.push(format!("Entry not listed in manifest, will be ignored / 清单中未列出的条目，解压时忽略: {}", name));
+        }
+    }
+
+    for warning in &warnings {
+        println!("⚠️  {}", warning);
+    }
+    for problem in &problems {
+        println!("❌ {}", problem);
+    }
+    if !problems.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Archive verification failed: {} problem(s) / 归档校验失败：{} 个问题",
+            problems.len(),
+            problems.len()
+        ));
+    }
+    println!("✅ Archive OK / 归档校验通过");
+    Ok(())
+}
+
+/// 本地文件与同步状态的差异
+#[derive(Debug, Default)]
+struct Drift {
+    /// 内容与上次同步时不同
+    modified: Vec<String>,
+    /// 上次同步过但本地已不存在
+    missing: Vec<String>,
+    /// 从未同步过的 skill 或文件
+    extra: Vec<String>,
+}
+
+/// 显示原始路径：用户目录下的路径加 ~/ 前缀
+fn display_path(path: &str) -> String {
+    if path.starts_with('/') || path.as_bytes().get(1) == Some(&b':') {
+        path.to_string()
+    } else {
+        format!("~/{}", path)
+    }
+}
+
+/// 执行校验命令：重新计算已安装 skills 的 SHA256，与同步状态中记录的上次同步内容比较，
+/// 报告被修改、缺失和多出的文件；有差异时返回错误，便于审计脚本判断
+///
+/// 只检查扫描目录下的文件；整目录同步的 skill 还会报告目录中未同步过的文件
+pub fn execute_verify(dirs: Vec<PathBuf>) -> Result<()> {
+    let state = SyncState::load()?;
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Failed to get home directory / 无法获取用户目录"))?;
+    let roots = resolve_skill_dirs(dirs)?;
+    let prefixes: Vec<String> = roots.iter().map(|r| format!("{}/", original_path(r))).collect();
+
+    let mut drift = Drift::default();
+    let recorded: Vec<(&String, &String)> = state
+        .synced
+        .iter()
+        .filter(|(path, _)| prefixes.iter().any(|p| path.starts_with(p)))
+        .collect();
+    for (path, expected) in &recorded {
+        let local = local_path(&home, path)?;
+        if !local.is_file() {
+            drift.missing.push(path.to_string());
+        } else if file_sha256(&local).ok().as_ref() != Some(*expected) {
+            drift.modified.push(path.to_string());
+        }
+    }
+
+    let mut extra = BTreeSet::new();
+    for (_, skills) in collect_skills(roots)? {
+        for skill in skills {
+            let entry = original_path(&skill.file);
+            if !state.synced.contains_key(&entry) {
+                extra.insert(entry);
+                continue;
+            }
+            let Some(dir) = skill.file.parent() else {
+                continue;
+            };
+            let dir_prefix = format!("{}/", original_path(dir));
+            let whole_dir = recorded.iter().any(|(p, _)| p.starts_with(&dir_prefix) && **p != entry);
+            if !whole_dir {
+                continue;
+            }
+            for file in WalkDir::new(long_path(dir)).into_iter().filter_map(|e| e.ok()) {
+                if file.file_type().is_file() {
+                    let path = original_path(&strip_long_prefix(file.path()));
+                    if !state.synced.contains_key(&path) {
+                        extra.insert(path);
+                    }
+                }
+            }
+        }
+    }
+    drift.extra = extra.into_iter().collect();
+
+    println!("🔍 Checked {} synced file(s) / 检查了 {} 个已同步文件", recorded.len(), recorded.len());
+    for path in &drift.modified {
+        println!("✏️  modified / 已修改: {}", display_path(path));
+    }
+    for path in &drift.missing {
+        println!("🗑️  missing  / 已缺失: {}", display_path(path));
+    }
+    for path in &drift.extra {
+        println!("➕ extra    / 未同步: {}", display_path(path));
+    }
+
+    let total = drift.modified.len() + drift.missing.len() + drift.extra.len();
+    if total > 0 {
+        return Err(anyhow::anyhow!(
+            "Drift detected: {} modified, {} missing, {} extra / 发现差异：{} 个已修改，{} 个已缺失，{} 个未同步",
+            drift.modified.len(),
+            drift.missing.len(),
+            drift.extra.len(),
+            drift.modified.len(),
+            drift.missing.len(),
+            drift.extra.len()
+        ));
+    }
+    println!("✅ Installed skills match the last sync / 已安装的 skills 与上次同步一致");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_traversal_entries() {
+        assert!(is_traversal("../evil"));
+        assert!(is_traversal("a/../../evil"));
+        assert!(is_traversal("/etc/passwd"));
+        assert!(is_traversal("C:/Windows/evil"));
+        assert!(is_traversal("a\\..\\evil"));
+        assert!(!is_traversal("skill_0.md"));
+        assert!(!is_traversal("skill_0/..hidden/file"));
+    }
+}
@@ -0,0 +1,397 @@
+//! 可选的 WebSocket 传输：使用 --websocket（或配置 websocket = true）时，归档通过 {server}/sync/ws 以二进制帧传输
+//!
+//! 服务器逐帧确认已收到的字节数，连接中断后从已确认的位置续传；定时同步还可以在同一连接上接收服务器推送的新版本通知。
+//! 其他接口（info、limits、renew 等）仍使用 HTTP。需要以 `--features websocket` 编译，未启用时使用 --websocket 会报错
+
+use std::sync::OnceLock;
+
+/// 是否通过 WebSocket 传输归档，启动时设置一次
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// 设置是否使用 WebSocket 传输：命令行参数或配置文件任一开启即可
+pub fn init(enabled: bool) {
+    let _ = ENABLED.set(enabled);
+}
+
+/// 当前是否使用 WebSocket 传输
+pub fn enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
+
+#[cfg(feature = "websocket")]
+pub use transport::{download, upload, wait_for_update};
+
+#[cfg(not(feature = "websocket"))]
+pub use unavailable::{download, upload, wait_for_update};
+
+/// 未启用 websocket 特性时的占位实现，调用时报错
+#[cfg(not(feature = "websocket"))]
+mod unavailable {
+    use anyhow::Result;
+    use std::path::Path;
+
+    use crate::sync::client::{UploadReply, UploadTarget};
+
+    fn error() -> anyhow::Error {
+        anyhow::anyhow!(
+            "WebSocket transport is not available in this build (rebuild with --features websocket) / 当前构建不支持 WebSocket 传输（请使用 --features websocket 重新编译）"
+        )
+    }
+
+    pub async fn upload(_: &str, _: &Path, _: &str, _: &str, _: UploadTarget<'_>) -> Result<UploadReply> {
+        Err(error())
+    }
+
+    pub async fn download(_: &str, _: &str, _: &Path) -> Result<(String, Option<String>)> {
+        Err(error())
+    }
+
+    pub async fn wait_for_update(_: &str, _: &str) -> Result<()> {
+        Err(error())
+    }
+}
+
+#[cfg(feature = "websocket")]
+mod transport {
+    use anyhow::{Context, Result};
+    use futures_util::{SinkExt, StreamExt};
+    use reqwest::StatusCode;
+    use serde::{Deserialize, Serialize};
+    use std::io::SeekFrom;
+    use std::path::Path;
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::HeaderValue;
+    use tokio_tungstenite::tungstenite::{self, Message};
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+    use crate::sync::client::{DownloadWriter, UploadReply, UploadTarget};
+    use crate::sync::http;
+    use crate::sync::pin;
+    use crate::sync::server_error;
+
+    /// 每个二进制帧携带的数据大小
+    const CHUNK_SIZE: usize = 256 * 1024;
+
+    /// 上传时最多允许多少个未确认的帧
+    const WINDOW: u64 = 8;
+
+    /// 连接中断后的最大续传次数
+    const MAX_RECONNECTS: u32 = 3;
+
+    type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    /// 控制消息（JSON 文本帧）
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Control {
+        /// 客户端：开始上传，服务器回复 ready 及已收到的字节数（同一 sha256 的上传可续传）
+        Upload {
+            file_name: String,
+            size: u64,
+            sha256: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            code: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            alias: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            owner_token: Option<String>,
+        },
+        /// 服务器：可以从 offset 开始发送
+        Ready { offset: u64 },
+        /// 双方：已收到并写入 offset 之前的全部字节
+        Ack { offset: u64 },
+        /// 服务器：上传完成，sha256 为服务器校验过的归档 SHA256
+        Done {
+            code: String,
+            #[serde(default)]
+            owner_token: Option<String>,
+            #[serde(default)]
+            alias: Option<String>,
+            #[serde(default)]
+            sha256: Option<String>,
+        },
+        /// 客户端：从 offset 开始下载业务码（或别名）对应的归档
+        Download { code: String, offset: u64 },
+        /// 服务器：归档总大小和 SHA256，之后发送二进制帧
+        Meta {
+            size: u64,
+            #[serde(default)]
+            sha256: Option<String>,
+        },
+        /// 客户端：订阅业务码的新版本通知
+        Subscribe { codes: Vec<String> },
+        /// 服务器：业务码有了新版本
+        Updated {
+            code: String,
+            #[serde(default)]
+            sha256: Option<String>,
+        },
+        /// 服务器：请求失败，status 与 HTTP 状态码含义相同
+        Error {
+            #[serde(default)]
+            status: Option<u16>,
+            message: String,
+        },
+    }
+
+    /// 连接中断（可以续传），与服务器明确返回的错误区分
+    #[derive(Debug)]
+    struct Disconnected(String);
+
+    impl std::fmt::Display for Disconnected {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Connection lost / 连接中断: {}", self.0)
+        }
+    }
+
+    impl std::error::Error for Disconnected {}
+
+    fn disconnected(e: impl std::fmt::Display) -> anyhow::Error {
+        anyhow::Error::new(Disconnected(e.to_string()))
+    }
+
+    /// 收到的一帧：控制消息或数据
+    enum Frame {
+        Control(Control),
+        Data(Vec<u8>),
+    }
+
+    /// 由服务器地址得到 WebSocket 地址：http -> ws，https -> wss
+    fn ws_url(server_url: &str) -> Result<String> {
+        if http::unix_socket().is_some() {
+            return Err(anyhow::anyhow!(
+                "WebSocket transport does not support unix:// servers / WebSocket 传输不支持 unix:// 服务器"
+            ));
+        }
+        let base = server_url.trim_end_matches('/');
+        let url = if let Some(rest) = base.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = base.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            return Err(anyhow::anyhow!(
+                "WebSocket transport needs an http:// or https:// server / WebSocket 传输需要 http:// 或 https:// 服务器地址: {}",
+                server_url
+            ));
+        };
+        Ok(format!("{}/sync/ws", url))
+    }
+
+    /// 建立连接，设置了 API 令牌时附带 Authorization 头
+    async fn connect(server_url: &str) -> Result<Socket> {
+        let mut request = ws_url(server_url)?
+            .into_client_request()
+            .context("Invalid WebSocket address / 无效的 WebSocket 地址")?;
+        if let Some(value) = http::api_token().and_then(|t| HeaderValue::from_str(&format!("Bearer {}", t)).ok()) {
+            request.headers_mut().insert("authorization", value);
+        }
+        let uri = request.uri().clone();
+
+        match tokio_tungstenite::connect_async(request).await {
+            Ok((socket, _)) => {
+                // wss:// 与 HTTP 请求一样核对服务器证书
+                if let MaybeTlsStream::Rustls(tls) = socket.get_ref() {
+                    if let Some(cert) = tls.get_ref().1.peer_certificates().and_then(|certs| certs.first()) {
+                        pin::check_peer(uri.host().unwrap_or_default(), uri.port_u16().unwrap_or(443), cert)?;
+                    }
+                }
+                Ok(socket)
+            }
+            Err(tungstenite::Error::Http(response)) => {
+                let status = StatusCode::from_u16(response.status().as_u16()).unwrap_or(StatusCode::BAD_GATEWAY);
+                http::check_access(status)?;
+                Err(anyhow::anyhow!(
+                    "Server does not support WebSocket transfers / 服务器不支持 WebSocket 传输: {}",
+                    status
+                ))
+            }
+            Err(e) => Err(disconnected(e)),
+        }
+    }
+
+    async fn send(socket: &mut Socket, control: &Control) -> Result<()> {
+        let text = serde_json::to_string(control)?;
+        socket.send(Message::text(text)).await.map_err(disconnected)
+    }
+
+    /// 读取下一帧，跳过 ping/pong；服务器返回 error 时转换为错误
+    async fn recv(socket: &mut Socket) -> Result<Frame> {
+        loop {
+            let message = socket
+                .next()
+                .await
+                .ok_or_else(|| disconnected("closed by server / 服务器关闭了连接"))?
+                .map_err(disconnected)?;
+            let control = match message {
+                Message::Binary(data) => return Ok(Frame::Data(data.to_vec())),
+                Message::Text(text) => serde_json::from_str::<Control>(text.as_str())
+                    .with_context(|| format!("Invalid message from server / 服务器消息格式错误: {}", text.as_str()))?,
+                Message::Close(_) => return Err(disconnected("closed by server / 服务器关闭了连接")),
+                _ => continue,
+            };
+            if let Control::Error { status, message } = control {
+                if let Some(status) = status.and_then(|s| StatusCode::from_u16(s).ok()) {
+                    return Err(server_error::from_status("WebSocket transfer failed / WebSocket 传输失败", status, &message, None));
+                }
+                return Err(anyhow::anyhow!("WebSocket transfer failed / WebSocket 传输失败: {}", message));
+            }
+            return Ok(Frame::Control(control));
+        }
+    }
+
+    /// 连接中断且未超过重连次数时等待片刻并返回 true（调用方续传），服务器返回的错误不重试
+    async fn should_resume(error: &anyhow::Error, reconnects: &mut u32) -> bool {
+        if !error.is::<Disconnected>() || *reconnects >= MAX_RECONNECTS {
+            return false;
+        }
+        *reconnects += 1;
+        println!(
+            "🔌 {}, resuming ({}/{}) / 正在续传 ({}/{})",
+            error, reconnects, MAX_RECONNECTS, reconnects, MAX_RECONNECTS
+        );
+        tokio::time::sleep(std::time::Duration::from_secs(*reconnects as u64)).await;
+        true
+    }
+
+    /// 上传一次：从服务器确认的位置开始发送，保持最多 WINDOW 个未确认的帧
+    async fn upload_once(server_url: &str, path: &Path, start: &Control, size: u64) -> Result<UploadReply> {
+        let mut socket = connect(server_url).await?;
+        send(&mut socket, start).await?;
+
+        let mut acked = match recv(&mut socket).await? {
+            Frame::Control(Control::Ready { offset }) => offset.min(size),
+            _ => return Err(anyhow::anyhow!("Unexpected reply from server / 服务器响应异常")),
+        };
+        if acked > 0 {
+            println!("⏩ Resuming upload at / 从该位置续传: {}", indicatif::HumanBytes(acked));
+        }
+
+        let mut file = tokio::fs::File::open(path).await?;
+        file.seek(SeekFrom::Start(acked)).await?;
+        let mut sent = acked;
+        loop {
+            while sent < size && sent - acked < WINDOW * CHUNK_SIZE as u64 {
+                let mut data = Vec::with_capacity(CHUNK_SIZE);
+                let read = (&mut file).take(CHUNK_SIZE as u64).read_to_end(&mut data).await?;
+                if read == 0 {
+                    return Err(anyhow::anyhow!("Archive changed during upload / 上传过程中归档被修改"));
+                }
+                socket.send(Message::binary(data)).await.map_err(disconnected)?;
+                sent += read as u64;
+            }
+
+            match recv(&mut socket).await? {
+                Frame::Control(Control::Ack { offset }) => acked = offset.min(sent),
+                Frame::Control(Control::Done { code, owner_token, alias, sha256 }) => {
+                    let _ = socket.close(None).await;
+                    let sha256 = sha256.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+                    return Ok(UploadReply { code, owner_token, alias, sha256 });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 上传文件：服务器逐帧确认，连接中断后从已确认的位置续传
+    pub async fn upload(
+        server_url: &str,
+        path: &Path,
+        file_name: &str,
+        sha256: &str,
+        target: UploadTarget<'_>,
+    ) -> Result<UploadReply> {
+        let size = std::fs::metadata(path)?.len();
+        let start = Control::Upload {
+            file_name: file_name.to_string(),
+            size,
+            sha256: sha256.to_string(),
+            code: target.code.map(str::to_string),
+            alias: target.alias.map(str::to_string),
+            owner_token: target.owner_token.map(str::to_string),
+        };
+        let mut reconnects = 0;
+        loop {
+            match upload_once(server_url, path, &start, size).await {
+                Err(e) if should_resume(&e, &mut reconnects).await => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// 下载一次：从 writer 已写入的位置继续，每收到一帧回复确认
+    async fn download_once(server_url: &str, code: &str, writer: &mut DownloadWriter) -> Result<Option<String>> {
+        let mut socket = connect(server_url).await?;
+        send(
+            &mut socket,
+            &Control::Download {
+                code: code.to_string(),
+                offset: writer.written(),
+            },
+        )
+        .await?;
+
+        let (size, sha256) = match recv(&mut socket).await? {
+            Frame::Control(Control::Meta { size, sha256 }) => (size, sha256),
+            _ => return Err(anyhow::anyhow!("Unexpected reply from server / 服务器响应异常")),
+        };
+        http::check_download_size(size)?;
+        writer.set_length(size)?;
+
+        while writer.written() < size {
+            if let Frame::Data(data) = recv(&mut socket).await? {
+                writer.write(&data)?;
+                send(&mut socket, &Control::Ack { offset: writer.written() }).await?;
+            }
+        }
+        let _ = socket.close(None).await;
+        Ok(sha256.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()))
+    }
+
+    /// 下载归档并逐帧写入 path，连接中断后从已写入的位置续传；返回内容的 SHA256 和服务器提供的 SHA256
+    pub async fn download(server_url: &str, code: &str, path: &Path) -> Result<(String, Option<String>)> {
+        let mut writer = DownloadWriter::create(path)?;
+        let mut reconnects = 0;
+        let sha256 = loop {
+            match download_once(server_url, code, &mut writer).await {
+                Err(e) if should_resume(&e, &mut reconnects).await => continue,
+                result => break result?,
+            }
+        };
+        Ok((writer.finish()?, sha256))
+    }
+
+    /// 订阅业务码并等待服务器推送新版本通知，连接断开时返回错误
+    pub async fn wait_for_update(server_url: &str, code: &str) -> Result<()> {
+        let mut socket = connect(server_url).await?;
+        send(&mut socket, &Control::Subscribe { codes: vec![code.to_string()] }).await?;
+        loop {
+            if let Frame::Control(Control::Updated { code, .. }) = recv(&mut socket).await? {
+                println!("📣 New version pushed by server / 服务器推送了新版本: {}", code);
+                let _ = socket.close(None).await;
+                return Ok(());
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn maps_server_urls_to_websocket_urls() {
+            assert_eq!(ws_url("http://127.0.0.1:8080").unwrap(), "ws://127.0.0.1:8080/sync/ws");
+            assert_eq!(ws_url("https://sync.example.com/").unwrap(), "wss://sync.example.com/sync/ws");
+            assert!(ws_url("grpc://sync.example.com").is_err());
+        }
+
+        #[test]
+        fn control_messages_use_snake_case_tags() {
+            let text = serde_json::to_string(&Control::Ack { offset: 42 }).unwrap();
+            assert_eq!(text, r#"{"type":"ack","offset":42}"#);
+            let control: Control = serde_json::from_str(r#"{"type":"meta","size":7}"#).unwrap();
+            assert!(matches!(control, Control::Meta { size: 7, sha256: None }));
+        }
+    }
+}